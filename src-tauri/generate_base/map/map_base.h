@@ -4,12 +4,22 @@
 #include <stdint.h>
 
 #define MAX_CONNECTIONS 8
+typedef enum {
+    NODE_JUNCTION = 0,
+    NODE_STATION  = 1,
+    NODE_CHARGER  = 2,
+} NODE_TYPE;
 typedef struct {
     uint16_t locate;
     uint32_t length;
+    int16_t  heading;   // degrees, north = 0, clockwise
+    uint8_t  one_way;
 } CONNECT;
 typedef struct LOCATION {
     uint16_t id;
+    uint8_t  type;      // NODE_TYPE
+    int32_t  x;
+    int32_t  y;
     CONNECT connect[MAX_CONNECTIONS];
 } LOCATION;
 
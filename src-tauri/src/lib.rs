@@ -3,7 +3,7 @@ use std::{path::PathBuf, sync::Mutex as SyncMutex};
 use tokio::sync::{Mutex as AsyncMutex};
 use log::LevelFilter;
 use mods::{
-    directory_mod, log_mod, loop_cmd_mod, map_mod, matlab_mod::{self}, mcu_control_mod, mcu_store_mod, plotter_mod::{self}, tauri_test_mod, uart_mod::{self}, uart_packet_mod, wifi_mod::{self}, wifi_packet_mod
+    bootloader_mod, bridge_mod, directory_mod, heartbeat_mod, log_mod, loop_cmd_mod, map_format_mod, map_history_mod, map_mission_mod, map_mod, map_normalize_mod, map_route_mod, map_track_mod, map_validate_mod, matlab_mod::{self}, mcu_control_mod, mcu_store_mod, mqtt_mod, ota_mod, plotter_mod::{self}, tauri_test_mod, teleop_mod, uart_mod::{self}, uart_packet_mod, uart_packet_proc_mod, wifi_discovery_mod, wifi_mod::{self}, wifi_packet_mod, wifi_packet_proc_mod
 };
 
 pub mod mods {
@@ -11,6 +11,7 @@ pub mod mods {
     pub mod tauri_test_mod;
     pub mod directory_mod;
     pub mod loop_cmd_mod;
+    pub mod heartbeat_mod;
     pub mod user_vec_mod;
     pub mod uart_packet_mod;
     pub mod uart_packet_proc_mod;
@@ -18,11 +19,25 @@ pub mod mods {
     pub mod wifi_mod;
     pub mod wifi_packet_mod;
     pub mod wifi_packet_proc_mod;
+    pub mod wifi_tcp_mod;
+    pub mod wifi_discovery_mod;
     pub mod mcu_const;
     pub mod mcu_control_mod;
     pub mod mcu_store_mod;
+    pub mod bridge_mod;
+    pub mod mqtt_mod;
+    pub mod ota_mod;
+    pub mod bootloader_mod;
+    pub mod teleop_mod;
     pub mod plotter_mod;
     pub mod map_mod;
+    pub mod map_validate_mod;
+    pub mod map_route_mod;
+    pub mod map_normalize_mod;
+    pub mod map_history_mod;
+    pub mod map_format_mod;
+    pub mod map_track_mod;
+    pub mod map_mission_mod;
     pub mod matlab_mod;
 }
 
@@ -46,7 +61,20 @@ pub struct GlobalState {
     pub wifi_tcp_transmit_buffer:   AsyncMutex<wifi_packet_mod::WifiTrceBuffer>,
     pub wifi_udp_receive_buffer:    AsyncMutex<wifi_packet_mod::WifiTrceBuffer>,
     pub wifi_udp_transmit_buffer:   AsyncMutex<wifi_packet_mod::WifiTrceBuffer>,
+    pub wifi_rx_stats:              AsyncMutex<wifi_packet_proc_mod::WifiRxStats>,
+    pub wifi_devices:               AsyncMutex<wifi_discovery_mod::DeviceRegistry>,
+    pub wifi_mock_device:           AsyncMutex<wifi_discovery_mod::MockDevice>,
+    pub mcu_link:                   AsyncMutex<mcu_control_mod::McuLinkManager>,
     pub store_datas:                AsyncMutex<mcu_store_mod::DataStore>,
+    pub link_monitor:               AsyncMutex<heartbeat_mod::LinkMonitor>,
+    pub vehicle_tracker:            AsyncMutex<map_track_mod::VehicleTracker>,
+    pub mission_manager:            AsyncMutex<map_mission_mod::MissionManager>,
+    pub bridge_manager:             AsyncMutex<bridge_mod::BridgeManager>,
+    pub mqtt_manager:               AsyncMutex<mqtt_mod::MqttManager>,
+    pub ota_manager:                AsyncMutex<ota_mod::OtaManager>,
+    pub ota_mock_device:            AsyncMutex<ota_mod::OtaMockDevice>,
+    pub bootloader_manager:         AsyncMutex<bootloader_mod::BootloaderManager>,
+    pub teleop:                     AsyncMutex<teleop_mod::TeleopState>,
     pub matlab_engine:              SyncMutex <matlab_mod::MatlabEngine>,
     pub rand_datas:                 AsyncMutex<plotter_mod::ChartRandDatas>,
 }
@@ -64,7 +92,20 @@ pub fn run() {
         wifi_tcp_transmit_buffer:   AsyncMutex::new(wifi_packet_mod::WifiTrceBuffer::new(10, wifi_packet_mod::WIFI_TCP_PACKET_MAX_SIZE)),
         wifi_udp_receive_buffer:    AsyncMutex::new(wifi_packet_mod::WifiTrceBuffer::new(10, wifi_packet_mod::WIFI_UDP_PACKET_MAX_SIZE)),
         wifi_udp_transmit_buffer:   AsyncMutex::new(wifi_packet_mod::WifiTrceBuffer::new(10, wifi_packet_mod::WIFI_UDP_PACKET_MAX_SIZE)),
+        wifi_rx_stats:              AsyncMutex::new(wifi_packet_proc_mod::WifiRxStats::new()),
+        wifi_devices:               AsyncMutex::new(wifi_discovery_mod::DeviceRegistry::new()),
+        wifi_mock_device:           AsyncMutex::new(wifi_discovery_mod::MockDevice::new()),
+        mcu_link:                   AsyncMutex::new(mcu_control_mod::McuLinkManager::new()),
         store_datas:                AsyncMutex::new(mcu_store_mod::DataStore::new(100)),
+        link_monitor:               AsyncMutex::new(heartbeat_mod::LinkMonitor::new()),
+        vehicle_tracker:            AsyncMutex::new(map_track_mod::VehicleTracker::new()),
+        mission_manager:            AsyncMutex::new(map_mission_mod::MissionManager::new()),
+        bridge_manager:             AsyncMutex::new(bridge_mod::BridgeManager::new()),
+        mqtt_manager:               AsyncMutex::new(mqtt_mod::MqttManager::new()),
+        ota_manager:                AsyncMutex::new(ota_mod::OtaManager::new()),
+        ota_mock_device:            AsyncMutex::new(ota_mod::OtaMockDevice::new()),
+        bootloader_manager:         AsyncMutex::new(bootloader_mod::BootloaderManager::new()),
+        teleop:                     AsyncMutex::new(teleop_mod::TeleopState::new()),
         matlab_engine:              SyncMutex ::new(matlab_mod::MatlabEngine::new()),
         rand_datas:                 AsyncMutex::new(plotter_mod::ChartRandDatas::new_rand("temp", "disp", 100)),
     };
@@ -81,11 +122,71 @@ pub fn run() {
             mcu_control_mod::cmd_send_spd_stop,
             mcu_control_mod::cmd_send_spd_once,
             mcu_control_mod::cmd_send_spd_start,
+            mcu_control_mod::cmd_send_data_select,
+            mcu_control_mod::cmd_motor_set_speed,
+            mcu_control_mod::cmd_motor_set_direction,
+            mcu_control_mod::cmd_motor_tracking,
+            mcu_control_mod::cmd_vehicle_control,
+            mcu_control_mod::cmd_vehicle_emergency_stop,
+            mcu_control_mod::cmd_mcu_link_set,
+            mcu_control_mod::cmd_mcu_link_get,
+            heartbeat_mod::cmd_mcu_link_status,
+            bridge_mod::cmd_bridge_start,
+            bridge_mod::cmd_bridge_stop,
+            bridge_mod::cmd_bridge_status,
+            mqtt_mod::cmd_mqtt_start,
+            mqtt_mod::cmd_mqtt_stop,
+            mqtt_mod::cmd_mqtt_status,
+            ota_mod::cmd_ota_start,
+            ota_mod::cmd_ota_cancel,
+            ota_mod::cmd_ota_status,
+            ota_mod::cmd_ota_mock_start,
+            ota_mod::cmd_ota_mock_stop,
+            bootloader_mod::cmd_bootloader_flash,
+            bootloader_mod::cmd_bootloader_status,
+            teleop_mod::cmd_teleop_start,
+            teleop_mod::cmd_teleop_stop,
+            teleop_mod::cmd_teleop_input,
+            teleop_mod::cmd_teleop_status,
+            wifi_mod::cmd_wifi_interfaces,
             wifi_mod::cmd_wifi_start,
+            wifi_mod::cmd_wifi_stop,
+            wifi_mod::cmd_wifi_restart,
+            wifi_mod::cmd_wifi_status,
             wifi_mod::cmd_wifi_transmie,
+            wifi_mod::cmd_wifi_peers,
+            wifi_mod::cmd_wifi_disconnect,
+            wifi_packet_proc_mod::cmd_wifi_rx_stats,
+            wifi_packet_proc_mod::cmd_wifi_rx_stats_reset,
+            wifi_discovery_mod::cmd_wifi_discover,
+            wifi_discovery_mod::cmd_wifi_devices,
+            wifi_discovery_mod::cmd_wifi_device_remove,
+            wifi_discovery_mod::cmd_wifi_mock_device_start,
+            wifi_discovery_mod::cmd_wifi_mock_device_stop,
             map_mod::map_load,
             map_mod::map_save,
+            map_validate_mod::map_validate,
+            map_normalize_mod::map_normalize,
+            map_history_mod::map_history_list,
+            map_history_mod::map_history_diff,
+            map_history_mod::map_history_restore,
+            map_history_mod::map_history_undo,
+            map_format_mod::map_import,
+            map_format_mod::map_export,
+            map_route_mod::map_route,
+            map_route_mod::map_distance_table,
+            map_track_mod::map_vehicle_location,
+            map_mission_mod::map_mission_list,
+            map_mission_mod::map_mission_save,
+            map_mission_mod::map_mission_delete,
+            map_mission_mod::map_mission_plan,
+            map_mission_mod::map_mission_start,
+            map_mission_mod::map_mission_pause,
+            map_mission_mod::map_mission_resume,
+            map_mission_mod::map_mission_abort,
+            map_mission_mod::map_mission_status,
             plotter_mod::chart_generate,
+            plotter_mod::map_chart,
         ])
         .setup(|app| {
             setup(app.handle().clone());
@@ -96,5 +197,8 @@ pub fn run() {
 }
 fn setup(app: AppHandle) {
     directory_mod::setup(app.clone());
+    if let Err(e) = uart_packet_proc_mod::gen_h_file(app.clone()) {
+        log::error!("Generate mcu_const.h failed: {}", e);
+    }
     loop_cmd_mod::setup(app);
 }
@@ -0,0 +1,742 @@
+use std::{error::Error, fs, path::Path, time::Duration};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt}, sync::watch, task::JoinHandle, time::timeout};
+use tokio_serial::{Parity, SerialPort};
+use crate::GlobalState;
+
+// STM32 系統記憶體開機載入程式協定（AN3155），UART 需為 8E1
+// 指令：[cmd, !cmd]，位址：[addr u32 大端序, XOR 檢查碼]，每一步回覆 ACK 或 NACK
+// STM32 system memory bootloader protocol (AN3155), UART must be 8E1
+// Command: [cmd, !cmd], address: [addr u32 big-endian, XOR checksum], every step answers ACK or NACK
+const BL_SYNC: u8 = 0x7F;
+const BL_ACK: u8 = 0x79;
+const BL_NACK: u8 = 0x1F;
+const BL_CMD_GET: u8 = 0x00;
+const BL_CMD_GET_ID: u8 = 0x02;
+const BL_CMD_READ_MEMORY: u8 = 0x11;
+const BL_CMD_GO: u8 = 0x21;
+const BL_CMD_WRITE_MEMORY: u8 = 0x31;
+const BL_CMD_ERASE: u8 = 0x43;
+const BL_CMD_EXTENDED_ERASE: u8 = 0x44;
+
+/// 單次讀寫記憶體的最大長度 <br>
+/// Maximum length of one memory read / write
+const BL_BLOCK_SIZE: usize = 256;
+const BL_ACK_TIMEOUT_MS: u64 = 1000;
+/// 整片抹除需較長時間 <br>
+/// Mass erase takes longer
+const BL_ERASE_TIMEOUT_MS: u64 = 30000;
+
+/// bin 檔預設載入位址（內部快閃記憶體起點） <br>
+/// Default load address of bin files (start of internal flash)
+pub const BL_FLASH_BASE: u32 = 0x0800_0000;
+
+/// 模擬開機載入程式的快閃記憶體大小與頁大小 <br>
+/// Flash size and page size of the simulated bootloader
+const SIM_FLASH_SIZE: usize = 128 * 1024;
+const SIM_PAGE_SIZE: usize = 2 * 1024;
+const SIM_PRODUCT_ID: u16 = 0x0410;
+
+type BootResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+fn xor(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, b| sum ^ b)
+}
+
+/// 位址加上 XOR 檢查碼 <br>
+/// Address followed by its XOR checksum
+fn addr_bytes(addr: u32) -> [u8; 5] {
+    let [a, b, c, d] = addr.to_be_bytes();
+    [a, b, c, d, a ^ b ^ c ^ d]
+}
+
+/// 連續的韌體區段 <br>
+/// Contiguous firmware segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareSegment {
+    pub addr: u32,
+    pub data: Vec<u8>,
+}
+
+/// 韌體映像，由 Intel HEX 或 bin 檔解析 <br>
+/// Firmware image parsed from Intel HEX or bin files
+#[derive(Debug, Clone, Default)]
+pub struct FirmwareImage {
+    segments: Vec<FirmwareSegment>,
+}
+impl FirmwareImage {
+    /// bin 檔：整個檔案放在 base 位址 <br>
+    /// Bin file: the whole file is placed at base
+    pub fn from_bin(data: Vec<u8>, base: u32) -> BootResult<Self> {
+        if data.is_empty() {
+            return Err("Firmware file is empty".into());
+        }
+        Ok(Self { segments: vec![FirmwareSegment { addr: base, data }] })
+    }
+
+    /// Intel HEX：支援延伸線性與延伸區段位址，相鄰資料合併為區段 <br>
+    /// Intel HEX: supports extended linear and segment addresses, adjacent data is merged into segments
+    pub fn from_hex(text: &str) -> BootResult<Self> {
+        let mut image = Self::default();
+        let mut upper = 0u32;
+        for record in ihex::Reader::new(text) {
+            match record? {
+                ihex::Record::Data { offset, value } => image.push(upper + offset as u32, &value),
+                ihex::Record::ExtendedLinearAddress(high) => upper = (high as u32) << 16,
+                ihex::Record::ExtendedSegmentAddress(segment) => upper = (segment as u32) << 4,
+                ihex::Record::EndOfFile => break,
+                ihex::Record::StartLinearAddress(_) | ihex::Record::StartSegmentAddress { .. } => {}
+            }
+        }
+        if image.segments.is_empty() {
+            return Err("Firmware file has no data".into());
+        }
+        image.segments.sort_by_key(|s| s.addr);
+        for pair in image.segments.windows(2) {
+            if pair[0].addr as usize + pair[0].data.len() > pair[1].addr as usize {
+                return Err(format!("Firmware segments overlap at {:#010x}", pair[1].addr).into());
+            }
+        }
+        Ok(image)
+    }
+
+    /// 依副檔名載入：`.hex`／`.ihex` 為 Intel HEX，其餘視為 bin <br>
+    /// Loads by extension: `.hex` / `.ihex` are Intel HEX, anything else is bin
+    pub fn load(path: &Path, base: u32) -> BootResult<Self> {
+        let is_hex = path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("hex") || ext.eq_ignore_ascii_case("ihex"));
+        if is_hex {
+            Self::from_hex(&fs::read_to_string(path)?)
+        } else {
+            Self::from_bin(fs::read(path)?, base)
+        }
+    }
+
+    fn push(&mut self, addr: u32, data: &[u8]) {
+        match self.segments.last_mut() {
+            Some(last) if last.addr as usize + last.data.len() == addr as usize => last.data.extend_from_slice(data),
+            _ => self.segments.push(FirmwareSegment { addr, data: data.to_vec() }),
+        }
+    }
+
+    pub fn segments(&self) -> &[FirmwareSegment] {
+        &self.segments
+    }
+
+    /// 總位元組數 <br>
+    /// Total number of bytes
+    pub fn size(&self) -> usize {
+        self.segments.iter().map(|s| s.data.len()).sum()
+    }
+
+    /// 最低位址，Go 指令由此開始執行 <br>
+    /// Lowest address, where the Go command starts execution
+    pub fn start_addr(&self) -> Option<u32> {
+        self.segments.first().map(|s| s.addr)
+    }
+
+    /// 依 BL_BLOCK_SIZE 切塊並附上各塊位址，位址超出 32 位元時回傳錯誤 <br>
+    /// Splits into BL_BLOCK_SIZE blocks with their addresses, fails when an address does not fit in 32 bits
+    fn blocks(&self) -> BootResult<Vec<(u32, &[u8])>> {
+        let mut blocks = Vec::new();
+        for segment in &self.segments {
+            for (i, block) in segment.data.chunks(BL_BLOCK_SIZE).enumerate() {
+                let addr = u32::try_from(i * BL_BLOCK_SIZE).ok()
+                    .and_then(|offset| segment.addr.checked_add(offset))
+                    .filter(|addr| addr.checked_add(block.len() as u32 - 1).is_some())
+                    .ok_or_else(|| format!("Firmware block {} of segment {:#010x} exceeds the 32-bit address space", i, segment.addr))?;
+                blocks.push((addr, block));
+            }
+        }
+        Ok(blocks)
+    }
+}
+
+/// AN3155 開機載入程式連線 <br>
+/// AN3155 bootloader connection
+pub struct Stm32Bootloader<S> {
+    stream: S,
+    version: u8,
+    commands: Vec<u8>,
+}
+impl<S: AsyncRead + AsyncWrite + Unpin> Stm32Bootloader<S> {
+    /// 送出同步位元組並讀取支援的指令；已同步的裝置會回覆 NACK，同樣視為成功 <br>
+    /// Sends the sync byte and reads the supported commands; an already synced device answers NACK, which also counts as success
+    pub async fn connect(stream: S) -> BootResult<Self> {
+        let mut bootloader = Self { stream, version: 0, commands: Vec::new() };
+        bootloader.stream.write_all(&[BL_SYNC]).await?;
+        match bootloader.byte(BL_ACK_TIMEOUT_MS).await? {
+            BL_ACK | BL_NACK => {}
+            other => return Err(format!("Bootloader sync got {:#04x}", other).into()),
+        }
+        bootloader.get().await?;
+        Ok(bootloader)
+    }
+
+    /// 開機載入程式版本，例：`3.1` <br>
+    /// Bootloader version, e.g. `3.1`
+    pub fn version(&self) -> String {
+        format!("{}.{}", self.version >> 4, self.version & 0x0F)
+    }
+
+    async fn byte(&mut self, timeout_ms: u64) -> BootResult<u8> {
+        timeout(Duration::from_millis(timeout_ms), self.stream.read_u8()).await
+            .map_err(|_| "Bootloader reply timed out")?
+            .map_err(|e| e.into())
+    }
+
+    async fn bytes(&mut self, len: usize) -> BootResult<Vec<u8>> {
+        let mut buffer = vec![0u8; len];
+        timeout(Duration::from_millis(BL_ACK_TIMEOUT_MS), self.stream.read_exact(&mut buffer)).await
+            .map_err(|_| "Bootloader reply timed out")??;
+        Ok(buffer)
+    }
+
+    async fn ack(&mut self, timeout_ms: u64) -> BootResult<()> {
+        match self.byte(timeout_ms).await? {
+            BL_ACK => Ok(()),
+            BL_NACK => Err("Bootloader answered NACK".into()),
+            other => Err(format!("Bootloader answered {:#04x}", other).into()),
+        }
+    }
+
+    async fn command(&mut self, cmd: u8) -> BootResult<()> {
+        if !self.commands.is_empty() && !self.commands.contains(&cmd) {
+            return Err(format!("Bootloader does not support command {:#04x}", cmd).into());
+        }
+        self.stream.write_all(&[cmd, !cmd]).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await
+            .map_err(|e| format!("Command {:#04x}: {}", cmd, e).into())
+    }
+
+    async fn get(&mut self) -> BootResult<()> {
+        self.command(BL_CMD_GET).await?;
+        let len = self.byte(BL_ACK_TIMEOUT_MS).await? as usize;
+        let data = self.bytes(len + 1).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await?;
+        self.version = data[0];
+        self.commands = data[1..].to_vec();
+        Ok(())
+    }
+
+    /// 讀取產品 ID <br>
+    /// Reads the product id
+    pub async fn get_id(&mut self) -> BootResult<u16> {
+        self.command(BL_CMD_GET_ID).await?;
+        let len = self.byte(BL_ACK_TIMEOUT_MS).await? as usize;
+        let data = self.bytes(len + 1).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await?;
+        match data[..] {
+            [high, low, ..] => Ok(u16::from_be_bytes([high, low])),
+            [low] => Ok(low as u16),
+            [] => Err("Bootloader returned an empty id".into()),
+        }
+    }
+
+    /// 讀取記憶體，長度 1..=256 <br>
+    /// Reads memory, length 1..=256
+    pub async fn read_memory(&mut self, addr: u32, len: usize) -> BootResult<Vec<u8>> {
+        if len == 0 || len > BL_BLOCK_SIZE {
+            return Err(format!("Read length must be 1..={}: {}", BL_BLOCK_SIZE, len).into());
+        }
+        self.command(BL_CMD_READ_MEMORY).await?;
+        self.stream.write_all(&addr_bytes(addr)).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await?;
+        let n = (len - 1) as u8;
+        self.stream.write_all(&[n, !n]).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await?;
+        self.bytes(len).await
+    }
+
+    /// 寫入記憶體，長度 1..=256，不足 4 的倍數時補 0xFF <br>
+    /// Writes memory, length 1..=256, padded with 0xFF to a multiple of 4
+    pub async fn write_memory(&mut self, addr: u32, data: &[u8]) -> BootResult<()> {
+        if data.is_empty() || data.len() > BL_BLOCK_SIZE {
+            return Err(format!("Write length must be 1..={}: {}", BL_BLOCK_SIZE, data.len()).into());
+        }
+        self.command(BL_CMD_WRITE_MEMORY).await?;
+        self.stream.write_all(&addr_bytes(addr)).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await?;
+        let mut frame = Vec::with_capacity(BL_BLOCK_SIZE + 2);
+        frame.push(0);
+        frame.extend_from_slice(data);
+        frame.resize(1 + data.len().next_multiple_of(4), 0xFF);
+        frame[0] = (frame.len() - 2) as u8;
+        frame.push(xor(&frame));
+        self.stream.write_all(&frame).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await
+            .map_err(|e| format!("Write {:#010x}: {}", addr, e).into())
+    }
+
+    /// 整片抹除，支援時使用延伸抹除指令 <br>
+    /// Mass erase, using the extended erase command when supported
+    pub async fn erase_all(&mut self) -> BootResult<()> {
+        if self.commands.contains(&BL_CMD_EXTENDED_ERASE) {
+            self.command(BL_CMD_EXTENDED_ERASE).await?;
+            self.stream.write_all(&[0xFF, 0xFF, 0x00]).await?;
+        } else {
+            self.command(BL_CMD_ERASE).await?;
+            self.stream.write_all(&[0xFF, 0x00]).await?;
+        }
+        self.ack(BL_ERASE_TIMEOUT_MS).await
+            .map_err(|e| format!("Erase: {}", e).into())
+    }
+
+    /// 跳至位址執行 <br>
+    /// Jumps to the address
+    pub async fn go(&mut self, addr: u32) -> BootResult<()> {
+        self.command(BL_CMD_GO).await?;
+        self.stream.write_all(&addr_bytes(addr)).await?;
+        self.ack(BL_ACK_TIMEOUT_MS).await
+    }
+}
+
+/// 燒錄階段 <br>
+/// Flashing stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BootState {
+    Idle,
+    Connecting,
+    Erasing,
+    Writing,
+    Verifying,
+    Starting,
+    Done,
+    Failed { error: String },
+}
+
+/// 燒錄進度，以 `bootloader-progress` 事件送往前端 <br>
+/// Flashing progress, sent to the frontend as `bootloader-progress` events
+#[derive(Debug, Clone, Serialize)]
+pub struct BootProgress {
+    #[serde(flatten)]
+    pub state: BootState,
+    pub simulated: bool,
+    pub version: Option<String>,
+    pub product_id: Option<u16>,
+    pub written: u32,
+    pub verified: u32,
+    pub total: u32,
+}
+impl BootProgress {
+    fn idle() -> Self {
+        Self {
+            state: BootState::Idle,
+            simulated: false,
+            version: None,
+            product_id: None,
+            written: 0,
+            verified: 0,
+            total: 0,
+        }
+    }
+}
+
+/// 燒錄選項 <br>
+/// Flashing options
+#[derive(Debug, Clone, Copy)]
+pub struct BootOptions {
+    pub verify: bool,
+    pub go: bool,
+}
+
+/// 將進度變化以 `bootloader-progress` 事件送往前端，直到燒錄結束 <br>
+/// Forwards progress changes to the frontend as `bootloader-progress` events until flashing ends
+async fn progress_forward(app: AppHandle, mut progress: watch::Receiver<BootProgress>) {
+    while progress.changed().await.is_ok() {
+        let current = progress.borrow_and_update().clone();
+        if let Err(e) = app.emit("bootloader-progress", &current) {
+            error!("Emit bootloader-progress failed: {}", e);
+        }
+        if matches!(current.state, BootState::Done | BootState::Failed { .. }) {
+            break;
+        }
+    }
+}
+
+/// 完整燒錄流程：同步、讀取 ID、整片抹除、逐塊寫入、選擇性驗證並執行 <br>
+/// Full flashing flow: sync, read id, mass erase, write block by block, optionally verify and run
+async fn flash<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    image: &FirmwareImage,
+    options: BootOptions,
+    progress: &watch::Sender<BootProgress>,
+) -> BootResult<()> {
+    let blocks = image.blocks()?;
+    let mut bootloader = Stm32Bootloader::connect(stream).await?;
+    let product_id = bootloader.get_id().await?;
+    let version = bootloader.version();
+    info!("Bootloader {} connected, product id {:#06x}", version, product_id);
+    progress.send_modify(|p| {
+        p.version = Some(version);
+        p.product_id = Some(product_id);
+        p.state = BootState::Erasing;
+    });
+    bootloader.erase_all().await?;
+    progress.send_modify(|p| p.state = BootState::Writing);
+    for &(addr, block) in &blocks {
+        bootloader.write_memory(addr, block).await?;
+        progress.send_modify(|p| p.written += block.len() as u32);
+    }
+    if options.verify {
+        progress.send_modify(|p| p.state = BootState::Verifying);
+        for &(addr, block) in &blocks {
+            if bootloader.read_memory(addr, block.len()).await? != block {
+                return Err(format!("Verify failed at {:#010x}", addr).into());
+            }
+            progress.send_modify(|p| p.verified += block.len() as u32);
+        }
+    }
+    if options.go {
+        if let Some(addr) = image.start_addr() {
+            progress.send_modify(|p| p.state = BootState::Starting);
+            bootloader.go(addr).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 經由已開啟的 UART 燒錄：暫停封包收發、改為偶同位，結束後還原 <br>
+/// Flashes over the open UART: pauses packet traffic and switches to even parity, restoring both afterwards
+async fn flash_uart(app: &AppHandle, image: &FirmwareImage, options: BootOptions, progress: &watch::Sender<BootProgress>) -> BootResult<()> {
+    let global_state = app.state::<GlobalState>();
+    let mut stream = global_state.uart_manager.lock().await.raw_take().await?;
+    let parity = stream.parity().unwrap_or(Parity::None);
+    let result = match stream.set_parity(Parity::Even) {
+        Ok(_) => flash(&mut stream, image, options, progress).await,
+        Err(e) => Err(format!("Set parity failed: {}", e).into()),
+    };
+    if let Err(e) = stream.set_parity(parity) {
+        warn!("Restore parity failed: {}", e);
+    }
+    global_state.uart_manager.lock().await.raw_restore(app.clone(), stream).await;
+    result
+}
+
+/// 模擬開機載入程式，於串流另一端回應 AN3155 指令，收到 Go 後結束 <br>
+/// Simulated bootloader answering AN3155 commands on the other end of a stream, returns after Go
+pub async fn simulate<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S) -> BootResult<()> {
+    let mut flash = vec![0xFFu8; SIM_FLASH_SIZE];
+    let commands = [BL_CMD_GET, BL_CMD_GET_ID, BL_CMD_READ_MEMORY, BL_CMD_GO, BL_CMD_WRITE_MEMORY, BL_CMD_EXTENDED_ERASE];
+    while stream.read_u8().await? != BL_SYNC {}
+    stream.write_u8(BL_ACK).await?;
+    // 讀取位址並檢查範圍，回傳快閃記憶體內的偏移
+    // reads an address and checks its range, returns the offset inside flash
+    async fn addr_read<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, len: usize) -> BootResult<Option<usize>> {
+        let mut bytes = [0u8; 5];
+        stream.read_exact(&mut bytes).await?;
+        let addr = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let offset = addr.wrapping_sub(BL_FLASH_BASE) as usize;
+        let ok = xor(&bytes) == 0 && addr >= BL_FLASH_BASE && offset + len <= SIM_FLASH_SIZE;
+        stream.write_u8(if ok { BL_ACK } else { BL_NACK }).await?;
+        Ok(ok.then_some(offset))
+    }
+    loop {
+        let mut cmd = [0u8; 2];
+        stream.read_exact(&mut cmd).await?;
+        if cmd[0] != !cmd[1] || !commands.contains(&cmd[0]) {
+            stream.write_u8(BL_NACK).await?;
+            continue;
+        }
+        stream.write_u8(BL_ACK).await?;
+        match cmd[0] {
+            BL_CMD_GET => {
+                let mut reply = vec![commands.len() as u8, 0x31];
+                reply.extend(commands);
+                reply.push(BL_ACK);
+                stream.write_all(&reply).await?;
+            }
+            BL_CMD_GET_ID => {
+                let [high, low] = SIM_PRODUCT_ID.to_be_bytes();
+                stream.write_all(&[1, high, low, BL_ACK]).await?;
+            }
+            BL_CMD_READ_MEMORY => {
+                let Some(offset) = addr_read(&mut stream, 1).await? else { continue; };
+                let mut n = [0u8; 2];
+                stream.read_exact(&mut n).await?;
+                let len = n[0] as usize + 1;
+                if n[0] != !n[1] || offset + len > SIM_FLASH_SIZE {
+                    stream.write_u8(BL_NACK).await?;
+                    continue;
+                }
+                stream.write_u8(BL_ACK).await?;
+                stream.write_all(&flash[offset..offset + len]).await?;
+            }
+            BL_CMD_WRITE_MEMORY => {
+                let Some(offset) = addr_read(&mut stream, 1).await? else { continue; };
+                let n = stream.read_u8().await?;
+                let mut data = vec![0u8; n as usize + 2];
+                stream.read_exact(&mut data).await?;
+                let checksum = data.pop().unwrap_or_default();
+                let end = offset + data.len();
+                if n ^ xor(&data) != checksum || end > SIM_FLASH_SIZE {
+                    stream.write_u8(BL_NACK).await?;
+                    continue;
+                }
+                // 快閃記憶體只能將位元由 1 寫成 0
+                // flash can only clear bits
+                flash[offset..end].iter_mut().zip(&data).for_each(|(cell, b)| *cell &= b);
+                stream.write_u8(BL_ACK).await?;
+            }
+            BL_CMD_EXTENDED_ERASE => {
+                let count = stream.read_u16().await?;
+                if count == 0xFFFF {
+                    let checksum = stream.read_u8().await?;
+                    let ok = checksum == 0x00;
+                    if ok {
+                        flash.fill(0xFF);
+                    }
+                    stream.write_u8(if ok { BL_ACK } else { BL_NACK }).await?;
+                    continue;
+                }
+                let mut pages = vec![0u8; (count as usize + 1) * 2 + 1];
+                stream.read_exact(&mut pages).await?;
+                let checksum = pages.pop().unwrap_or_default();
+                let ok = xor(&count.to_be_bytes()) ^ xor(&pages) == checksum;
+                if ok {
+                    for page in pages.chunks(2).map(|p| u16::from_be_bytes([p[0], p[1]]) as usize) {
+                        let start = (page * SIM_PAGE_SIZE).min(SIM_FLASH_SIZE);
+                        let end = (start + SIM_PAGE_SIZE).min(SIM_FLASH_SIZE);
+                        flash[start..end].fill(0xFF);
+                    }
+                }
+                stream.write_u8(if ok { BL_ACK } else { BL_NACK }).await?;
+            }
+            BL_CMD_GO => {
+                if addr_read(&mut stream, 0).await?.is_some() {
+                    info!("Simulated bootloader started the application");
+                    return Ok(());
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// 開機載入程式燒錄管理，同時只允許一次燒錄 <br>
+/// Bootloader flashing manager, one flash at a time
+pub struct BootloaderManager {
+    progress: watch::Sender<BootProgress>,
+    task: Option<JoinHandle<()>>,
+}
+impl BootloaderManager {
+    pub fn new() -> Self {
+        Self {
+            progress: watch::Sender::new(BootProgress::idle()),
+            task: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn progress(&self) -> BootProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// 啟動背景燒錄任務；simulated 為 true 時改對模擬開機載入程式燒錄 <br>
+    /// Starts the background flashing task; flashes the simulated bootloader when simulated is true
+    fn start(&mut self, app: AppHandle, image: FirmwareImage, options: BootOptions, simulated: bool) -> Result<(), String> {
+        if self.is_running() {
+            return Err("Bootloader flashing already running".into());
+        }
+        self.progress.send_replace(BootProgress {
+            state: BootState::Connecting,
+            simulated,
+            total: image.size() as u32,
+            ..BootProgress::idle()
+        });
+        let progress = self.progress.clone();
+        let forward = tokio::spawn(progress_forward(app.clone(), progress.subscribe()));
+        self.task = Some(tokio::spawn(async move {
+            let result = if simulated {
+                let (host, device) = tokio::io::duplex(BL_BLOCK_SIZE * 4);
+                let device_task = tokio::spawn(async move {
+                    if let Err(e) = simulate(device).await {
+                        debug!("Simulated bootloader ended: {}", e);
+                    }
+                });
+                let result = flash(host, &image, options, &progress).await;
+                device_task.abort();
+                result
+            } else {
+                flash_uart(&app, &image, options, &progress).await
+            };
+            let state = match result {
+                Ok(_) => {
+                    info!("Bootloader flashing done ({} bytes)", image.size());
+                    BootState::Done
+                }
+                Err(e) => {
+                    error!("Bootloader flashing failed: {}", e);
+                    BootState::Failed { error: e.to_string() }
+                }
+            };
+            progress.send_modify(|p| p.state = state);
+            let _ = forward.await;
+        }));
+        Ok(())
+    }
+}
+impl Default for BootloaderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析十進位或 `0x` 開頭的十六進位位址 <br>
+/// Parses a decimal or `0x` prefixed hexadecimal address
+fn addr_parse(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    let result = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse::<u32>(),
+    };
+    result.map_err(|e| format!("Invalid address {}: {}", text, e))
+}
+
+/// Tauri 指令：經由 UART 以 STM32 開機載入程式燒錄韌體（.hex 或 .bin） <br>
+/// Tauri command: flash firmware (.hex or .bin) through the STM32 bootloader over UART
+#[tauri::command]
+pub async fn cmd_bootloader_flash(
+    app: AppHandle,
+    path: String,
+    base_addr: Option<String>,
+    verify: Option<bool>,
+    go: Option<bool>,
+    simulate: Option<bool>,
+) -> Result<BootProgress, String> {
+    let base = match base_addr.filter(|s| !s.trim().is_empty()) {
+        Some(addr) => addr_parse(&addr)?,
+        None => BL_FLASH_BASE,
+    };
+    let image = FirmwareImage::load(Path::new(&path), base)
+        .map_err(|e| format!("Load firmware {} failed: {}", path, e))?;
+    let options = BootOptions { verify: verify.unwrap_or(true), go: go.unwrap_or(true) };
+    let simulated = simulate.unwrap_or(false);
+    info!(
+        "Bootloader flashing {} ({} bytes in {} segments{})",
+        path, image.size(), image.segments().len(), if simulated { ", simulated" } else { "" },
+    );
+    let global_state = app.state::<GlobalState>();
+    let progress = {
+        let mut bootloader = global_state.bootloader_manager.lock().await;
+        bootloader.start(app.clone(), image, options, simulated).map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+        bootloader.progress()
+    };
+    if let Err(e) = app.emit("bootloader-progress", &progress) {
+        error!("Emit bootloader-progress failed: {}", e);
+    }
+    Ok(progress)
+}
+
+/// Tauri 指令：取得燒錄進度 <br>
+/// Tauri command: get the flashing progress
+#[tauri::command]
+pub async fn cmd_bootloader_status(app: AppHandle) -> Result<BootProgress, String> {
+    let global_state = app.state::<GlobalState>();
+    let bootloader = global_state.bootloader_manager.lock().await;
+    Ok(bootloader.progress())
+}
+
+#[cfg(test)]
+mod tests {
+    use ihex::{create_object_file_representation, Record};
+    use super::*;
+
+    fn hex(records: &[Record]) -> String {
+        create_object_file_representation(records).unwrap()
+    }
+
+    async fn flash_simulated(image: &FirmwareImage, options: BootOptions) -> (BootResult<()>, BootProgress) {
+        let (host, device) = tokio::io::duplex(BL_BLOCK_SIZE * 4);
+        let device_task = tokio::spawn(simulate(device));
+        let progress = watch::Sender::new(BootProgress::idle());
+        let result = flash(host, image, options, &progress).await;
+        if result.is_ok() && options.go {
+            device_task.await.unwrap().unwrap();
+        } else {
+            device_task.abort();
+        }
+        let progress = progress.borrow().clone();
+        (result, progress)
+    }
+
+    #[tokio::test]
+    async fn flash_verify_and_go() {
+        let data: Vec<u8> = (0..1001u32).map(|i| (i * 7) as u8).collect();
+        let image = FirmwareImage::from_bin(data, BL_FLASH_BASE + 0x400).unwrap();
+        let (result, progress) = flash_simulated(&image, BootOptions { verify: true, go: true }).await;
+        result.unwrap();
+        assert_eq!(progress.state, BootState::Starting);
+        assert_eq!((progress.written, progress.verified), (1001, 1001));
+        assert_eq!(progress.version.as_deref(), Some("3.1"));
+        assert_eq!(progress.product_id, Some(SIM_PRODUCT_ID));
+    }
+
+    #[tokio::test]
+    async fn flash_outside_device_fails() {
+        let image = FirmwareImage::from_bin(vec![0x55; 16], BL_FLASH_BASE + SIM_FLASH_SIZE as u32).unwrap();
+        let (result, progress) = flash_simulated(&image, BootOptions { verify: false, go: false }).await;
+        assert!(result.unwrap_err().to_string().contains("NACK"));
+        assert_eq!(progress.written, 0);
+    }
+
+    #[test]
+    fn blocks_past_address_space() {
+        let base = u32::MAX - BL_BLOCK_SIZE as u32 + 1;
+        let image = FirmwareImage::from_bin(vec![0; BL_BLOCK_SIZE], base).unwrap();
+        assert_eq!(image.blocks().unwrap(), vec![(base, &[0u8; BL_BLOCK_SIZE][..])]);
+        let image = FirmwareImage::from_bin(vec![0; BL_BLOCK_SIZE + 1], base).unwrap();
+        assert!(image.blocks().is_err());
+        let image = FirmwareImage::from_bin(vec![0; 2], u32::MAX).unwrap();
+        assert!(image.blocks().is_err());
+    }
+
+    #[test]
+    fn hex_extended_linear_address() {
+        let text = hex(&[
+            Record::ExtendedLinearAddress(0x0800),
+            Record::Data { offset: 0xFFFC, value: vec![1, 2, 3, 4] },
+            Record::ExtendedLinearAddress(0x0801),
+            Record::Data { offset: 0x0000, value: vec![5, 6] },
+            Record::Data { offset: 0x0100, value: vec![7] },
+            Record::EndOfFile,
+        ]);
+        let image = FirmwareImage::from_hex(&text).unwrap();
+        assert_eq!(image.segments(), &[
+            FirmwareSegment { addr: 0x0800_FFFC, data: vec![1, 2, 3, 4, 5, 6] },
+            FirmwareSegment { addr: 0x0801_0100, data: vec![7] },
+        ]);
+        assert_eq!(image.start_addr(), Some(0x0800_FFFC));
+    }
+
+    #[test]
+    fn hex_extended_segment_address() {
+        let text = hex(&[
+            Record::ExtendedSegmentAddress(0x1000),
+            Record::Data { offset: 0x0010, value: vec![0xAA, 0xBB] },
+            Record::EndOfFile,
+        ]);
+        let image = FirmwareImage::from_hex(&text).unwrap();
+        assert_eq!(image.segments(), &[FirmwareSegment { addr: 0x0001_0010, data: vec![0xAA, 0xBB] }]);
+    }
+
+    #[test]
+    fn hex_overlap_rejected() {
+        let text = hex(&[
+            Record::Data { offset: 0x0100, value: vec![0; 8] },
+            Record::Data { offset: 0x0104, value: vec![1; 4] },
+            Record::EndOfFile,
+        ]);
+        assert!(FirmwareImage::from_hex(&text).unwrap_err().to_string().contains("overlap"));
+    }
+}
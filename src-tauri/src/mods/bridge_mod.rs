@@ -0,0 +1,434 @@
+use std::{collections::HashMap, net::{IpAddr, Ipv4Addr, SocketAddr}, time::Instant};
+use axum::{
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Query, Request, State},
+    http::{header, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::{net::TcpListener, sync::{broadcast::error::RecvError, watch}, task::JoinHandle};
+use crate::{mods::{heartbeat_mod::LinkReport, mcu_control_mod::{self, McuCommand}, mcu_store_mod::{DataType, DataUpdate}}, GlobalState};
+
+/// 預設綁定位址，僅本機可連線 <br>
+/// Default bind address, local connections only
+pub const DEFAULT_BRIDGE_IP: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+pub const DEFAULT_BRIDGE_PORT: u16 = 8765;
+
+/// 單一通道最多回傳的資料筆數 <br>
+/// Maximum number of values returned for one channel
+const CHANNEL_QUERY_MAX: usize = 1000;
+
+/// 內建的即時資料頁面 <br>
+/// Built-in live data page
+const INDEX_HTML: &str = r#"<!doctype html>
+<html>
+<head><meta charset="utf-8"><title>Telemetry</title></head>
+<body style="font-family: monospace">
+<h3>Telemetry</h3>
+<table id="t" border="1" cellpadding="4"></table>
+<script>
+const token = new URLSearchParams(location.search).get("token") || "";
+const proto = location.protocol === "https:" ? "wss" : "ws";
+const ws = new WebSocket(`${proto}://${location.host}/ws?token=${encodeURIComponent(token)}`);
+const rows = {};
+ws.onmessage = (e) => {
+    const msg = JSON.parse(e.data);
+    if (msg.type !== "data") return;
+    const key = JSON.stringify(msg.channel);
+    if (!rows[key]) {
+        rows[key] = document.getElementById("t").insertRow();
+        rows[key].insertCell().textContent = key;
+        rows[key].insertCell();
+        rows[key].insertCell();
+    }
+    rows[key].cells[1].textContent = msg.value;
+    rows[key].cells[2].textContent = new Date(msg.ts_ms).toLocaleTimeString();
+};
+ws.onclose = () => document.title = "Telemetry (disconnected)";
+</script>
+</body>
+</html>
+"#;
+
+/// 橋接伺服器設定 <br>
+/// Bridge server configuration
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeConfig {
+    pub bind: SocketAddr,
+    #[serde(skip_serializing)]
+    pub token: Option<String>,
+}
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::new(DEFAULT_BRIDGE_IP, DEFAULT_BRIDGE_PORT),
+            token: None,
+        }
+    }
+}
+
+/// 橋接伺服器狀態 <br>
+/// Bridge server status
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeStatus {
+    pub running: bool,
+    pub addr: Option<SocketAddr>,
+    pub token_set: bool,
+    pub uptime_secs: Option<u64>,
+}
+
+/// WebSocket 送出的訊息 <br>
+/// Messages sent over the WebSocket
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WsMessage {
+    Data(DataUpdate),
+    Lagged { skipped: u64 },
+    CommandResult { ok: bool, error: Option<String> },
+}
+
+/// REST 狀態回應 <br>
+/// REST status response
+#[derive(Debug, Clone, Serialize)]
+struct ApiStatus {
+    link: LinkReport,
+    channels: Vec<DataType>,
+}
+
+/// REST 通道資料回應 <br>
+/// REST channel data response
+#[derive(Debug, Clone, Serialize)]
+struct ApiChannel {
+    channel: DataType,
+    values: Vec<f64>,
+}
+
+#[derive(Clone)]
+struct BridgeState {
+    app: AppHandle,
+    token: Option<String>,
+    shutdown: watch::Receiver<bool>,
+}
+
+/// 嵌入式 WebSocket／HTTP 橋接伺服器 <br>
+/// Embedded WebSocket / HTTP bridge server
+pub struct BridgeManager {
+    config: BridgeConfig,
+    shutdown: Option<watch::Sender<bool>>,
+    task: Option<JoinHandle<()>>,
+    local_addr: Option<SocketAddr>,
+    started_at: Option<Instant>,
+}
+impl BridgeManager {
+    pub fn new() -> Self {
+        Self {
+            config: BridgeConfig::default(),
+            shutdown: None,
+            task: None,
+            local_addr: None,
+            started_at: None,
+        }
+    }
+
+    /// 是否已啟動 <br>
+    /// Returns whether the server is running
+    pub fn is_started(&self) -> bool {
+        self.shutdown.is_some()
+    }
+
+    /// 綁定位址並啟動伺服器；非本機位址必須設定 token <br>
+    /// Binds the address and starts the server; a token is required for non-loopback addresses
+    pub async fn start(&mut self, app: AppHandle, config: BridgeConfig) -> Result<SocketAddr, String> {
+        if let Some(addr) = self.local_addr.filter(|_| self.is_started()) {
+            return Err(format!("Bridge already running at {}", addr));
+        }
+        if !config.bind.ip().is_loopback() && config.token.is_none() {
+            return Err(format!("Token required when binding to non-loopback address {}", config.bind));
+        }
+        let listener = TcpListener::bind(config.bind).await
+            .map_err(|e| format!("Bridge bind {} failed: {}", config.bind, e))?;
+        let local_addr = listener.local_addr().map_err(|e| e.to_string())?;
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let state = BridgeState { app, token: config.token.clone(), shutdown: shutdown_rx.clone() };
+        let router = Router::new()
+            .route("/", get(index_get))
+            .route("/ws", get(ws_get))
+            .route("/api/status", get(status_get))
+            .route("/api/channels", get(channels_get))
+            .route("/api/channel", get(channel_get))
+            .route("/api/command", post(command_post))
+            .layer(middleware::from_fn_with_state(state.clone(), auth))
+            .with_state(state);
+        let task = tokio::spawn(async move {
+            let server = axum::serve(listener, router)
+                .with_graceful_shutdown(async move { let _ = shutdown_rx.changed().await; });
+            if let Err(e) = server.await {
+                error!("Bridge server failed: {}", e);
+            }
+            info!("Bridge server stopped");
+        });
+        self.config = config;
+        self.shutdown = Some(shutdown_tx);
+        self.task = Some(task);
+        self.local_addr = Some(local_addr);
+        self.started_at = Some(Instant::now());
+        Ok(local_addr)
+    }
+
+    /// 停止伺服器並關閉所有 WebSocket 連線，未啟動時不做任何事 <br>
+    /// Stops the server and closes every WebSocket session, does nothing when not running
+    pub async fn stop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(true);
+        }
+        if let Some(task) = self.task.take() {
+            task.abort();
+            let _ = task.await;
+        }
+        self.local_addr = None;
+        self.started_at = None;
+    }
+
+    /// 取得目前狀態 <br>
+    /// Returns the current status
+    pub fn status(&self) -> BridgeStatus {
+        BridgeStatus {
+            running: self.is_started(),
+            addr: self.local_addr,
+            token_set: self.config.token.is_some(),
+            uptime_secs: self.started_at.map(|t| t.elapsed().as_secs()),
+        }
+    }
+}
+impl Default for BridgeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 以固定時間比較 token，耗時只與設定的 token 長度有關 <br>
+/// Compares tokens in constant time, the duration depends only on the configured token length
+fn token_eq(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    let mut diff = given.len() ^ token.len();
+    for (i, byte) in token.iter().enumerate() {
+        diff |= (given.get(i).copied().unwrap_or(0) ^ byte) as usize;
+    }
+    diff == 0
+}
+
+/// 取出網址查詢字串中經過百分比解碼的 token <br>
+/// Returns the percent-decoded token from the URL query
+fn query_token(uri: &Uri) -> Option<String> {
+    Query::<HashMap<String, String>>::try_from_uri(uri).ok()?.0.remove("token")
+}
+
+/// 驗證 token：`Authorization: Bearer <token>` 或 `?token=<token>` <br>
+/// Checks the token: `Authorization: Bearer <token>` or `?token=<token>`
+async fn auth(State(state): State<BridgeState>, request: Request, next: Next) -> Response {
+    let Some(token) = state.token.as_deref() else {
+        return next.run(request).await;
+    };
+    let header_ok = request.headers().get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|v| token_eq(v, token));
+    let query_ok = query_token(request.uri()).is_some_and(|v| token_eq(&v, token));
+    // 首頁不需 token，由頁面自行帶入
+    // the index page is public, it forwards the token itself
+    if header_ok || query_ok || request.uri().path() == "/" {
+        return next.run(request).await;
+    }
+    warn!("Bridge request rejected: {}", request.uri().path());
+    (StatusCode::UNAUTHORIZED, "Invalid token").into_response()
+}
+
+async fn index_get() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn status_get(State(state): State<BridgeState>) -> Json<ApiStatus> {
+    let global_state = state.app.state::<GlobalState>();
+    let link = global_state.link_monitor.lock().await.report();
+    Json(ApiStatus { link, channels: DataType::all() })
+}
+
+/// 取得所有通道的最新值 <br>
+/// Returns the latest value of every channel
+async fn channels_get(State(state): State<BridgeState>) -> Json<Vec<ApiChannel>> {
+    let global_state = state.app.state::<GlobalState>();
+    let store_datas = global_state.store_datas.lock().await;
+    Json(DataType::all().into_iter().map(|channel| ApiChannel {
+        channel,
+        values: store_datas.get(channel).to_f64().last().copied().into_iter().collect(),
+    }).collect())
+}
+
+/// 取得單一通道最近 n 筆，例：`/api/channel?channel=motor_left.speed_present&n=50` <br>
+/// Returns the last n values of one channel, e.g. `/api/channel?channel=motor_left.speed_present&n=50`
+async fn channel_get(
+    State(state): State<BridgeState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<ApiChannel>, (StatusCode, String)> {
+    let name = query.get("channel")
+        .ok_or((StatusCode::BAD_REQUEST, "Missing channel".to_string()))?;
+    let channel = channel_parse(name)
+        .ok_or((StatusCode::BAD_REQUEST, format!("Unknown channel: {}", name)))?;
+    let n = query.get("n")
+        .and_then(|n| n.parse::<usize>().ok())
+        .unwrap_or(CHANNEL_QUERY_MAX)
+        .min(CHANNEL_QUERY_MAX);
+    let global_state = state.app.state::<GlobalState>();
+    let values = global_state.store_datas.lock().await.get(channel).to_f64();
+    let values = values[values.len().saturating_sub(n)..].to_vec();
+    Ok(Json(ApiChannel { channel, values }))
+}
+
+/// 解析 `motor_left.speed_present` 或 `vehicel_pos` 形式的通道名稱 <br>
+/// Parses a channel name like `motor_left.speed_present` or `vehicel_pos`
+fn channel_parse(name: &str) -> Option<DataType> {
+    let value = match name.split_once('.') {
+        Some((motor, quantity)) => serde_json::json!({ motor: quantity }),
+        None => serde_json::json!(name),
+    };
+    serde_json::from_value(value).ok()
+}
+
+async fn command_post(
+    State(state): State<BridgeState>,
+    Json(command): Json<McuCommand>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    mcu_control_mod::command_execute(state.app, command).await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+async fn ws_get(State(state): State<BridgeState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| ws_session(state.app, socket, state.shutdown))
+}
+
+fn ws_text(message: &WsMessage) -> Option<Message> {
+    match serde_json::to_string(message) {
+        Ok(text) => Some(Message::Text(text.into())),
+        Err(e) => {
+            error!("Bridge message encode failed: {}", e);
+            None
+        }
+    }
+}
+
+/// 單一 WebSocket 連線：推送資料更新並執行收到的指令，伺服器停止時關閉 <br>
+/// One WebSocket session: pushes data updates and executes received commands, closes when the server stops
+async fn ws_session(app: AppHandle, mut socket: WebSocket, mut shutdown: watch::Receiver<bool>) {
+    let mut updates = {
+        let global_state = app.state::<GlobalState>();
+        let store_datas = global_state.store_datas.lock().await;
+        store_datas.subscribe()
+    };
+    info!("Bridge WebSocket connected");
+    loop {
+        let outgoing = tokio::select! {
+            _ = shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            update = updates.recv() => match update {
+                Ok(update) => WsMessage::Data(update),
+                Err(RecvError::Lagged(skipped)) => WsMessage::Lagged { skipped },
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let result = match serde_json::from_str::<McuCommand>(text.as_str()) {
+                        Ok(command) => mcu_control_mod::command_execute(app.clone(), command).await,
+                        Err(e) => Err(format!("Invalid command: {}", e)),
+                    };
+                    WsMessage::CommandResult { ok: result.is_ok(), error: result.err() }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!("Bridge WebSocket error: {}", e);
+                    break;
+                }
+            },
+        };
+        let Some(message) = ws_text(&outgoing) else { continue; };
+        if socket.send(message).await.is_err() {
+            break;
+        }
+    }
+    info!("Bridge WebSocket disconnected");
+}
+
+/// Tauri 指令：啟動橋接伺服器 <br>
+/// Tauri command: start the bridge server
+#[tauri::command]
+pub async fn cmd_bridge_start(
+    app: AppHandle,
+    bind_addr: Option<String>,
+    port: Option<u16>,
+    token: Option<String>,
+) -> Result<BridgeStatus, String> {
+    let ip = match bind_addr.filter(|s| !s.trim().is_empty()) {
+        Some(addr) => addr.trim().parse::<IpAddr>()
+            .map_err(|e| format!("Invalid bind address {}: {}", addr, e))?,
+        None => DEFAULT_BRIDGE_IP,
+    };
+    let config = BridgeConfig {
+        bind: SocketAddr::new(ip, port.unwrap_or(DEFAULT_BRIDGE_PORT)),
+        token: token.filter(|t| !t.is_empty()),
+    };
+    let global_state = app.state::<GlobalState>();
+    let mut bridge = global_state.bridge_manager.lock().await;
+    let addr = bridge.start(app.clone(), config).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    info!("Bridge server started at {}", addr);
+    Ok(bridge.status())
+}
+
+/// Tauri 指令：停止橋接伺服器 <br>
+/// Tauri command: stop the bridge server
+#[tauri::command]
+pub async fn cmd_bridge_stop(app: AppHandle) -> Result<BridgeStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let mut bridge = global_state.bridge_manager.lock().await;
+    bridge.stop().await;
+    Ok(bridge.status())
+}
+
+/// Tauri 指令：取得橋接伺服器狀態 <br>
+/// Tauri command: get the bridge server status
+#[tauri::command]
+pub async fn cmd_bridge_status(app: AppHandle) -> Result<BridgeStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let bridge = global_state.bridge_manager.lock().await;
+    Ok(bridge.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_compare() {
+        assert!(token_eq("secret", "secret"));
+        assert!(!token_eq("secreT", "secret"));
+        assert!(!token_eq("secret-longer", "secret"));
+        assert!(!token_eq("", "secret"));
+    }
+
+    #[test]
+    fn token_percent_decoded() {
+        let uri: Uri = "/ws?n=1&token=a%2Bb%26c%3D%20d".parse().unwrap();
+        assert_eq!(query_token(&uri).as_deref(), Some("a+b&c= d"));
+        let uri: Uri = "/ws?n=1".parse().unwrap();
+        assert_eq!(query_token(&uri), None);
+    }
+}
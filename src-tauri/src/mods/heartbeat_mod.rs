@@ -0,0 +1,154 @@
+use std::time::{Duration, Instant};
+use log::{error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{mods::{mcu_const, mcu_control_mod}, GlobalState};
+
+/// MCU 連線狀態 <br>
+/// MCU link status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    Disconnected,   // 鏈路未就緒／link not ready
+    Waiting,        // 已開啟但尚未收到心跳／open but no heartbeat received yet
+    Alive,          // 心跳正常／heartbeat received in time
+    Stale,          // 心跳逾時／heartbeat timed out
+}
+
+/// 傳送給前端的連線狀態 <br>
+/// Link status sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkReport {
+    pub status: LinkStatus,
+    pub since_last_ms: Option<u64>,
+    pub last_seq: Option<u8>,
+}
+
+/// 心跳監控器，記錄上位機送出與 MCU 回報的心跳 <br>
+/// Heartbeat monitor, tracks host heartbeats sent and MCU heartbeats received
+pub struct LinkMonitor {
+    status: LinkStatus,
+    tx_seq: u8,
+    last_seq: Option<u8>,
+    last_beat: Option<Instant>,
+    opened_at: Option<Instant>,
+    timeout: Duration,
+}
+impl LinkMonitor {
+    /// 建立心跳監控器 <br>
+    /// Creates a heartbeat monitor
+    pub fn new() -> Self {
+        Self {
+            status: LinkStatus::Disconnected,
+            tx_seq: 0,
+            last_seq: None,
+            last_beat: None,
+            opened_at: None,
+            timeout: Duration::from_millis(mcu_const::HEARTBEAT_TIMEOUT_MS as u64),
+        }
+    }
+
+    /// 取得下一個送出的心跳序號 <br>
+    /// Returns the next heartbeat sequence number to send
+    pub fn next_seq(&mut self) -> u8 {
+        self.tx_seq = self.tx_seq.wrapping_add(1);
+        self.tx_seq
+    }
+
+    /// 記錄收到的 MCU 心跳 <br>
+    /// Records a heartbeat received from the MCU
+    pub fn beat(&mut self, seq: Option<u8>) {
+        self.last_beat = Some(Instant::now());
+        self.last_seq = seq;
+    }
+
+    /// 依埠狀態與心跳時間更新狀態，狀態改變時回傳新狀態 <br>
+    /// Updates the status from port state and heartbeat age, returns the new status when it changed
+    pub fn update(&mut self, port_open: bool) -> Option<LinkStatus> {
+        let now = Instant::now();
+        let status = if !port_open {
+            self.opened_at = None;
+            self.last_beat = None;
+            self.last_seq = None;
+            LinkStatus::Disconnected
+        } else {
+            let opened_at = *self.opened_at.get_or_insert(now);
+            match self.last_beat {
+                Some(beat) if now.duration_since(beat) <= self.timeout => LinkStatus::Alive,
+                Some(_) => LinkStatus::Stale,
+                None if now.duration_since(opened_at) <= self.timeout => LinkStatus::Waiting,
+                None => LinkStatus::Stale,
+            }
+        };
+        if status == self.status {
+            return None;
+        }
+        self.status = status;
+        Some(status)
+    }
+
+    /// 取得目前狀態報告 <br>
+    /// Returns the current status report
+    pub fn report(&self) -> LinkReport {
+        LinkReport {
+            status: self.status,
+            since_last_ms: self.last_beat.map(|t| t.elapsed().as_millis() as u64),
+            last_seq: self.last_seq,
+        }
+    }
+}
+
+impl Default for LinkMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 送出上位機心跳，並檢查 MCU 心跳是否逾時 <br>
+/// Sends the host heartbeat and checks whether the MCU heartbeat timed out
+pub async fn heartbeat_proccess(app: AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    let port_open = mcu_control_mod::link_ready(&app).await;
+    let (changed, report, seq) = {
+        let mut link_monitor = global_state.link_monitor.lock().await;
+        let changed = link_monitor.update(port_open);
+        let seq = link_monitor.next_seq();
+        (changed, link_monitor.report(), seq)
+    };
+    if port_open {
+        let cmd = vec![mcu_const::CMD_CODE_HEARTBEAT, seq];
+        if let Err(e) = mcu_control_mod::transmit_push(&app, cmd).await {
+            error!("Heartbeat send failed: {}", e);
+        }
+    }
+    let Some(status) = changed else { return; };
+    if let Err(e) = app.emit("mcu-link-status", &report) {
+        error!("Emit mcu-link-status failed: {}", e);
+    }
+    global_state.mqtt_manager.lock().await.link_publish(&report);
+    if status == LinkStatus::Stale {
+        warn!("MCU heartbeat timed out: {:?}", report);
+        if let Err(e) = app.emit("mcu-link-alert", &report) {
+            error!("Emit mcu-link-alert failed: {}", e);
+        }
+    } else {
+        info!("MCU link status: {:?}", status);
+    }
+}
+
+/// 解析 MCU 心跳封包 <br>
+/// Parses an MCU heartbeat packet
+pub async fn re_pkt_heartbeat(app: AppHandle, data: Vec<u8>) {
+    let global_state = app.state::<GlobalState>();
+    let mut link_monitor = global_state.link_monitor.lock().await;
+    link_monitor.beat(data.first().copied());
+}
+
+/// Tauri 指令：取得 MCU 連線狀態 <br>
+/// Tauri command: get the MCU link status
+#[tauri::command]
+pub async fn cmd_mcu_link_status(app: AppHandle) -> Result<LinkReport, String> {
+    let global_state = app.state::<GlobalState>();
+    let link_monitor = global_state.link_monitor.lock().await;
+    Ok(link_monitor.report())
+}
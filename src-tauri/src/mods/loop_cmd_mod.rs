@@ -1,7 +1,7 @@
 use std::time::Duration;
 use tauri::AppHandle;
 use tokio::time::interval;
-use crate::mods::uart_packet_proc_mod;
+use crate::mods::{heartbeat_mod, map_mission_mod, mcu_const, mcu_control_mod, teleop_mod, uart_packet_proc_mod, wifi_discovery_mod, wifi_packet_proc_mod};
 
 pub fn setup(app: AppHandle) {
     let app_10ms = app.clone();
@@ -9,6 +9,47 @@ pub fn setup(app: AppHandle) {
         let mut ticker = interval(Duration::from_millis(10));
         loop {
             uart_packet_proc_mod::re_pkt_proccess(app_10ms.clone()).await;
+            wifi_packet_proc_mod::re_pkt_proccess(app_10ms.clone()).await;
+            ticker.tick().await;
+        }
+    });
+    let app_100ms = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_millis(100));
+        loop {
+            mcu_control_mod::motor_tracking_check(&app_100ms).await;
+            ticker.tick().await;
+        }
+    });
+    let app_heartbeat = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_millis(mcu_const::HEARTBEAT_PERIOD_MS as u64));
+        loop {
+            heartbeat_mod::heartbeat_proccess(app_heartbeat.clone()).await;
+            ticker.tick().await;
+        }
+    });
+    let app_teleop = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_millis(teleop_mod::TELEOP_PERIOD_MS));
+        loop {
+            teleop_mod::teleop_proccess(app_teleop.clone()).await;
+            ticker.tick().await;
+        }
+    });
+    let app_mission = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_millis(map_mission_mod::MISSION_PERIOD_MS));
+        loop {
+            map_mission_mod::mission_proccess(app_mission.clone()).await;
+            ticker.tick().await;
+        }
+    });
+    let app_discovery = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(Duration::from_millis(wifi_discovery_mod::DISCOVERY_PERIOD_MS));
+        loop {
+            wifi_discovery_mod::discovery_proccess(app_discovery.clone()).await;
             ticker.tick().await;
         }
     });
@@ -0,0 +1,637 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use log::{error, info};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use crate::{
+    mods::{
+        map_mod::{self, AngleType, CoordType, InConnect, InItem, LenType, NodeKind, PosType, MAX_CONNECTIONS},
+        map_validate_mod::{self, MapIssueCode, MapReport},
+    },
+    GlobalState, MAP_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER,
+};
+
+/// 列舉：支援的地圖交換格式  
+/// Enum: supported map exchange formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapFormat {
+    Graphml,
+    Geojson,
+    Dot,
+    Csv,
+}
+impl MapFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            MapFormat::Graphml => "graphml",
+            MapFormat::Geojson => "geojson",
+            MapFormat::Dot => "dot",
+            MapFormat::Csv => "csv",
+        }
+    }
+
+    /// 依副檔名判斷格式  
+    /// Detects the format from the file extension
+    fn from_path(path: &Path) -> Result<Self, String> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+        match ext.as_str() {
+            "graphml" | "xml" => Ok(MapFormat::Graphml),
+            "geojson" | "json" => Ok(MapFormat::Geojson),
+            "dot" | "gv" => Ok(MapFormat::Dot),
+            "csv" => Ok(MapFormat::Csv),
+            _ => Err(format!("Unknown map format: {:?}", path)),
+        }
+    }
+}
+
+/// 結構：匯入中的節點，key 為來源檔案中的識別字  
+/// Struct: node being imported, key is the identifier in the source file
+#[derive(Default)]
+struct DraftNode {
+    key:        String,
+    name:       String,
+    kind:       NodeKind,
+    coord:      Option<(f64, f64)>,
+}
+
+/// 結構：匯入中的邊  
+/// Struct: edge being imported
+struct DraftEdge {
+    from:       String,
+    to:         String,
+    len:        Option<f64>,
+    heading:    Option<AngleType>,
+    one_way:    bool,
+}
+
+/// 結構：匯入中的圖，邊引用但未宣告的節點會自動建立  
+/// Struct: graph being imported, nodes referenced by edges but never declared are created automatically
+#[derive(Default)]
+struct GraphDraft {
+    nodes:  Vec<DraftNode>,
+    edges:  Vec<DraftEdge>,
+}
+impl GraphDraft {
+    fn node(&mut self, key: &str) -> &mut DraftNode {
+        let idx = match self.nodes.iter().position(|n| n.key == key) {
+            Some(idx) => idx,
+            None => {
+                self.nodes.push(DraftNode { key: key.to_string(), name: key.to_string(), ..Default::default() });
+                self.nodes.len() - 1
+            }
+        };
+        &mut self.nodes[idx]
+    }
+
+    /// 轉為地圖項目：來源 id 皆為不重複且非 0 的整數時保留，否則依出現順序重新編號為 1..n  
+    /// Converts to map items: source ids are kept when all are unique non-zero integers, otherwise renumbered 1..n in order
+    fn into_items(mut self) -> MapImportResult {
+        let referenced: Vec<String> = self.edges.iter().flat_map(|e| [e.from.clone(), e.to.clone()]).collect();
+        for key in &referenced {
+            self.node(key);
+        }
+        let numeric: Vec<Option<PosType>> = self.nodes.iter()
+            .map(|n| n.key.parse::<PosType>().ok().filter(|id| *id != 0))
+            .collect();
+        let mut seen = HashSet::new();
+        let keep = numeric.iter().all(|id| id.is_some_and(|id| seen.insert(id)));
+        let id_map: Vec<MapIdMapping> = self.nodes.iter().zip(&numeric).enumerate()
+            .map(|(i, (node, id))| MapIdMapping {
+                source: node.key.clone(),
+                id: if keep { id.unwrap() } else { (i + 1) as PosType },
+            })
+            .collect();
+        let index: HashMap<&str, usize> = self.nodes.iter().enumerate().map(|(i, n)| (n.key.as_str(), i)).collect();
+
+        let mut report = MapReport::default();
+        for (node, mapping) in self.nodes.iter().zip(&id_map) {
+            let Some((x, y)) = node.coord.filter(|(x, y)| x.fract() != 0.0 || y.fract() != 0.0) else { continue };
+            let message = format!("Coordinates ({}, {}) rounded to ({}, {})", x, y, x.round(), y.round());
+            report.warning(MapIssueCode::CoordRounded, mapping.id, None, message);
+        }
+        let mut items: Vec<InItem> = self.nodes.iter().zip(&id_map).map(|(node, mapping)| InItem {
+            id: mapping.id,
+            name: node.name.clone(),
+            kind: node.kind,
+            x: node.coord.map(|c| c.0.round() as CoordType),
+            y: node.coord.map(|c| c.1.round() as CoordType),
+            connect: (0..MAX_CONNECTIONS).map(|_| InConnect { pos: 0, len: 0, heading: None, one_way: false }).collect(),
+        }).collect();
+
+        for edge in &self.edges {
+            let from = index[edge.from.as_str()];
+            let to = index[edge.to.as_str()];
+            let straight = self.nodes[from].coord.zip(self.nodes[to].coord)
+                .map(|(a, b)| (b.0 - a.0, b.1 - a.1));
+            let len = edge.len
+                .or_else(|| straight.map(|(dx, dy)| (dx * dx + dy * dy).sqrt()))
+                .unwrap_or(0.0)
+                .round() as LenType;
+            // 有座標時依方位選槽位，北方為 0 順時針 / With coordinates the slot follows the bearing, north 0 clockwise
+            let heading = edge.heading.or_else(|| straight
+                .filter(|(dx, dy)| *dx != 0.0 || *dy != 0.0)
+                .map(|(dx, dy)| (dx.atan2(dy).to_degrees().rem_euclid(360.0).round() as AngleType) % 360));
+            let connect = &mut items[from].connect;
+            let preferred = heading.map(|h| ((h as f64 / 45.0).round() as usize) % MAX_CONNECTIONS);
+            let slot = preferred
+                .filter(|s| connect[*s].pos == 0)
+                .or_else(|| connect.iter().position(|c| c.pos == 0));
+            let Some(slot) = slot else {
+                let message = format!("No free slot for the edge to {}", id_map[to].id);
+                report.error(MapIssueCode::NoFreeSlot, id_map[from].id, None, message);
+                continue;
+            };
+            let heading = heading.filter(|h| *h != (slot * 45) as AngleType);
+            connect[slot] = InConnect { pos: id_map[to].id, len, heading, one_way: edge.one_way };
+        }
+
+        let mut validation = map_validate_mod::validate(&items);
+        validation.issues.extend(report.issues);
+        MapImportResult { items, id_map, report: validation }
+    }
+}
+
+/// 結構：來源 id 與地圖 id 的對應  
+/// Struct: mapping from source id to map id
+#[derive(Debug, Clone, Serialize)]
+pub struct MapIdMapping {
+    pub source: String,
+    pub id:     PosType,
+}
+
+/// 結構：匯入結果，尚未保存，report 為驗證結果  
+/// Struct: import result, not saved yet, report holds the validation result
+#[derive(Serialize)]
+pub struct MapImportResult {
+    pub(crate) items:   Vec<InItem>,
+    pub id_map:         Vec<MapIdMapping>,
+    pub report:         MapReport,
+}
+
+fn kind_parse(text: &str) -> NodeKind {
+    serde_json::from_value(Value::String(text.trim().to_lowercase())).unwrap_or_default()
+}
+
+fn kind_text(kind: NodeKind) -> String {
+    serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+fn bool_parse(text: &str) -> bool {
+    matches!(text.trim().to_lowercase().as_str(), "true" | "1" | "yes")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 以地圖項目的連接逐一列出有向邊與其出發方位，未設定方位的連接依槽位推算  
+/// Lists the directed edges of the map items one by one with their departure heading, derived from the slot when unset
+fn edges(items: &[InItem]) -> impl Iterator<Item = (&InItem, &InConnect, AngleType)> {
+    items.iter().flat_map(|item| item.connect.iter()
+        .enumerate()
+        .filter(|(_, c)| c.pos != 0)
+        .map(move |(slot, c)| (item, c, c.heading_or_slot(slot))))
+}
+
+fn graphml_export(items: &[InItem]) -> String {
+    let mut text = String::new();
+    text.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    text.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    text.push_str("  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n");
+    text.push_str("  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+    text.push_str("  <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"int\"/>\n");
+    text.push_str("  <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"int\"/>\n");
+    text.push_str("  <key id=\"len\" for=\"edge\" attr.name=\"len\" attr.type=\"long\"/>\n");
+    text.push_str("  <key id=\"heading\" for=\"edge\" attr.name=\"heading\" attr.type=\"int\"/>\n");
+    text.push_str("  <key id=\"one_way\" for=\"edge\" attr.name=\"one_way\" attr.type=\"boolean\"/>\n");
+    text.push_str("  <graph id=\"map\" edgedefault=\"directed\">\n");
+    for item in items {
+        text.push_str(&format!("    <node id=\"{}\">\n", item.id));
+        text.push_str(&format!("      <data key=\"name\">{}</data>\n", xml_escape(&item.name)));
+        text.push_str(&format!("      <data key=\"kind\">{}</data>\n", kind_text(item.kind)));
+        if let Some((x, y)) = item.coord() {
+            text.push_str(&format!("      <data key=\"x\">{}</data>\n      <data key=\"y\">{}</data>\n", x, y));
+        }
+        text.push_str("    </node>\n");
+    }
+    for (item, conn, heading) in edges(items) {
+        text.push_str(&format!("    <edge source=\"{}\" target=\"{}\">\n", item.id, conn.pos));
+        text.push_str(&format!("      <data key=\"len\">{}</data>\n", conn.len));
+        text.push_str(&format!("      <data key=\"heading\">{}</data>\n", heading));
+        text.push_str(&format!("      <data key=\"one_way\">{}</data>\n", conn.one_way));
+        text.push_str("    </edge>\n");
+    }
+    text.push_str("  </graph>\n</graphml>\n");
+    text
+}
+
+/// 解析 GraphML，資料欄位依 key 的 attr.name 對應（name/label、kind/type、x、y、len/length/weight、heading、one_way）  
+/// Parses GraphML, data fields are matched by the key's attr.name (name/label, kind/type, x, y, len/length/weight, heading, one_way)
+fn graphml_import(text: &str) -> Result<GraphDraft, String> {
+    let doc = roxmltree::Document::parse(text).map_err(|e| format!("GraphML parse error: {}", e))?;
+    let keys: HashMap<&str, String> = doc.descendants()
+        .filter(|n| n.has_tag_name("key"))
+        .filter_map(|n| Some((n.attribute("id")?, n.attribute("attr.name").unwrap_or(n.attribute("id")?).to_lowercase())))
+        .collect();
+    let graph = doc.descendants().find(|n| n.has_tag_name("graph")).ok_or("GraphML has no graph")?;
+    let undirected = graph.attribute("edgedefault") == Some("undirected");
+    let data = |node: roxmltree::Node| -> HashMap<String, String> {
+        node.children()
+            .filter(|c| c.has_tag_name("data"))
+            .filter_map(|c| {
+                let key = c.attribute("key")?;
+                let name = keys.get(key).cloned().unwrap_or_else(|| key.to_lowercase());
+                Some((name, c.text().unwrap_or_default().trim().to_string()))
+            })
+            .collect()
+    };
+
+    let mut draft = GraphDraft::default();
+    for node in graph.children().filter(|n| n.has_tag_name("node")) {
+        let key = node.attribute("id").ok_or("GraphML node without id")?;
+        let fields = data(node);
+        let x = fields.get("x").and_then(|v| v.parse::<f64>().ok());
+        let y = fields.get("y").and_then(|v| v.parse::<f64>().ok());
+        let entry = draft.node(key);
+        if let Some(name) = fields.get("name").or(fields.get("label")) {
+            entry.name = name.clone();
+        }
+        if let Some(kind) = fields.get("kind").or(fields.get("type")) {
+            entry.kind = kind_parse(kind);
+        }
+        entry.coord = x.zip(y);
+    }
+    for edge in graph.children().filter(|n| n.has_tag_name("edge")) {
+        let from = edge.attribute("source").ok_or("GraphML edge without source")?.to_string();
+        let to = edge.attribute("target").ok_or("GraphML edge without target")?.to_string();
+        let fields = data(edge);
+        let len = ["len", "length", "weight"].iter().find_map(|k| fields.get(*k)?.parse::<f64>().ok());
+        let heading = fields.get("heading").and_then(|v| v.parse::<AngleType>().ok());
+        let one_way = fields.get("one_way").is_some_and(|v| bool_parse(v));
+        let directed = edge.attribute("directed").map_or(!undirected, |d| d == "true");
+        draft.edges.push(DraftEdge { from: from.clone(), to: to.clone(), len, heading, one_way });
+        if !directed {
+            draft.edges.push(DraftEdge { from: to, to: from, len, heading: None, one_way: false });
+        }
+    }
+    Ok(draft)
+}
+
+fn geojson_export(items: &[InItem]) -> String {
+    let coords: HashMap<PosType, (CoordType, CoordType)> = items.iter()
+        .filter_map(|item| item.coord().map(|c| (item.id, c)))
+        .collect();
+    let mut features: Vec<Value> = items.iter().map(|item| json!({
+        "type": "Feature",
+        "geometry": item.coord().map(|(x, y)| json!({ "type": "Point", "coordinates": [x, y] })),
+        "properties": { "id": item.id, "name": item.name, "kind": item.kind },
+    })).collect();
+    for (item, conn, heading) in edges(items) {
+        let geometry = coords.get(&item.id).zip(coords.get(&conn.pos))
+            .map(|(a, b)| json!({ "type": "LineString", "coordinates": [[a.0, a.1], [b.0, b.1]] }));
+        features.push(json!({
+            "type": "Feature",
+            "geometry": geometry,
+            "properties": { "from": item.id, "to": conn.pos, "len": conn.len, "heading": heading, "one_way": conn.one_way },
+        }));
+    }
+    serde_json::to_string_pretty(&json!({ "type": "FeatureCollection", "features": features })).unwrap_or_default()
+}
+
+/// 解析 GeoJSON：Point 為節點（properties.id / name / kind），LineString 為邊（properties.from / to / len）；
+/// 座標需為與長度同單位的平面座標，明確宣告經緯度 crs（CRS84 / EPSG:4326）的輸入會被拒絕  
+/// Parses GeoJSON: Points are nodes (properties.id / name / kind), LineStrings are edges (properties.from / to / len);
+/// coordinates must be projected in the map length unit, input declaring a longitude/latitude crs (CRS84 / EPSG:4326) is rejected
+fn geojson_import(text: &str) -> Result<GraphDraft, String> {
+    let root: Value = serde_json::from_str(text).map_err(|e| format!("GeoJSON parse error: {}", e))?;
+    if let Some(crs) = root["crs"]["properties"]["name"].as_str() {
+        if crs.contains("CRS84") || crs.contains("4326") {
+            return Err(format!("GeoJSON crs {} is longitude/latitude, reproject it to the map length unit first", crs));
+        }
+    }
+    let features = root["features"].as_array().ok_or("GeoJSON has no features")?;
+    let key = |v: &Value| match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let mut draft = GraphDraft::default();
+    for (i, feature) in features.iter().enumerate() {
+        let props = &feature["properties"];
+        let geometry = &feature["geometry"];
+        let is_edge = geometry["type"] == "LineString" || (props.get("from").is_some() && props.get("to").is_some());
+        if is_edge {
+            let (Some(from), Some(to)) = (key(&props["from"]), key(&props["to"])) else {
+                return Err(format!("GeoJSON edge feature {} needs properties.from and properties.to", i));
+            };
+            draft.edges.push(DraftEdge {
+                from,
+                to,
+                len: props["len"].as_f64().or(props["length"].as_f64()),
+                heading: props["heading"].as_i64().map(|h| h as AngleType),
+                one_way: props["one_way"].as_bool().unwrap_or(false),
+            });
+            continue;
+        }
+        let node_key = key(&props["id"]).unwrap_or_else(|| (i + 1).to_string());
+        let coord = geometry["coordinates"].as_array()
+            .and_then(|c| Some((c.first()?.as_f64()?, c.get(1)?.as_f64()?)));
+        let node = draft.node(&node_key);
+        if let Some(name) = props["name"].as_str() {
+            node.name = name.to_string();
+        }
+        if let Some(kind) = props["kind"].as_str() {
+            node.kind = kind_parse(kind);
+        }
+        node.coord = coord;
+    }
+    Ok(draft)
+}
+
+fn dot_export(items: &[InItem]) -> String {
+    let mut text = String::from("digraph map {\n");
+    for item in items {
+        let mut attrs = vec![
+            format!("label=\"{}\"", dot_escape(&item.name)),
+            format!("kind=\"{}\"", kind_text(item.kind)),
+        ];
+        if let Some((x, y)) = item.coord() {
+            attrs.push(format!("pos=\"{},{}!\"", x, y));
+        }
+        text.push_str(&format!("    {} [{}];\n", item.id, attrs.join(", ")));
+    }
+    for (item, conn, heading) in edges(items) {
+        let mut attrs = vec![format!("label=\"{}\"", conn.len), format!("len={}", conn.len), format!("heading={}", heading)];
+        if conn.one_way {
+            attrs.push("one_way=true".into());
+        }
+        text.push_str(&format!("    {} -> {} [{}];\n", item.id, conn.pos, attrs.join(", ")));
+    }
+    text.push_str("}\n");
+    text
+}
+
+/// 解析常見的 DOT 子集：每行一個節點或邊敘述，`--` 視為雙向；長度取 len、weight 或數字 label  
+/// Parses a common DOT subset: one node or edge statement per line, `--` means both directions; length comes from len, weight or a numeric label
+fn dot_import(text: &str) -> Result<GraphDraft, String> {
+    let id = r#"("(?:[^"\\]|\\.)*"|[\w.]+)"#;
+    let edge_re = Regex::new(&format!(r"^\s*{id}\s*(->|--)\s*{id}\s*(?:\[(.*)\])?\s*;?\s*$")).map_err(|e| e.to_string())?;
+    let node_re = Regex::new(&format!(r"^\s*{id}\s*(?:\[(.*)\])?\s*;?\s*$")).map_err(|e| e.to_string())?;
+    let attr_re = Regex::new(r#"(\w+)\s*=\s*("(?:[^"\\]|\\.)*"|[^,;\s\]]+)"#).map_err(|e| e.to_string())?;
+    let unquote = |s: &str| s.trim_matches('"').replace("\\\"", "\"").replace("\\\\", "\\");
+    let attrs = |s: Option<regex::Match>| -> HashMap<String, String> {
+        s.map(|m| attr_re.captures_iter(m.as_str())
+            .map(|c| (c[1].to_lowercase(), unquote(&c[2])))
+            .collect())
+            .unwrap_or_default()
+    };
+    const KEYWORDS: [&str; 6] = ["graph", "digraph", "node", "edge", "strict", "subgraph"];
+
+    let mut draft = GraphDraft::default();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") || line.starts_with('#') || line == "}" || line.ends_with('{') {
+            continue;
+        }
+        if let Some(c) = edge_re.captures(line) {
+            let fields = attrs(c.get(4));
+            let len = ["len", "weight", "label"].iter().find_map(|k| fields.get(*k)?.parse::<f64>().ok());
+            let heading = fields.get("heading").and_then(|v| v.parse::<AngleType>().ok());
+            let one_way = fields.get("one_way").is_some_and(|v| bool_parse(v));
+            let (from, to) = (unquote(&c[1]), unquote(&c[3]));
+            draft.edges.push(DraftEdge { from: from.clone(), to: to.clone(), len, heading, one_way });
+            if &c[2] == "--" {
+                draft.edges.push(DraftEdge { from: to, to: from, len, heading: None, one_way: false });
+            }
+        } else if let Some(c) = node_re.captures(line) {
+            let key = unquote(&c[1]);
+            if KEYWORDS.contains(&key.as_str()) || line.contains('=') && c.get(2).is_none() {
+                continue;
+            }
+            let fields = attrs(c.get(2));
+            let coord = fields.get("pos").and_then(|p| {
+                let (x, y) = p.trim_end_matches('!').split_once(',')?;
+                Some((x.trim().parse::<f64>().ok()?, y.trim().parse::<f64>().ok()?))
+            });
+            let node = draft.node(&key);
+            if let Some(label) = fields.get("label") {
+                node.name = label.clone();
+            }
+            if let Some(kind) = fields.get("kind") {
+                node.kind = kind_parse(kind);
+            }
+            if coord.is_some() {
+                node.coord = coord;
+            }
+        }
+    }
+    Ok(draft)
+}
+
+fn csv_export(items: &[InItem]) -> String {
+    let mut text = String::from("from,to,len,one_way,heading\n");
+    for (item, conn, heading) in edges(items) {
+        text.push_str(&format!("{},{},{},{},{}\n", item.id, conn.pos, conn.len, conn.one_way, heading));
+    }
+    text
+}
+
+/// 解析 CSV 邊列表：from,to,len[,one_way[,heading]]，首行為標題時略過，# 開頭為註解  
+/// Parses a CSV edge list: from,to,len[,one_way[,heading]], a header line is skipped and lines starting with # are comments
+fn csv_import(text: &str) -> Result<GraphDraft, String> {
+    let mut draft = GraphDraft::default();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split(',').map(|c| c.trim().trim_matches('"')).collect();
+        if cols.len() < 2 {
+            return Err(format!("CSV line {}: expected from,to,len", line_no + 1));
+        }
+        let len = cols.get(2).and_then(|v| v.parse::<f64>().ok());
+        if line_no == 0 && len.is_none() && cols.get(2).is_some_and(|v| !v.is_empty()) {
+            continue;
+        }
+        draft.edges.push(DraftEdge {
+            from: cols[0].to_string(),
+            to: cols[1].to_string(),
+            len,
+            heading: cols.get(4).and_then(|v| v.parse::<AngleType>().ok()),
+            one_way: cols.get(3).is_some_and(|v| bool_parse(v)),
+        });
+    }
+    Ok(draft)
+}
+
+fn map_folder(app: &AppHandle) -> PathBuf {
+    let global_state = app.state::<GlobalState>();
+    let root_path = global_state.root_path.lock().unwrap().clone();
+    root_path.join(ROOT_GEN_FILES_FOLDER).join(MAP_GEN_FILES_FOLDER)
+}
+
+/// Tauri 命令：由檔案匯入地圖，format 為空時依副檔名判斷；結果需經 map_save 保存  
+/// Tauri command: import a map from a file, the format follows the extension when empty; save the result with map_save
+#[tauri::command]
+pub fn map_import(path: String, format: Option<MapFormat>) -> Result<MapImportResult, String> {
+    let result = import(Path::new(&path), format).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    info!("Imported {} nodes from {}", result.items.len(), path);
+    Ok(result)
+}
+
+fn import(path: &Path, format: Option<MapFormat>) -> Result<MapImportResult, String> {
+    let format = match format {
+        Some(format) => format,
+        None => MapFormat::from_path(path)?,
+    };
+    let text = fs::read_to_string(path).map_err(|e| format!("Read {:?} failed: {}", path, e))?;
+    let draft = match format {
+        MapFormat::Graphml => graphml_import(&text)?,
+        MapFormat::Geojson => geojson_import(&text)?,
+        MapFormat::Dot => dot_import(&text)?,
+        MapFormat::Csv => csv_import(&text)?,
+    };
+    Ok(draft.into_items())
+}
+
+/// Tauri 命令：將已保存的地圖匯出，path 為空時寫到 generate/map/map_info.<ext>，回傳檔案路徑  
+/// Tauri command: export the saved map, writes to generate/map/map_info.<ext> when path is empty, returns the file path
+#[tauri::command]
+pub fn map_export(app: AppHandle, format: MapFormat, path: Option<String>) -> Result<String, String> {
+    let items = map_mod::map_items_read(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let text = match format {
+        MapFormat::Graphml => graphml_export(&items),
+        MapFormat::Geojson => geojson_export(&items),
+        MapFormat::Dot => dot_export(&items),
+        MapFormat::Csv => csv_export(&items),
+    };
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let folder = map_folder(&app);
+            fs::create_dir_all(&folder).map_err(|e| format!("Create folder failed: {}", e))?;
+            folder.join(format!("map_info.{}", format.extension()))
+        }
+    };
+    fs::write(&path, text).map_err(|e| {
+        let msg = format!("Write {:?} failed: {}", path, e);
+        error!("{}", msg);
+        msg
+    })?;
+    info!("Exported map to {:?}", path);
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    /// 1 在原點，2 在東方，3 在北方；3 → 1 的方位刻意與槽位不同  
+    /// 1 at the origin, 2 to the east, 3 to the north; 3 → 1 has a heading that differs from its slot
+    fn sample() -> Vec<InItem> {
+        serde_json::from_value(json!([
+            { "id": 1, "name": "Home", "kind": "station", "x": 0, "y": 0, "connect": [
+                { "pos": 3, "len": 10 }, { "pos": 0, "len": 0 }, { "pos": 2, "len": 10 }, { "pos": 0, "len": 0 },
+                { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 } ] },
+            { "id": 2, "name": "East", "x": 10, "y": 0, "connect": [
+                { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 },
+                { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 1, "len": 10 }, { "pos": 3, "len": 15, "one_way": true } ] },
+            { "id": 3, "name": "North", "kind": "charger", "x": 0, "y": 10, "connect": [
+                { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 },
+                { "pos": 1, "len": 10, "heading": 170 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 }, { "pos": 0, "len": 0 } ] }
+        ])).unwrap()
+    }
+
+    fn edge_summary(items: &[InItem]) -> BTreeMap<(PosType, PosType), (LenType, AngleType, bool)> {
+        edges(items).map(|(item, conn, heading)| ((item.id, conn.pos), (conn.len, heading, conn.one_way))).collect()
+    }
+
+    type NodeSummary = (PosType, String, NodeKind, Option<(CoordType, CoordType)>);
+
+    fn node_summary(items: &[InItem]) -> Vec<NodeSummary> {
+        let mut nodes: Vec<_> = items.iter().map(|item| (item.id, item.name.clone(), item.kind, item.coord())).collect();
+        nodes.sort_by_key(|node| node.0);
+        nodes
+    }
+
+    fn round_trip(export: fn(&[InItem]) -> String, import: fn(&str) -> Result<GraphDraft, String>) -> MapImportResult {
+        let result = import(&export(&sample())).unwrap().into_items();
+        assert!(!result.report.has_errors(), "{}", result.report.error_text());
+        assert_eq!(edge_summary(&result.items), edge_summary(&sample()));
+        result
+    }
+
+    #[test]
+    fn graphml_round_trip() {
+        let result = round_trip(graphml_export, graphml_import);
+        assert_eq!(node_summary(&result.items), node_summary(&sample()));
+    }
+
+    #[test]
+    fn geojson_round_trip() {
+        let result = round_trip(geojson_export, geojson_import);
+        assert_eq!(node_summary(&result.items), node_summary(&sample()));
+    }
+
+    #[test]
+    fn dot_round_trip() {
+        let result = round_trip(dot_export, dot_import);
+        assert_eq!(node_summary(&result.items), node_summary(&sample()));
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        round_trip(csv_export, csv_import);
+    }
+
+    #[test]
+    fn export_slot_headings() {
+        let text = dot_export(&sample());
+        assert!(text.contains("1 -> 2 [label=\"10\", len=10, heading=90];"));
+        assert!(text.contains("3 -> 1 [label=\"10\", len=10, heading=170];"));
+    }
+
+    #[test]
+    fn geojson_rejects_geographic_crs() {
+        let text = json!({ "type": "FeatureCollection",
+            "crs": { "type": "name", "properties": { "name": "urn:ogc:def:crs:OGC:1.3:CRS84" } },
+            "features": [
+                { "type": "Feature", "geometry": { "type": "Point", "coordinates": [121.5654, 25.0330] }, "properties": { "id": 1 } }
+            ] }).to_string();
+        assert!(geojson_import(&text).is_err());
+    }
+
+    #[test]
+    fn geojson_small_metric_map_reports_rounding() {
+        let text = json!({ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "geometry": { "type": "Point", "coordinates": [2.5, 1.0] }, "properties": { "id": 1 } },
+            { "type": "Feature", "geometry": { "type": "Point", "coordinates": [4.0, 1.0] }, "properties": { "id": 2 } },
+            { "type": "Feature", "geometry": { "type": "LineString", "coordinates": [[2.5, 1.0], [4.0, 1.0]] },
+              "properties": { "from": 1, "to": 2, "len": 2 } }
+        ] }).to_string();
+        let result = geojson_import(&text).unwrap().into_items();
+        assert_eq!((result.items[0].x, result.items[0].y), (Some(3), Some(1)));
+        let rounded: Vec<Option<PosType>> = result.report.issues.iter()
+            .filter(|i| i.code == MapIssueCode::CoordRounded)
+            .map(|i| i.node)
+            .collect();
+        assert_eq!(rounded, vec![Some(1)]);
+    }
+}
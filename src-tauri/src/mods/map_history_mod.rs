@@ -0,0 +1,386 @@
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::{mods::{map_mod::{self, InItem, LenType, PosType}, map_route_mod::MapRouteOutput}, GlobalState, MAP_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER};
+
+/// 常數：歷史版本資料夾名稱，位於 generate/map 之下  
+/// Constant: history folder name, under generate/map
+const HISTORY_FOLDER: &str = "history";
+
+/// 常數：首次保存前既有地圖的版本說明  
+/// Constant: description of the version holding the map that existed before the first save
+const BASELINE_DESCRIPTION: &str = "Existing map";
+
+/// 結構：單一歷史版本，保存時的地圖與路由表選項；restored 為此版本還原自的版本  
+/// Struct: one history version, the map and routing options at save time; restored is the version it was restored from
+#[derive(Serialize, Deserialize)]
+struct MapVersion {
+    version:        u32,
+    timestamp:      String,
+    description:    String,
+    routing:        MapRouteOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restored:       Option<u32>,
+    items:          Vec<InItem>,
+}
+
+/// 結構：版本列表項目  
+/// Struct: version list entry
+#[derive(Debug, Clone, Serialize)]
+pub struct MapVersionInfo {
+    pub version:        u32,
+    pub timestamp:      String,
+    pub description:    String,
+    pub nodes:          usize,
+    pub edges:          usize,
+}
+
+/// 結構：有向邊與長度  
+/// Struct: directed edge with its length
+#[derive(Debug, Clone, Serialize)]
+pub struct MapDiffEdge {
+    pub from:   PosType,
+    pub to:     PosType,
+    pub len:    LenType,
+}
+
+/// 結構：長度改變的邊  
+/// Struct: edge whose length changed
+#[derive(Debug, Clone, Serialize)]
+pub struct MapDiffEdgeChange {
+    pub from:       PosType,
+    pub to:         PosType,
+    pub old_len:    LenType,
+    pub new_len:    LenType,
+}
+
+/// 結構：兩個版本間的差異，changed_nodes 為名稱、種類或座標改變的節點  
+/// Struct: differences between two versions, changed_nodes lists nodes whose name, kind or coordinates changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MapDiff {
+    pub from:           u32,
+    pub to:             u32,
+    pub added_nodes:    Vec<PosType>,
+    pub removed_nodes:  Vec<PosType>,
+    pub changed_nodes:  Vec<PosType>,
+    pub added_edges:    Vec<MapDiffEdge>,
+    pub removed_edges:  Vec<MapDiffEdge>,
+    pub changed_edges:  Vec<MapDiffEdgeChange>,
+}
+
+fn history_folder(app: &AppHandle) -> PathBuf {
+    let global_state = app.state::<GlobalState>();
+    let root_path = global_state.root_path.lock().unwrap().clone();
+    root_path.join(ROOT_GEN_FILES_FOLDER).join(MAP_GEN_FILES_FOLDER).join(HISTORY_FOLDER)
+}
+
+fn version_path(folder: &Path, version: u32) -> PathBuf {
+    folder.join(format!("v{:04}.json", version))
+}
+
+/// 列出所有已保存的版本號，由小到大  
+/// Lists all saved version numbers in ascending order
+fn versions(folder: &Path) -> Result<Vec<u32>, String> {
+    if !folder.exists() {
+        return Ok(Vec::new());
+    }
+    let mut versions: Vec<u32> = fs::read_dir(folder)
+        .map_err(|e| format!("Read history folder failed: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_prefix('v')?.strip_suffix(".json")?.parse().ok()
+        })
+        .collect();
+    versions.sort_unstable();
+    Ok(versions)
+}
+
+fn version_load(folder: &Path, version: u32) -> Result<MapVersion, String> {
+    let text = fs::read_to_string(version_path(folder, version))
+        .map_err(|e| format!("Read version {} failed: {}", version, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Parse version {} failed: {}", version, e))
+}
+
+fn edges(items: &[InItem]) -> BTreeMap<(PosType, PosType), LenType> {
+    items.iter()
+        .flat_map(|item| item.connect.iter().filter(|c| c.pos != 0).map(move |c| ((item.id, c.pos), c.len)))
+        .collect()
+}
+
+/// 比較兩份地圖的節點與邊  
+/// Compares the nodes and edges of two maps
+fn diff(old: &[InItem], new: &[InItem]) -> MapDiff {
+    let old_nodes: BTreeMap<PosType, &InItem> = old.iter().map(|item| (item.id, item)).collect();
+    let new_nodes: BTreeMap<PosType, &InItem> = new.iter().map(|item| (item.id, item)).collect();
+    let mut result = MapDiff::default();
+    for (id, item) in &new_nodes {
+        match old_nodes.get(id) {
+            None => result.added_nodes.push(*id),
+            Some(before) => {
+                if before.name != item.name || before.kind != item.kind || before.coord() != item.coord() {
+                    result.changed_nodes.push(*id);
+                }
+            }
+        }
+    }
+    result.removed_nodes = old_nodes.keys().filter(|id| !new_nodes.contains_key(id)).copied().collect();
+
+    let old_edges = edges(old);
+    let new_edges = edges(new);
+    for (&(from, to), &len) in &new_edges {
+        match old_edges.get(&(from, to)) {
+            None => result.added_edges.push(MapDiffEdge { from, to, len }),
+            Some(&old_len) if old_len != len => {
+                result.changed_edges.push(MapDiffEdgeChange { from, to, old_len, new_len: len });
+            }
+            _ => {}
+        }
+    }
+    for (&(from, to), &len) in &old_edges {
+        if !new_edges.contains_key(&(from, to)) {
+            result.removed_edges.push(MapDiffEdge { from, to, len });
+        }
+    }
+    result
+}
+
+/// 將目前的 map_info.json 存為新版本，回傳版本號  
+/// Stores the current map_info.json as a new version, returns the version number
+pub(crate) fn snapshot(app: &AppHandle, description: String, routing: MapRouteOutput) -> Result<u32, String> {
+    version_write(app, description, routing, None)
+}
+
+/// 尚無任何版本時，先將既有的 map_info.json 存為第一個版本，避免被首次保存覆蓋  
+/// When there are no versions yet, stores the existing map_info.json as the first version so the first save does not overwrite it
+pub(crate) fn baseline(app: &AppHandle) -> Result<(), String> {
+    if !versions(&history_folder(app))?.is_empty() || map_mod::map_items_read(app).is_err() {
+        return Ok(());
+    }
+    version_write(app, BASELINE_DESCRIPTION.to_string(), MapRouteOutput::default(), None).map(|_| ())
+}
+
+fn version_write(app: &AppHandle, description: String, routing: MapRouteOutput, restored: Option<u32>) -> Result<u32, String> {
+    let items = map_mod::map_items_read(app)?;
+    let folder = history_folder(app);
+    fs::create_dir_all(&folder).map_err(|e| format!("Create history folder failed: {}", e))?;
+    let version = versions(&folder)?.last().map_or(1, |v| v + 1);
+    let snapshot = MapVersion {
+        version,
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        description,
+        routing,
+        restored,
+        items,
+    };
+    let text = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Serialize JSON error: {}", e))?;
+    fs::write(version_path(&folder, version), text)
+        .map_err(|e| format!("Write version {} failed: {}", version, e))?;
+    info!("Saved map version {}", version);
+    Ok(version)
+}
+
+/// 以指定版本重新產生地圖檔案，並記錄為新版本  
+/// Regenerates the map files from the given version and records it as a new version
+fn restore(app: &AppHandle, version: u32) -> Result<String, String> {
+    let folder = history_folder(app);
+    let saved = version_load(&folder, version)?;
+    let path = map_mod::map_items_save(app, saved.items, saved.routing, false)?;
+    let new_version = version_write(app, format!("Restore v{}", version), saved.routing, Some(version))?;
+    info!("Restored map version {} as version {}", version, new_version);
+    Ok(path)
+}
+
+/// Tauri 命令：列出所有地圖版本  
+/// Tauri command: list all map versions
+#[tauri::command]
+pub fn map_history_list(app: AppHandle) -> Result<Vec<MapVersionInfo>, String> {
+    let folder = history_folder(&app);
+    let mut list = Vec::new();
+    for version in versions(&folder)? {
+        let snapshot = version_load(&folder, version).map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+        list.push(MapVersionInfo {
+            version,
+            timestamp: snapshot.timestamp,
+            description: snapshot.description,
+            nodes: snapshot.items.len(),
+            edges: edges(&snapshot.items).len(),
+        });
+    }
+    Ok(list)
+}
+
+/// Tauri 命令：比較兩個版本，to 為空時與最新版本比較  
+/// Tauri command: compare two versions, compares against the latest when `to` is empty
+#[tauri::command]
+pub fn map_history_diff(app: AppHandle, from: u32, to: Option<u32>) -> Result<MapDiff, String> {
+    let folder = history_folder(&app);
+    diff_versions(&folder, from, to).map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+fn diff_versions(folder: &Path, from: u32, to: Option<u32>) -> Result<MapDiff, String> {
+    let to = match to {
+        Some(to) => to,
+        None => *versions(folder)?.last().ok_or("No saved versions")?,
+    };
+    let old = version_load(folder, from)?;
+    let new = version_load(folder, to)?;
+    Ok(MapDiff { from, to, ..diff(&old.items, &new.items) })
+}
+
+/// Tauri 命令：還原指定版本  
+/// Tauri command: restore the given version
+#[tauri::command]
+pub fn map_history_restore(app: AppHandle, version: u32) -> Result<String, String> {
+    restore(&app, version).map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// 由 (版本, 還原來源) 列表找出復原目標：還原版本代表其來源，目前地圖的前一版即為目標，重複復原會逐步往回  
+/// Finds the undo target from (version, restored from) pairs: a restore stands for its source,
+/// and the version before the current map is the target, so repeated undos keep stepping back
+fn undo_target(chain: &[(u32, Option<u32>)]) -> Option<u32> {
+    let restored_from = |version: u32| {
+        chain.iter().find(|(v, _)| *v == version).and_then(|(_, restored)| *restored)
+    };
+    let resolve = |mut version: u32| {
+        while let Some(source) = restored_from(version).filter(|&source| source < version) {
+            version = source;
+        }
+        version
+    };
+    let current = resolve(chain.last()?.0);
+    let previous = chain.iter().rev().map(|(v, _)| *v).find(|&v| v < current)?;
+    Some(resolve(previous))
+}
+
+/// Tauri 命令：復原上一次保存，連續呼叫會依序往更早的版本復原  
+/// Tauri command: undo the last save, repeated calls keep stepping back to earlier versions
+#[tauri::command]
+pub fn map_history_undo(app: AppHandle) -> Result<String, String> {
+    let folder = history_folder(&app);
+    let mut chain = Vec::new();
+    for version in versions(&folder)? {
+        chain.push((version, version_load(&folder, version)?.restored));
+    }
+    let Some(previous) = undo_target(&chain) else {
+        let msg = "Nothing to undo".to_string();
+        error!("{}", msg);
+        return Err(msg);
+    };
+    restore(&app, previous).map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use super::*;
+
+    /// 依 (目標, 長度) 建立節點，其餘槽位為空  
+    /// Builds a node from (target, length) pairs, remaining slots are empty
+    fn node(id: PosType, name: &str, x: i64, links: &[(PosType, LenType)]) -> Value {
+        let mut connect: Vec<Value> = links.iter().map(|(pos, len)| json!({ "pos": pos, "len": len })).collect();
+        connect.resize(map_mod::MAX_CONNECTIONS, json!({ "pos": 0, "len": 0 }));
+        json!({ "id": id, "name": name, "x": x, "y": 0, "connect": connect })
+    }
+
+    fn items(nodes: Vec<Value>) -> Vec<InItem> {
+        serde_json::from_value(Value::Array(nodes)).unwrap()
+    }
+
+    fn edge_pairs(edges: &[MapDiffEdge]) -> Vec<(PosType, PosType, LenType)> {
+        edges.iter().map(|e| (e.from, e.to, e.len)).collect()
+    }
+
+    #[test]
+    fn diff_nodes_added_removed_changed() {
+        let old = items(vec![node(1, "A", 0, &[]), node(2, "B", 10, &[]), node(3, "C", 20, &[])]);
+        let mut new_nodes = vec![node(1, "A", 0, &[]), node(2, "B2", 10, &[]), node(3, "C", 25, &[]), node(4, "D", 30, &[])];
+        new_nodes[0]["kind"] = json!("station");
+        let result = diff(&old, &items(new_nodes));
+        assert_eq!(result.added_nodes, vec![4]);
+        assert!(result.removed_nodes.is_empty());
+        assert_eq!(result.changed_nodes, vec![1, 2, 3]);
+
+        let result = diff(&old, &items(vec![node(1, "A", 0, &[]), node(3, "C", 20, &[])]));
+        assert_eq!(result.removed_nodes, vec![2]);
+        assert!(result.added_nodes.is_empty() && result.changed_nodes.is_empty());
+    }
+
+    #[test]
+    fn diff_edges_added_removed_changed() {
+        let old = items(vec![node(1, "A", 0, &[(2, 10), (3, 5)]), node(2, "B", 10, &[(1, 10)]), node(3, "C", 20, &[])]);
+        let new = items(vec![node(1, "A", 0, &[(2, 12)]), node(2, "B", 10, &[(1, 10), (3, 7)]), node(3, "C", 20, &[])]);
+        let result = diff(&old, &new);
+        assert!(result.added_nodes.is_empty() && result.removed_nodes.is_empty() && result.changed_nodes.is_empty());
+        assert_eq!(edge_pairs(&result.added_edges), vec![(2, 3, 7)]);
+        assert_eq!(edge_pairs(&result.removed_edges), vec![(1, 3, 5)]);
+        let changed: Vec<_> = result.changed_edges.iter().map(|e| (e.from, e.to, e.old_len, e.new_len)).collect();
+        assert_eq!(changed, vec![(1, 2, 10, 12)]);
+    }
+
+    #[test]
+    fn diff_versions_defaults_to_latest() {
+        let folder = std::env::temp_dir().join(format!("map_history_diff_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let maps = [
+            items(vec![node(1, "A", 0, &[])]),
+            items(vec![node(1, "A", 0, &[]), node(2, "B", 10, &[])]),
+            items(vec![node(1, "A", 0, &[]), node(2, "B", 10, &[]), node(3, "C", 20, &[])]),
+        ];
+        for (i, items) in maps.into_iter().enumerate() {
+            let version = i as u32 + 1;
+            let saved = MapVersion {
+                version,
+                timestamp: String::new(),
+                description: String::new(),
+                routing: MapRouteOutput::default(),
+                restored: None,
+                items,
+            };
+            fs::write(version_path(&folder, version), serde_json::to_string(&saved).unwrap()).unwrap();
+        }
+        let latest = diff_versions(&folder, 1, None);
+        let explicit = diff_versions(&folder, 1, Some(2));
+        let _ = fs::remove_dir_all(&folder);
+        let latest = latest.unwrap();
+        assert_eq!((latest.from, latest.to, latest.added_nodes), (1, 3, vec![2, 3]));
+        let explicit = explicit.unwrap();
+        assert_eq!((explicit.from, explicit.to, explicit.added_nodes), (1, 2, vec![2]));
+    }
+
+    #[test]
+    fn undo_steps_back_through_restores() {
+        let mut chain = vec![(1, None), (2, None), (3, None)];
+        assert_eq!(undo_target(&chain), Some(2));
+        chain.push((4, Some(2)));
+        assert_eq!(undo_target(&chain), Some(1));
+        chain.push((5, Some(1)));
+        assert_eq!(undo_target(&chain), None);
+    }
+
+    #[test]
+    fn undo_after_save_returns_to_restored_map() {
+        let chain = vec![(1, None), (2, None), (3, Some(1)), (4, None)];
+        assert_eq!(undo_target(&chain), Some(1));
+    }
+
+    #[test]
+    fn undo_needs_two_versions() {
+        assert_eq!(undo_target(&[]), None);
+        assert_eq!(undo_target(&[(1, None)]), None);
+    }
+}
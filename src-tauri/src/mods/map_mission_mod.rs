@@ -0,0 +1,565 @@
+use std::{fs, path::PathBuf};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{
+    mods::{
+        map_mod::{self, AngleType, LenType, PosType},
+        map_route_mod::MapGraph,
+        map_track_mod::{LocationState, VehicleLocation},
+        mcu_const, mcu_control_mod,
+    },
+    GlobalState, MAP_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER,
+};
+
+/// 常數：任務資料夾名稱，位於 generate/map 之下  
+/// Constant: mission folder name, under generate/map
+const MISSION_FOLDER: &str = "mission";
+
+/// 常數：任務處理週期（ms），每週期最多送出一個航點  
+/// Constant: mission process period in ms, at most one waypoint is sent per period
+pub const MISSION_PERIOD_MS: u64 = 100;
+
+/// 列舉：任務步驟  
+/// Enum: mission step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MissionStep {
+    Goto { node: PosType },     // 前往節點／drive to a node
+    Wait { ms: u32 },           // 原地等待／wait in place
+}
+
+/// 結構：任務定義，以名稱區分  
+/// Struct: mission definition, identified by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mission {
+    pub name:   String,
+    pub steps:  Vec<MissionStep>,
+}
+
+/// 結構：送給 MCU 的航點，heading 與 len 為由前一航點前往 node 的邊  
+/// Struct: waypoint sent to the MCU, heading and len describe the edge from the previous waypoint to node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub node:       PosType,
+    pub heading:    AngleType,
+    pub len:        LenType,
+    pub wait_ms:    u32,
+}
+impl Waypoint {
+    /// 編碼為航點指令  
+    /// Encodes the waypoint command
+    fn encode(&self, id: u16, index: u16) -> Vec<u8> {
+        let mut cmd = vec![mcu_const::CMD_CODE_MISSION, mcu_const::MISSION_CMD_WAYPOINT];
+        cmd.extend_from_slice(&id.to_be_bytes());
+        cmd.extend_from_slice(&index.to_be_bytes());
+        cmd.extend_from_slice(&self.node.to_be_bytes());
+        cmd.extend_from_slice(&self.heading.to_be_bytes());
+        cmd.extend_from_slice(&self.len.to_be_bytes());
+        cmd.extend_from_slice(&self.wait_ms.to_be_bytes());
+        cmd
+    }
+}
+
+/// 列舉：任務執行狀態  
+/// Enum: mission run status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissionStatus {
+    Planned,
+    Running,
+    Paused,
+    Completed,
+    Aborted,
+}
+
+/// 結構：任務執行進度，sent 為已送出的航點數，reached 為已抵達的航點數  
+/// Struct: mission run progress, sent counts waypoints sent and reached counts waypoints reached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionRun {
+    pub id:         u16,
+    pub name:       String,
+    pub start:      PosType,
+    pub status:     MissionStatus,
+    pub waypoints:  Vec<Waypoint>,
+    pub sent:       usize,
+    pub reached:    usize,
+    #[serde(skip)]
+    departed:       bool,   // 已離開前一航點／left the previous waypoint
+}
+impl MissionRun {
+    fn control_encode(&self, code: u8) -> Vec<u8> {
+        let mut cmd = vec![mcu_const::CMD_CODE_MISSION, code];
+        cmd.extend_from_slice(&self.id.to_be_bytes());
+        cmd
+    }
+
+    fn begin_encode(&self) -> Vec<u8> {
+        let mut cmd = self.control_encode(mcu_const::MISSION_CMD_BEGIN);
+        cmd.extend_from_slice(&self.start.to_be_bytes());
+        cmd.extend_from_slice(&(self.waypoints.len() as u16).to_be_bytes());
+        cmd
+    }
+
+    /// 下一個可送出的航點，最多領先已抵達的航點 MISSION_WINDOW 個  
+    /// The next waypoint that may be sent, at most MISSION_WINDOW ahead of the reached ones
+    fn pending(&self) -> Option<Vec<u8>> {
+        if self.status != MissionStatus::Running
+            || self.sent >= self.waypoints.len()
+            || self.sent >= self.reached + mcu_const::MISSION_WINDOW as usize
+        {
+            return None;
+        }
+        Some(self.waypoints[self.sent].encode(self.id, self.sent as u16))
+    }
+
+    /// 前一個航點的節點，尚未抵達任何航點時為起點  
+    /// The node of the previous waypoint, the start before any waypoint is reached
+    fn previous(&self) -> PosType {
+        match self.reached {
+            0 => self.start,
+            reached => self.waypoints[reached - 1].node,
+        }
+    }
+
+    /// 依車輛位置更新已抵達的航點，狀態改變時回傳 true；
+    /// 只有離開前一航點後抵達下一個航點才算抵達，不會跳過航點  
+    /// Updates the reached waypoints from the vehicle location, returns true when anything changed;
+    /// a waypoint only counts once the vehicle arrives at it after leaving the previous one, waypoints are never skipped
+    fn locate(&mut self, location: &VehicleLocation) -> bool {
+        if !matches!(self.status, MissionStatus::Running | MissionStatus::Paused)
+            || location.state == LocationState::OffMap
+        {
+            return false;
+        }
+        let mut changed = false;
+        while self.reached < self.sent {
+            let previous = self.previous();
+            let target = self.waypoints[self.reached].node;
+            // 原地航點不需移動；車輛不在前一航點即視為已離開
+            // in-place waypoints need no move; the vehicle has left once it is anywhere but the previous waypoint
+            if target == previous || location.node != previous {
+                self.departed = true;
+            }
+            if !self.departed || location.node != target {
+                break;
+            }
+            self.reached += 1;
+            self.departed = false;
+            changed = true;
+        }
+        // 車輛可能在未停下的情況下通過最後一個航點
+        // the vehicle may pass the last waypoint without stopping on it
+        if self.reached == self.waypoints.len() {
+            self.status = MissionStatus::Completed;
+        }
+        changed
+    }
+}
+
+/// 結構：任務管理器，保存目前執行中的任務  
+/// Struct: mission manager, holds the current mission run
+pub struct MissionManager {
+    run:        Option<MissionRun>,
+    next_id:    u16,
+}
+impl MissionManager {
+    /// 建立沒有任務的管理器  
+    /// Creates a manager without a mission
+    pub fn new() -> Self {
+        Self {
+            run: None,
+            next_id: 1,
+        }
+    }
+
+    /// 目前的任務進度  
+    /// The current mission run
+    pub fn run(&self) -> Option<MissionRun> {
+        self.run.clone()
+    }
+}
+
+impl Default for MissionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn mission_folder(app: &AppHandle) -> PathBuf {
+    let global_state = app.state::<GlobalState>();
+    let root_path = global_state.root_path.lock().unwrap().clone();
+    root_path.join(ROOT_GEN_FILES_FOLDER).join(MAP_GEN_FILES_FOLDER).join(MISSION_FOLDER)
+}
+
+/// 讀取已保存的任務定義，檔案不存在時為空  
+/// Reads the saved mission definitions, empty when the file does not exist
+fn missions_read(app: &AppHandle) -> Result<Vec<Mission>, String> {
+    let path = mission_folder(app).join("missions.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(&path).map_err(|e| format!("Read missions failed: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Parse missions failed: {}", e))
+}
+
+fn missions_write(app: &AppHandle, missions: &[Mission]) -> Result<(), String> {
+    let folder = mission_folder(app);
+    fs::create_dir_all(&folder).map_err(|e| format!("Create mission folder failed: {}", e))?;
+    let text = serde_json::to_string_pretty(missions)
+        .map_err(|e| format!("Serialize JSON error: {}", e))?;
+    fs::write(folder.join("missions.json"), text).map_err(|e| format!("Write missions failed: {}", e))
+}
+
+/// 將任務進度寫入 run.json 並通知前端  
+/// Writes the mission run to run.json and notifies the frontend
+fn run_publish(app: &AppHandle, run: &MissionRun) {
+    let folder = mission_folder(app);
+    let result = fs::create_dir_all(&folder)
+        .map_err(|e| format!("Create mission folder failed: {}", e))
+        .and_then(|_| serde_json::to_string_pretty(run).map_err(|e| format!("Serialize JSON error: {}", e)))
+        .and_then(|text| fs::write(folder.join("run.json"), text).map_err(|e| format!("Write run failed: {}", e)));
+    if let Err(e) = result {
+        error!("{}", e);
+    }
+    if let Err(e) = app.emit("mission-progress", run) {
+        error!("Emit mission-progress failed: {}", e);
+    }
+}
+
+/// 由起點依序規劃任務步驟的路徑，展開為航點；等待累加到前一個航點，尚未移動時在起點建立原地航點  
+/// Plans the route of each mission step from the start and expands it into waypoints;
+/// waits add to the previous waypoint, or to an in-place waypoint at the start before any move
+fn plan(graph: &MapGraph, mission: &Mission, start: PosType) -> Result<Vec<Waypoint>, String> {
+    let mut waypoints: Vec<Waypoint> = Vec::new();
+    let mut current = start;
+    for step in &mission.steps {
+        match *step {
+            MissionStep::Goto { node } => {
+                let route = graph.shortest(current, node)?
+                    .ok_or_else(|| format!("No route from {} to {}", current, node))?;
+                for pair in route.path.windows(2) {
+                    let (len, heading) = graph.edge(pair[0], pair[1])
+                        .ok_or_else(|| format!("No connection from {} to {}", pair[0], pair[1]))?;
+                    waypoints.push(Waypoint { node: pair[1], heading, len: len as LenType, wait_ms: 0 });
+                }
+                current = node;
+            }
+            MissionStep::Wait { ms } => {
+                if waypoints.is_empty() {
+                    waypoints.push(Waypoint { node: start, heading: 0, len: 0, wait_ms: 0 });
+                }
+                let last = waypoints.last_mut().unwrap();
+                last.wait_ms = last.wait_ms.saturating_add(ms);
+            }
+        }
+    }
+    if waypoints.is_empty() {
+        return Err(format!("Mission {} has no waypoints", mission.name));
+    }
+    if waypoints.len() > u16::MAX as usize {
+        return Err(format!("Mission {} has too many waypoints: {}", mission.name, waypoints.len()));
+    }
+    Ok(waypoints)
+}
+
+/// 規劃任務，start 為空時以車輛目前所在節點為起點  
+/// Plans a mission, starting from the vehicle's current node when start is empty
+async fn run_plan(app: &AppHandle, mission: &Mission, start: Option<PosType>) -> Result<MissionRun, String> {
+    let start = match start {
+        Some(start) => start,
+        None => {
+            let global_state = app.state::<GlobalState>();
+            let location = global_state.vehicle_tracker.lock().await.last();
+            location
+                .filter(|location| location.state != LocationState::OffMap)
+                .map(|location| location.node)
+                .ok_or("Vehicle location unknown, a start node is required")?
+        }
+    };
+    let items = map_mod::map_items_read(app)?;
+    let waypoints = plan(&MapGraph::new(&items), mission, start)?;
+    Ok(MissionRun {
+        id: 0,
+        name: mission.name.clone(),
+        start,
+        status: MissionStatus::Planned,
+        waypoints,
+        sent: 0,
+        reached: 0,
+        departed: false,
+    })
+}
+
+/// 每週期送出下一個航點  
+/// Sends the next waypoint every period
+pub async fn mission_proccess(app: AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    let mut manager = global_state.mission_manager.lock().await;
+    let Some(run) = manager.run.as_mut() else { return; };
+    let Some(cmd) = run.pending() else { return; };
+    if let Err(e) = mcu_control_mod::transmit_push(&app, cmd).await {
+        error!("Mission waypoint send failed: {}", e);
+        return;
+    }
+    run.sent += 1;
+    run_publish(&app, run);
+}
+
+/// 依車輛位置更新任務進度  
+/// Updates the mission progress from the vehicle location
+pub async fn mission_locate(app: &AppHandle, location: &VehicleLocation) {
+    let global_state = app.state::<GlobalState>();
+    let mut manager = global_state.mission_manager.lock().await;
+    let Some(run) = manager.run.as_mut() else { return; };
+    if !run.locate(location) {
+        return;
+    }
+    if run.status == MissionStatus::Completed {
+        info!("Mission {} completed", run.name);
+    }
+    run_publish(app, run);
+}
+
+/// 改變執行中任務的狀態並送出對應指令  
+/// Changes the status of the current mission and sends the matching command
+async fn run_control(app: &AppHandle, from: &[MissionStatus], to: MissionStatus, code: u8) -> Result<MissionRun, String> {
+    let global_state = app.state::<GlobalState>();
+    let mut manager = global_state.mission_manager.lock().await;
+    let run = manager.run.as_mut().ok_or("No mission")?;
+    if !from.contains(&run.status) {
+        return Err(format!("Mission {} is {:?}", run.name, run.status));
+    }
+    mcu_control_mod::transmit_push(app, run.control_encode(code)).await?;
+    run.status = to;
+    info!("Mission {} {:?}", run.name, to);
+    run_publish(app, run);
+    Ok(run.clone())
+}
+
+/// Tauri 命令：列出已保存的任務  
+/// Tauri command: list the saved missions
+#[tauri::command]
+pub fn map_mission_list(app: AppHandle) -> Result<Vec<Mission>, String> {
+    missions_read(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// Tauri 命令：保存任務，同名任務會被取代  
+/// Tauri command: save a mission, replacing one with the same name
+#[tauri::command]
+pub fn map_mission_save(app: AppHandle, mission: Mission) -> Result<Vec<Mission>, String> {
+    if mission.name.trim().is_empty() || mission.steps.is_empty() {
+        let msg = "Mission needs a name and at least one step".to_string();
+        error!("{}", msg);
+        return Err(msg);
+    }
+    let mut missions = missions_read(&app)?;
+    match missions.iter_mut().find(|m| m.name == mission.name) {
+        Some(existing) => *existing = mission,
+        None => missions.push(mission),
+    }
+    missions_write(&app, &missions).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    Ok(missions)
+}
+
+/// Tauri 命令：刪除任務  
+/// Tauri command: delete a mission
+#[tauri::command]
+pub fn map_mission_delete(app: AppHandle, name: String) -> Result<Vec<Mission>, String> {
+    let mut missions = missions_read(&app)?;
+    missions.retain(|m| m.name != name);
+    missions_write(&app, &missions).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    Ok(missions)
+}
+
+/// Tauri 命令：預覽任務的航點，不送出  
+/// Tauri command: preview the waypoints of a mission without sending
+#[tauri::command]
+pub async fn map_mission_plan(app: AppHandle, mission: Mission, start: Option<PosType>) -> Result<MissionRun, String> {
+    run_plan(&app, &mission, start).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// Tauri 命令：開始已保存的任務，送出開始指令後由 mission_proccess 陸續送出航點  
+/// Tauri command: start a saved mission, sends the begin command and mission_proccess sends the waypoints
+#[tauri::command]
+pub async fn map_mission_start(app: AppHandle, name: String, start: Option<PosType>) -> Result<MissionRun, String> {
+    let result = async {
+        let mission = missions_read(&app)?
+            .into_iter()
+            .find(|m| m.name == name)
+            .ok_or_else(|| format!("Mission {} not found", name))?;
+        let mut run = run_plan(&app, &mission, start).await?;
+        let global_state = app.state::<GlobalState>();
+        let mut manager = global_state.mission_manager.lock().await;
+        if let Some(current) = manager.run.as_ref() {
+            if matches!(current.status, MissionStatus::Running | MissionStatus::Paused) {
+                return Err(format!("Mission {} is still {:?}", current.name, current.status));
+            }
+        }
+        run.id = manager.next_id;
+        mcu_control_mod::transmit_push(&app, run.begin_encode()).await?;
+        manager.next_id = manager.next_id.wrapping_add(1).max(1);
+        run.status = MissionStatus::Running;
+        info!("Mission {} started from {} with {} waypoints", run.name, run.start, run.waypoints.len());
+        run_publish(&app, &run);
+        manager.run = Some(run.clone());
+        Ok(run)
+    }.await;
+    result.map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// Tauri 命令：暫停任務  
+/// Tauri command: pause the mission
+#[tauri::command]
+pub async fn map_mission_pause(app: AppHandle) -> Result<MissionRun, String> {
+    run_control(&app, &[MissionStatus::Running], MissionStatus::Paused, mcu_const::MISSION_CMD_PAUSE).await
+        .map_err(|e| {
+            error!("{}", e);
+            e
+        })
+}
+
+/// Tauri 命令：繼續已暫停的任務  
+/// Tauri command: resume the paused mission
+#[tauri::command]
+pub async fn map_mission_resume(app: AppHandle) -> Result<MissionRun, String> {
+    run_control(&app, &[MissionStatus::Paused], MissionStatus::Running, mcu_const::MISSION_CMD_RESUME).await
+        .map_err(|e| {
+            error!("{}", e);
+            e
+        })
+}
+
+/// Tauri 命令：中止任務，MCU 收到後停車並清除航點  
+/// Tauri command: abort the mission, the MCU stops and drops its waypoints
+#[tauri::command]
+pub async fn map_mission_abort(app: AppHandle) -> Result<MissionRun, String> {
+    let run = run_control(
+        &app,
+        &[MissionStatus::Running, MissionStatus::Paused],
+        MissionStatus::Aborted,
+        mcu_const::MISSION_CMD_ABORT,
+    ).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    warn!("Mission {} aborted at waypoint {}/{}", run.name, run.reached, run.waypoints.len());
+    Ok(run)
+}
+
+/// Tauri 命令：取得目前的任務進度  
+/// Tauri command: get the current mission run
+#[tauri::command]
+pub async fn map_mission_status(app: AppHandle) -> Result<Option<MissionRun>, String> {
+    let global_state = app.state::<GlobalState>();
+    let manager = global_state.mission_manager.lock().await;
+    Ok(manager.run())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waypoint(node: PosType) -> Waypoint {
+        Waypoint { node, heading: 0, len: 10, wait_ms: 0 }
+    }
+
+    fn run(start: PosType, nodes: &[PosType]) -> MissionRun {
+        MissionRun {
+            id: 1,
+            name: "test".to_string(),
+            start,
+            status: MissionStatus::Running,
+            waypoints: nodes.iter().map(|&node| waypoint(node)).collect(),
+            sent: nodes.len(),
+            reached: 0,
+            departed: false,
+        }
+    }
+
+    fn at(node: PosType) -> VehicleLocation {
+        on(node, None)
+    }
+
+    fn on(node: PosType, next: Option<PosType>) -> VehicleLocation {
+        VehicleLocation {
+            state: if next.is_some() { LocationState::OnEdge } else { LocationState::AtNode },
+            node,
+            name: None,
+            next,
+            travelled: 0,
+            edge_len: None,
+            progress: None,
+            message: None,
+            ts_ms: 0,
+        }
+    }
+
+    #[test]
+    fn out_and_back_waits_for_return() {
+        let mut run = run(1, &[2, 1]);
+        assert!(!run.locate(&at(1)));
+        assert_eq!(run.reached, 0);
+        assert!(!run.locate(&on(1, Some(2))));
+        assert!(run.locate(&at(2)));
+        assert_eq!(run.reached, 1);
+        assert!(!run.locate(&at(2)));
+        assert!(!run.locate(&on(2, Some(1))));
+        assert_eq!(run.status, MissionStatus::Running);
+        assert!(run.locate(&at(1)));
+        assert_eq!(run.reached, 2);
+        assert_eq!(run.status, MissionStatus::Completed);
+    }
+
+    #[test]
+    fn waypoints_are_not_skipped() {
+        let mut run = run(1, &[2, 3, 4]);
+        assert!(!run.locate(&at(3)));
+        assert_eq!(run.reached, 0);
+        assert!(run.locate(&at(2)));
+        assert!(run.locate(&on(3, Some(4))));
+        assert_eq!(run.reached, 2);
+    }
+
+    #[test]
+    fn unsent_waypoints_are_not_reached() {
+        let mut run = run(1, &[2, 3]);
+        run.sent = 1;
+        assert!(run.locate(&at(2)));
+        assert!(!run.locate(&at(3)));
+        assert_eq!(run.reached, 1);
+    }
+
+    #[test]
+    fn passing_last_waypoint_completes() {
+        let mut run = run(1, &[2]);
+        assert!(!run.locate(&on(1, Some(2))));
+        assert!(run.locate(&on(2, Some(3))));
+        assert_eq!(run.status, MissionStatus::Completed);
+    }
+
+    #[test]
+    fn wait_at_start_is_reached_in_place() {
+        let mut run = run(1, &[1, 2]);
+        assert!(run.locate(&at(1)));
+        assert_eq!(run.reached, 1);
+        assert!(!run.locate(&at(1)));
+        assert!(run.locate(&at(2)));
+        assert_eq!(run.status, MissionStatus::Completed);
+    }
+}
@@ -1,17 +1,26 @@
-use std::fs;
-use log::{error, info};
+use std::{fs, path::PathBuf};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
-use crate::{mods::directory_mod::create_file, GlobalState, MAP_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER};
+use crate::{mods::{directory_mod::create_file, map_history_mod, map_normalize_mod, map_route_mod::{self, MapGraph, MapRouteOutput}, map_validate_mod}, GlobalState, MAP_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER};
 
 /// 常數：用於 C 程式碼縮排  
 /// Constant: indentation for generated C code
-const TAB_SPACE: &str = "    ";
+pub(crate) const TAB_SPACE: &str = "    ";
 
 /// 型別別名：位置與長度資料型態  
 /// Type alias: data types for position and length
-type PosType = u16;
-type LenType = u32;
+pub(crate) type PosType = u16;
+pub(crate) type LenType = u32;
+
+/// 型別別名：座標（與長度同單位）與角度（度，北方為 0，順時針）資料型態  
+/// Type alias: coordinates (same unit as length) and angles (degrees, north is 0, clockwise)
+pub(crate) type CoordType = i32;
+pub(crate) type AngleType = i16;
+
+/// 常數：每個節點的連接上限，需與 map_base.h 的 MAX_CONNECTIONS 一致  
+/// Constant: connection limit per node, must match MAX_CONNECTIONS in map_base.h
+pub(crate) const MAX_CONNECTIONS: usize = 8;
 
 /// 常數：儲存檔案的資料夾路徑  
 /// Constant: folder path to store generated files
@@ -19,38 +28,90 @@ const MAP_BASE_H: &str = include_str!(
     concat!(env!("CARGO_MANIFEST_DIR"), "/generate_base/map/map_base.h")
 );
 
-/// 結構：MapConnect，定義 pos 和 len 欄位  
-/// Struct: MapConnect with pos and len fields
+/// 列舉：節點種類，需與 map_base.h 的 NODE_TYPE 一致  
+/// Enum: node kind, must match NODE_TYPE in map_base.h
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum NodeKind {
+    #[default]
+    Junction,
+    Station,
+    Charger,
+}
+impl NodeKind {
+    fn c_name(&self) -> &'static str {
+        match self {
+            NodeKind::Junction => "NODE_JUNCTION",
+            NodeKind::Station => "NODE_STATION",
+            NodeKind::Charger => "NODE_CHARGER",
+        }
+    }
+}
+
+/// 結構：MapConnect，定義 pos、len、出發方位與單向旗標  
+/// Struct: MapConnect with pos, len, departure heading and one-way flag
 #[derive(Serialize)]
 struct MapConnect {
-    pos:    PosType,
-    len:    LenType,
+    pos:        PosType,
+    len:        LenType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heading:    Option<AngleType>,
+    one_way:    bool,
 }
 
-/// 結構：MapItem，包含 id、名稱與多個連接  
-/// Struct: MapItem with id, name, and list of connects
+/// 結構：MapItem，包含 id、名稱、種類、座標與多個連接  
+/// Struct: MapItem with id, name, kind, coordinates and list of connects
 #[derive(Serialize)]
 struct MapItem {
     id:         PosType,
     name:       String,
+    kind:       NodeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x:          Option<CoordType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y:          Option<CoordType>,
     connect:    Vec<MapConnect>,
 }
 
-/// 輸入連接結構：InConnect，用於反序列化 JSON  
-/// Input struct: InConnect for JSON deserialization
-#[derive(Deserialize)]
-struct InConnect {
-    pos:    PosType,
-    len:    LenType,
+/// 輸入連接結構：InConnect，用於反序列化 JSON；新欄位皆有預設值以相容舊檔  
+/// Input struct: InConnect for JSON deserialization; new fields default so older files still load
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InConnect {
+    pub(crate) pos:     PosType,
+    pub(crate) len:     LenType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) heading: Option<AngleType>,  // 出發方位，未設定時依槽位方向／departure heading, slot direction when unset
+    #[serde(default)]
+    pub(crate) one_way: bool,               // 刻意的單向邊／intentional one-way edge
+}
+impl InConnect {
+    /// 出發方位，未設定時以槽位 N, EN, E ... 每 45 度推算  
+    /// Departure heading, derived from the slot (N, EN, E ... every 45 degrees) when unset
+    pub(crate) fn heading_or_slot(&self, slot: usize) -> AngleType {
+        self.heading.unwrap_or((slot * 45) as AngleType)
+    }
 }
 
-/// 輸入項目結構：InItem，用於反序列化 JSON  
-/// Input struct: InItem for JSON deserialization
-#[derive(Deserialize)]
-struct InItem {
-    id:         PosType,
-    name:       String,
-    connect:    Vec<InConnect>,
+/// 輸入項目結構：InItem，用於反序列化 JSON；新欄位皆有預設值以相容舊檔  
+/// Input struct: InItem for JSON deserialization; new fields default so older files still load
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InItem {
+    pub(crate) id:      PosType,
+    pub(crate) name:    String,
+    #[serde(default)]
+    pub(crate) kind:    NodeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) x:       Option<CoordType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) y:       Option<CoordType>,
+    pub(crate) connect: Vec<InConnect>,
+}
+impl InItem {
+    /// 座標，x 與 y 皆設定時才有值  
+    /// Coordinates, only present when both x and y are set
+    pub(crate) fn coord(&self) -> Option<(CoordType, CoordType)> {
+        self.x.zip(self.y)
+    }
 }
 
 /// Tauri 命令：載入現存的 JSON 檔案  
@@ -78,12 +139,34 @@ pub fn map_load(app: AppHandle) -> Result<String, String> {
     Ok(result)
 }
 
-/// 解析輸入的 JSON 字串，並生成 C/C++ 初始值陣列和新的 JSON 檔案。  
-/// Parses incoming JSON `data` and generates a C initializer array plus a JSON file.
+/// 已保存的 map_info.json 路徑  
+/// Path of the saved map_info.json
+pub(crate) fn map_info_path(app: &AppHandle) -> PathBuf {
+    let global_state = app.state::<GlobalState>();
+    let root_path = global_state.root_path.lock().unwrap().clone();
+    root_path.join(ROOT_GEN_FILES_FOLDER).join(MAP_GEN_FILES_FOLDER).join("map_info.json")
+}
+
+/// 讀取已保存的 map_info.json 並解析為 InItem 向量  
+/// Reads the saved map_info.json and parses it into a Vec<InItem>
+pub(crate) fn map_items_read(app: &AppHandle) -> Result<Vec<InItem>, String> {
+    let text = fs::read_to_string(map_info_path(app))
+        .map_err(|e| format!("Read JSON failed: {}", e))?;
+    if text.trim().is_empty() {
+        return Err("Map is not saved yet".into());
+    }
+    serde_json::from_str(&text).map_err(|e| format!("JSON parse error: {}", e))
+}
+
+/// 解析輸入的 JSON 字串，並生成 C/C++ 初始值陣列和新的 JSON 檔案；routing 可選擇額外產生路由表，mirror 自動補齊反向連接，成功後存為新的歷史版本。  
+/// Parses incoming JSON `data` and generates a C initializer array plus a JSON file; `routing` optionally adds routing tables, `mirror` completes reverse connections, and each save becomes a new history version.
 #[tauri::command]
 pub fn map_save(
     app: AppHandle,
-    data: String
+    data: String,
+    routing: Option<MapRouteOutput>,
+    mirror: Option<bool>,
+    description: Option<String>,
 ) -> Result<String, String> {
     // 反序列化 JSON 並轉成 InItem 向量
     // Deserialize JSON into Vec<InItem>
@@ -93,6 +176,52 @@ pub fn map_save(
             error!("{}", msg);
             msg
         })?;
+    let routing = routing.unwrap_or_default();
+    map_history_mod::baseline(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let path = map_items_save(&app, items, routing, mirror.unwrap_or(false))?;
+    map_history_mod::snapshot(&app, description.unwrap_or_default(), routing).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    Ok(path)
+}
+
+/// 驗證地圖並寫入 map_info.c、map_base.h、map_info.json 與選擇的路由表，回傳 C 檔路徑  
+/// Validates the map and writes map_info.c, map_base.h, map_info.json and the selected routing tables, returns the C file path
+pub(crate) fn map_items_save(
+    app: &AppHandle,
+    items: Vec<InItem>,
+    routing: MapRouteOutput,
+    mirror: bool,
+) -> Result<String, String> {
+    // 檢查雙向連接，再驗證地圖，有錯誤時不產生檔案
+    // Check bidirectional links, then validate the map, nothing is generated when there are errors
+    let normalized = map_normalize_mod::normalize(items, mirror);
+    let items = normalized.items;
+    let mut report = map_validate_mod::validate(&items);
+    report.issues.extend(normalized.report.issues);
+    for issue in report.warnings() {
+        warn!("{}", issue);
+    }
+    if report.has_errors() {
+        let msg = report.error_text();
+        error!("{}", msg);
+        return Err(msg);
+    }
+    // 先產生路由表，失敗時不寫入任何檔案
+    // Build the routing tables first so nothing is written when they fail
+    let route_code = if routing.any() {
+        let table = MapGraph::new(&items).all_pairs();
+        Some(map_route_mod::route_tables_c(&table, routing).map_err(|e| {
+            error!("{}", e);
+            e
+        })?)
+    } else {
+        None
+    };
 
     // 準備輸出檔案路徑與緩衝
     // Prepare file paths and buffers
@@ -107,7 +236,7 @@ pub fn map_save(
     let h_path = create_file(folder_path.clone(), "map_base.h").map_err(|e| {
         error!("{}", e);
     }).unwrap();
-    let json_path = create_file(folder_path, "map_info.json").map_err(|e| {
+    let json_path = create_file(folder_path.clone(), "map_info.json").map_err(|e| {
         error!("{}", e);
     }).unwrap();
     let mut c_code = String::new();
@@ -119,21 +248,29 @@ pub fn map_save(
     c_code.push_str("LOCATION locations_info[] = {\n");
     for item in items.iter() {
         let id = item.id;  // 使用輸入的 id / use id from input
-        c_code.push_str(&format!("{}{{{}, {{\n", TAB_SPACE, id));
+        let (x, y) = (item.x.unwrap_or(0), item.y.unwrap_or(0));  // 未設定座標輸出 0 / unset coordinates become 0
+        c_code.push_str(&format!("{}{{{}, {}, {}, {}, {{\n", TAB_SPACE, id, item.kind.c_name(), x, y));
 
         let mut connects: Vec<MapConnect> = Vec::with_capacity(item.connect.len());
-        for conn in &item.connect {
+        for (slot, conn) in item.connect.iter().enumerate() {
             let pos: PosType = conn.pos;  // 連接位置 / pos value
             let len: LenType = conn.len;  // 連接長度 / len value
+            let heading = if pos == 0 { 0 } else { conn.heading_or_slot(slot) };
 
-            c_code.push_str(&format!("{}{}{{{}, {}}},\n", TAB_SPACE, TAB_SPACE, pos, len));
-            connects.push(MapConnect { pos, len });
+            c_code.push_str(&format!(
+                "{}{}{{{}, {}, {}, {}}},\n",
+                TAB_SPACE, TAB_SPACE, pos, len, heading, conn.one_way as u8
+            ));
+            connects.push(MapConnect { pos, len, heading: conn.heading, one_way: conn.one_way });
         }
 
         c_code.push_str(&format!("{}}}}},\n", TAB_SPACE));
         json_items.push(MapItem {
             id,
             name: item.name.clone(),
+            kind: item.kind,
+            x: item.x,
+            y: item.y,
             connect: connects,
         });
     }
@@ -145,6 +282,22 @@ pub fn map_save(
     fs::write(&c_path, c_code    ).map_err(|e| format!("Write .c file error: {}", e))?;
     info!("Wrote C file to {:?}", c_path);
 
+    // 寫入路由表，未選擇時刪除舊檔以免與新地圖不符
+    // Write routing tables, stale files are removed when not requested so they never mismatch the map
+    let route_c_path = folder_path.join("map_route.c");
+    let route_h_path = folder_path.join("map_route.h");
+    if let Some((route_c, route_h)) = route_code {
+        fs::write(&route_h_path, route_h).map_err(|e| format!("Write .h file error: {}", e))?;
+        fs::write(&route_c_path, route_c).map_err(|e| format!("Write .c file error: {}", e))?;
+        info!("Wrote routing tables to {:?}", route_c_path);
+    } else {
+        for path in [&route_c_path, &route_h_path] {
+            if path.exists() {
+                fs::remove_file(path).map_err(|e| format!("Remove {:?} error: {}", path, e))?;
+            }
+        }
+    }
+
     // 序列化並寫入 JSON 檔案
     // Serialize and write JSON file
     let json_text = serde_json::to_string_pretty(&json_items)
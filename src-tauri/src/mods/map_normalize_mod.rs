@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use log::{error, info};
+use serde::Serialize;
+use crate::mods::{
+    map_mod::{AngleType, InConnect, InItem, LenType, PosType, MAX_CONNECTIONS},
+    map_validate_mod::{MapIssueCode, MapReport},
+};
+
+/// 結構：自動補上的反向連接  
+/// Struct: a reverse connection added by mirroring
+#[derive(Debug, Clone, Serialize)]
+pub struct MapMirror {
+    pub node:   PosType,    // 新增連接的節點／node that received the connection
+    pub slot:   usize,      // 放入的槽位／slot it was placed in
+    pub pos:    PosType,    // 連接目標／connection target
+    pub len:    LenType,
+}
+
+/// 結構：正規化結果，items 為處理後的地圖  
+/// Struct: normalization result, items is the processed map
+#[derive(Serialize)]
+pub struct MapNormalizeResult {
+    pub(crate) items:   Vec<InItem>,
+    pub mirrored:       Vec<MapMirror>,
+    pub report:         MapReport,
+}
+
+/// 反向方位，正規化到 0 ~ 359  
+/// Opposite heading, normalized to 0 ~ 359
+fn heading_reverse(heading: AngleType) -> AngleType {
+    ((heading as i32 + 180).rem_euclid(360)) as AngleType
+}
+
+/// 檢查雙向連接：未標記 one_way 的 A→B 需有等長的 B→A；mirror 時補上缺少的反向連接，優先放在對向槽位  
+/// Checks bidirectional links: each A→B not marked one_way needs a B→A of equal length; mirror adds missing ones, preferring the opposite slot
+pub(crate) fn normalize(mut items: Vec<InItem>, mirror: bool) -> MapNormalizeResult {
+    let mut report = MapReport::default();
+    let mut mirrored: Vec<MapMirror> = Vec::new();
+    let index: HashMap<PosType, usize> = items.iter().enumerate().map(|(i, item)| (item.id, i)).collect();
+
+    // 先收集所有有效的雙向候選邊，再修改
+    // Collect the candidate edges first, then modify
+    let mut pending: Vec<(usize, usize, LenType, AngleType)> = Vec::new();
+    for item in &items {
+        for (slot, conn) in item.connect.iter().enumerate() {
+            if conn.pos == 0 || conn.pos == item.id || conn.one_way {
+                continue;
+            }
+            let Some(&target) = index.get(&conn.pos) else { continue };
+            let reverse = items[target].connect.iter().enumerate()
+                .find(|(_, c)| c.pos == item.id);
+            match reverse {
+                Some((back_slot, back)) => {
+                    // 每對只回報一次 / Report each pair once
+                    if back.len != conn.len && (item.id < conn.pos || back.one_way) {
+                        let message = format!(
+                            "Length to {} is {} but {} slot {} has {}",
+                            conn.pos, conn.len, conn.pos, back_slot, back.len
+                        );
+                        report.warning(MapIssueCode::LengthMismatch, item.id, Some(slot), message);
+                    }
+                }
+                None => pending.push((target, index[&item.id], conn.len, heading_reverse(conn.heading_or_slot(slot)))),
+            }
+        }
+    }
+
+    let mut added: HashSet<(usize, usize)> = HashSet::new();
+    for (node, target, len, heading) in pending {
+        if !added.insert((node, target)) {
+            continue;
+        }
+        let node_id = items[node].id;
+        let target_id = items[target].id;
+        if !mirror {
+            let message = format!("{} connects here but there is no connection back", target_id);
+            report.warning(MapIssueCode::AsymmetricEdge, node_id, None, message);
+            continue;
+        }
+        let connect = &mut items[node].connect;
+        if connect.len() < MAX_CONNECTIONS {
+            connect.resize_with(MAX_CONNECTIONS, || InConnect { pos: 0, len: 0, heading: None, one_way: false });
+        }
+        let opposite = (heading as usize / 45) % MAX_CONNECTIONS;
+        let slot = if heading % 45 == 0 && connect[opposite].pos == 0 {
+            Some(opposite)
+        } else {
+            connect.iter().position(|c| c.pos == 0)
+        };
+        let Some(slot) = slot else {
+            let message = format!("No free slot to add the connection back to {}", target_id);
+            report.error(MapIssueCode::NoFreeSlot, node_id, None, message);
+            continue;
+        };
+        // 放在對向槽位時方位可由槽位推得 / The heading follows from the slot when placed opposite
+        let heading = if (slot * 45) as AngleType == heading { None } else { Some(heading) };
+        connect[slot] = InConnect { pos: target_id, len, heading, one_way: false };
+        mirrored.push(MapMirror { node: node_id, slot, pos: target_id, len });
+    }
+    if !mirrored.is_empty() {
+        info!("Mirrored {} connections", mirrored.len());
+    }
+    MapNormalizeResult { items, mirrored, report }
+}
+
+/// Tauri 命令：檢查並選擇性補齊地圖的雙向連接，回傳處理後的地圖  
+/// Tauri command: check and optionally complete the map's bidirectional links, returns the processed map
+#[tauri::command]
+pub fn map_normalize(data: String, mirror: bool) -> Result<MapNormalizeResult, String> {
+    let items: Vec<InItem> = serde_json::from_str(&data)
+        .map_err(|e| {
+            let msg = format!("JSON parse error: {}", e);
+            error!("{}", msg);
+            msg
+        })?;
+    Ok(normalize(items, mirror))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use super::*;
+
+    /// 依槽位建立 8 個連接，未列出的槽位為空  
+    /// Builds 8 connections by slot, unlisted slots are empty
+    fn node(id: PosType, slots: &[(usize, Value)]) -> Value {
+        let mut connect = vec![json!({ "pos": 0, "len": 0 }); MAX_CONNECTIONS];
+        for (slot, conn) in slots {
+            connect[*slot] = conn.clone();
+        }
+        json!({ "id": id, "name": "", "connect": connect })
+    }
+
+    fn items(nodes: Vec<Value>) -> Vec<InItem> {
+        serde_json::from_value(Value::Array(nodes)).unwrap()
+    }
+
+    fn codes(report: &MapReport) -> Vec<(MapIssueCode, Option<PosType>)> {
+        report.issues.iter().map(|i| (i.code, i.node)).collect()
+    }
+
+    #[test]
+    fn mirror_uses_opposite_slot() {
+        let result = normalize(items(vec![
+            node(1, &[(2, json!({ "pos": 2, "len": 12 }))]),
+            node(2, &[]),
+        ]), true);
+        assert!(result.report.issues.is_empty());
+        assert_eq!(result.mirrored.len(), 1);
+        assert_eq!((result.mirrored[0].node, result.mirrored[0].slot, result.mirrored[0].len), (2, 6, 12));
+        let back = &result.items[1].connect[6];
+        assert_eq!((back.pos, back.len, back.heading), (1, 12, None));
+    }
+
+    #[test]
+    fn mirror_falls_back_to_free_slot_with_heading() {
+        let result = normalize(items(vec![
+            node(1, &[(2, json!({ "pos": 2, "len": 12 })), (3, json!({ "pos": 3, "len": 5, "heading": 100 }))]),
+            node(2, &[(0, json!({ "pos": 3, "len": 9 })), (6, json!({ "pos": 3, "len": 9 }))]),
+            node(3, &[(0, json!({ "pos": 2, "len": 9 }))]),
+        ]), true);
+        let slots: Vec<(PosType, usize)> = result.mirrored.iter().map(|m| (m.node, m.slot)).collect();
+        assert_eq!(slots, vec![(2, 1), (3, 1)]);
+        assert_eq!(result.items[1].connect[1].heading, Some(270));
+        assert_eq!(result.items[2].connect[1].heading, Some(280));
+    }
+
+    #[test]
+    fn asymmetric_edge_without_mirror() {
+        let result = normalize(items(vec![
+            node(1, &[(2, json!({ "pos": 2, "len": 12 })), (4, json!({ "pos": 3, "len": 7, "one_way": true }))]),
+            node(2, &[]),
+            node(3, &[]),
+        ]), false);
+        assert!(result.mirrored.is_empty());
+        assert_eq!(codes(&result.report), vec![(MapIssueCode::AsymmetricEdge, Some(2))]);
+        assert!(result.items[1].connect.iter().all(|c| c.pos == 0));
+    }
+
+    #[test]
+    fn length_mismatch_reported_once() {
+        let result = normalize(items(vec![
+            node(1, &[(2, json!({ "pos": 2, "len": 12 }))]),
+            node(2, &[(6, json!({ "pos": 1, "len": 15 }))]),
+        ]), true);
+        assert!(result.mirrored.is_empty());
+        assert_eq!(codes(&result.report), vec![(MapIssueCode::LengthMismatch, Some(1))]);
+        assert_eq!(result.report.issues[0].slot, Some(2));
+    }
+
+    #[test]
+    fn mirror_without_free_slot() {
+        let full: Vec<(usize, Value)> = (0..MAX_CONNECTIONS)
+            .map(|slot| (slot, json!({ "pos": 3, "len": 1, "one_way": true })))
+            .collect();
+        let result = normalize(items(vec![
+            node(1, &[(2, json!({ "pos": 2, "len": 12 }))]),
+            node(2, &full),
+            node(3, &[]),
+        ]), true);
+        assert!(result.mirrored.is_empty());
+        assert!(result.report.has_errors());
+        assert_eq!(codes(&result.report), vec![(MapIssueCode::NoFreeSlot, Some(2))]);
+    }
+}
@@ -0,0 +1,432 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::mods::map_mod::{self, AngleType, CoordType, InItem, PosType, TAB_SPACE};
+
+/// 型別別名：路徑總長度，避免多段 LenType 相加溢位  
+/// Type alias: total route length, wide enough to sum many LenType edges
+pub(crate) type DistType = u64;
+
+/// 結構：有向加權圖，邊依 connect 的方向建立，pos 為 0 的空位略過  
+/// Struct: directed weighted graph, edges follow connect entries and empty slots (pos 0) are skipped
+pub(crate) struct MapGraph {
+    ids:    Vec<PosType>,                               // 節點 id，依輸入順序／node ids in input order
+    index:  HashMap<PosType, usize>,                    // id 對應索引／id to index
+    edges:  Vec<Vec<MapEdge>>,                          // 每個節點的出邊／outgoing edges of each node
+    coords: Vec<Option<(CoordType, CoordType)>>,        // 節點座標／node coordinates
+}
+
+/// 結構：圖中的一條出邊  
+/// Struct: one outgoing edge in the graph
+struct MapEdge {
+    to:         usize,
+    len:        DistType,
+    heading:    AngleType,
+}
+
+impl MapGraph {
+    /// 由地圖項目建立圖，連到不存在節點的邊會被忽略  
+    /// Builds the graph from map items, edges to missing nodes are ignored
+    pub(crate) fn new(items: &[InItem]) -> Self {
+        let ids: Vec<PosType> = items.iter().map(|item| item.id).collect();
+        let index: HashMap<PosType, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let edges = items.iter().map(|item| {
+            item.connect.iter()
+                .enumerate()
+                .filter(|(_, c)| c.pos != 0)
+                .filter_map(|(slot, c)| index.get(&c.pos).map(|to| MapEdge {
+                    to: *to,
+                    len: c.len as DistType,
+                    heading: c.heading_or_slot(slot),
+                }))
+                .collect()
+        }).collect();
+        let coords = items.iter().map(|item| item.coord()).collect();
+        Self { ids, index, edges, coords }
+    }
+
+    /// 以座標建立 A* 估計：直線距離乘上所有邊「長度 / 直線距離」的最小比值，確保不高估；任一節點缺座標時回傳 None  
+    /// Builds an A* estimate from coordinates: straight distance times the smallest edge "length / straight distance" ratio so it never overestimates; None when any node lacks coordinates
+    pub(crate) fn coord_heuristic(&self, to: PosType) -> Option<impl Fn(PosType) -> DistType + '_> {
+        let coords: Vec<(CoordType, CoordType)> = self.coords.iter().copied().collect::<Option<_>>()?;
+        let goal = coords[self.index_of(to).ok()?];
+        let straight = |a: (CoordType, CoordType), b: (CoordType, CoordType)| {
+            let dx = (a.0 as f64) - (b.0 as f64);
+            let dy = (a.1 as f64) - (b.1 as f64);
+            (dx * dx + dy * dy).sqrt()
+        };
+        let mut ratio = f64::INFINITY;
+        for (from, edges) in self.edges.iter().enumerate() {
+            for edge in edges {
+                let d = straight(coords[from], coords[edge.to]);
+                if d > 0.0 {
+                    ratio = ratio.min(edge.len as f64 / d);
+                }
+            }
+        }
+        if !ratio.is_finite() {
+            return None;
+        }
+        Some(move |id: PosType| {
+            self.index.get(&id).map_or(0, |&i| (straight(coords[i], goal) * ratio).floor() as DistType)
+        })
+    }
+
+    fn index_of(&self, id: PosType) -> Result<usize, String> {
+        self.index.get(&id).copied().ok_or_else(|| format!("Location {} not found", id))
+    }
+
+    /// Dijkstra 最短路徑  
+    /// Dijkstra shortest path
+    pub(crate) fn dijkstra(&self, from: PosType, to: PosType) -> Result<Option<MapRoute>, String> {
+        self.astar(from, to, |_| 0)
+    }
+
+    /// A* 最短路徑，heuristic 需為不高估的剩餘距離，回傳 0 時等同 Dijkstra  
+    /// A* shortest path, heuristic must never overestimate the remaining distance, returning 0 equals Dijkstra
+    pub(crate) fn astar<H>(&self, from: PosType, to: PosType, heuristic: H) -> Result<Option<MapRoute>, String>
+    where
+        H: Fn(PosType) -> DistType,
+    {
+        let start = self.index_of(from)?;
+        let goal = self.index_of(to)?;
+        let mut dist: Vec<Option<DistType>> = vec![None; self.ids.len()];
+        let mut prev: Vec<Option<usize>> = vec![None; self.ids.len()];
+        let mut heap = BinaryHeap::new();
+        dist[start] = Some(0);
+        heap.push(Reverse((heuristic(from), 0, start)));
+
+        while let Some(Reverse((_, cost, node))) = heap.pop() {
+            if node == goal {
+                break;
+            }
+            if dist[node].is_some_and(|d| cost > d) {
+                continue;
+            }
+            for edge in &self.edges[node] {
+                let (next, next_cost) = (edge.to, cost + edge.len);
+                if dist[next].is_none_or(|d| next_cost < d) {
+                    dist[next] = Some(next_cost);
+                    prev[next] = Some(node);
+                    heap.push(Reverse((next_cost + heuristic(self.ids[next]), next_cost, next)));
+                }
+            }
+        }
+
+        let Some(length) = dist[goal] else {
+            return Ok(None);
+        };
+        let mut path = vec![self.ids[goal]];
+        let mut node = goal;
+        while let Some(p) = prev[node] {
+            path.push(self.ids[p]);
+            node = p;
+        }
+        path.reverse();
+        let turns = self.turns(&path);
+        Ok(Some(MapRoute { from, to, path, length, turns }))
+    }
+
+    /// 最短路徑，所有節點皆有座標時使用 A*，否則使用 Dijkstra  
+    /// Shortest path, uses A* when every node has coordinates and Dijkstra otherwise
+    pub(crate) fn shortest(&self, from: PosType, to: PosType) -> Result<Option<MapRoute>, String> {
+        match self.coord_heuristic(to) {
+            Some(heuristic) => self.astar(from, to, heuristic),
+            None => self.dijkstra(from, to),
+        }
+    }
+
+    /// 兩節點間最短的直接邊，回傳長度與方位  
+    /// The shortest direct edge between two nodes, returns its length and heading
+    pub(crate) fn edge(&self, from: PosType, to: PosType) -> Option<(DistType, AngleType)> {
+        let from = *self.index.get(&from)?;
+        let to = *self.index.get(&to)?;
+        self.edges[from].iter()
+            .filter(|e| e.to == to)
+            .min_by_key(|e| e.len)
+            .map(|e| (e.len, e.heading))
+    }
+
+    /// 路徑上每個中間節點的轉彎角度（-180 ~ 179，正值為右轉），由進入與離開的邊方位相減  
+    /// Turn angle at each intermediate node of a path (-180 ~ 179, positive turns right), the leaving heading minus the arriving one
+    fn turns(&self, path: &[PosType]) -> Vec<AngleType> {
+        let headings: Vec<i32> = path.windows(2)
+            .filter_map(|pair| self.edge(pair[0], pair[1]).map(|(_, heading)| heading as i32))
+            .collect();
+        headings.windows(2)
+            .map(|pair| ((pair[1] - pair[0] + 180).rem_euclid(360) - 180) as AngleType)
+            .collect()
+    }
+
+    /// Floyd–Warshall 全點對最短距離與下一跳  
+    /// Floyd–Warshall all-pairs shortest distances and next hops
+    pub(crate) fn all_pairs(&self) -> MapDistanceTable {
+        let n = self.ids.len();
+        let mut dist: Vec<Vec<Option<DistType>>> = vec![vec![None; n]; n];
+        let mut next: Vec<Vec<Option<usize>>> = vec![vec![None; n]; n];
+        for i in 0..n {
+            dist[i][i] = Some(0);
+            next[i][i] = Some(i);
+            for edge in &self.edges[i] {
+                let (j, len) = (edge.to, edge.len);
+                if dist[i][j].is_none_or(|d| len < d) {
+                    dist[i][j] = Some(len);
+                    next[i][j] = Some(j);
+                }
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                let Some(ik) = dist[i][k] else { continue };
+                for j in 0..n {
+                    let Some(kj) = dist[k][j] else { continue };
+                    if dist[i][j].is_none_or(|d| ik + kj < d) {
+                        dist[i][j] = Some(ik + kj);
+                        next[i][j] = next[i][k];
+                    }
+                }
+            }
+        }
+        let next = next.into_iter()
+            .map(|row| row.into_iter().map(|hop| hop.map(|h| self.ids[h])).collect())
+            .collect();
+        MapDistanceTable { ids: self.ids.clone(), dist, next }
+    }
+}
+
+/// 結構：兩點間路徑，path 含起點與終點，turns 為各中間節點的轉彎角度  
+/// Struct: route between two locations, path includes both ends and turns holds the angle at each intermediate node
+#[derive(Debug, Clone, Serialize)]
+pub struct MapRoute {
+    pub from:   PosType,
+    pub to:     PosType,
+    pub path:   Vec<PosType>,
+    pub length: DistType,
+    pub turns:  Vec<AngleType>,
+}
+
+/// 結構：全點對距離表，dist[i][j] 與 next[i][j] 以 ids 的索引排列，None 表示不可達  
+/// Struct: all-pairs table, dist[i][j] and next[i][j] follow the order of ids, None means unreachable
+#[derive(Debug, Clone, Serialize)]
+pub struct MapDistanceTable {
+    pub ids:    Vec<PosType>,
+    pub dist:   Vec<Vec<Option<DistType>>>,
+    pub next:   Vec<Vec<Option<PosType>>>,
+}
+
+/// 結構：map_save 的路由表輸出選項，皆為 false 時不產生 map_route.c / map_route.h  
+/// Struct: routing table options for map_save, nothing is generated when both are false
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapRouteOutput {
+    pub next_hop:   bool,   // 下一跳表／next-hop table
+    pub distance:   bool,   // 全點對距離矩陣／all-pairs distance matrix
+}
+impl MapRouteOutput {
+    pub fn any(&self) -> bool {
+        self.next_hop || self.distance
+    }
+}
+
+/// 依最大值選擇最小的無號 C 型別，保留型別最大值（以 stdint.h 巨集表示）作為「不可達」，並回傳數值常數的後綴  
+/// Picks the smallest unsigned C type for max_value, keeping the type maximum (as its stdint.h macro) as "unreachable",
+/// and returns the suffix for numeric literals
+fn c_uint_type(max_value: u64) -> (&'static str, &'static str, &'static str) {
+    if max_value < u8::MAX as u64 {
+        ("uint8_t", "UINT8_MAX", "U")
+    } else if max_value < u16::MAX as u64 {
+        ("uint16_t", "UINT16_MAX", "U")
+    } else if max_value < u32::MAX as u64 {
+        ("uint32_t", "UINT32_MAX", "UL")
+    } else {
+        ("uint64_t", "UINT64_MAX", "ULL")
+    }
+}
+
+/// 產生路由表的 C 原始碼與標頭，陣列以 locations_info[] 的索引排列，下一跳存索引而非 id；沒有節點時回傳錯誤  
+/// Generates C source and header for the routing tables, arrays follow locations_info[] order and next hops store indexes, not ids;
+/// fails when there are no nodes
+pub(crate) fn route_tables_c(table: &MapDistanceTable, output: MapRouteOutput) -> Result<(String, String), String> {
+    let n = table.ids.len();
+    if n == 0 {
+        return Err("Routing tables need at least one node".into());
+    }
+    let index: HashMap<PosType, usize> = table.ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let (index_type, index_none, index_suffix) = c_uint_type(n as u64);
+    let max_dist = table.dist.iter().flatten().flatten().copied().max().unwrap_or(0);
+    let (dist_type, dist_none, dist_suffix) = c_uint_type(max_dist);
+
+    let mut h_code = String::new();
+    h_code.push_str("#ifndef PRINCIPAL_MAP_ROUTE_H\n#define PRINCIPAL_MAP_ROUTE_H\n\n");
+    h_code.push_str("#include <stdint.h>\n\n");
+    h_code.push_str(&format!("#define MAP_NODE_COUNT {}\n", n));
+    let mut c_code = String::new();
+    c_code.push_str("#include \"user/map_route.h\"\n");
+
+    if output.next_hop {
+        h_code.push_str(&format!("typedef {} MAP_INDEX;\n", index_type));
+        h_code.push_str(&format!("#define MAP_INDEX_NONE {}\n", index_none));
+        h_code.push_str("extern const MAP_INDEX map_next_hop[MAP_NODE_COUNT][MAP_NODE_COUNT];\n");
+        c_code.push_str("\nconst MAP_INDEX map_next_hop[MAP_NODE_COUNT][MAP_NODE_COUNT] = {\n");
+        for row in &table.next {
+            let cells: Vec<String> = row.iter()
+                .map(|hop| hop.map_or("MAP_INDEX_NONE".to_string(), |id| format!("{}{}", index[&id], index_suffix)))
+                .collect();
+            c_code.push_str(&format!("{}{{{}}},\n", TAB_SPACE, cells.join(", ")));
+        }
+        c_code.push_str("};\n");
+    }
+    if output.distance {
+        h_code.push_str(&format!("typedef {} MAP_DIST;\n", dist_type));
+        h_code.push_str(&format!("#define MAP_DIST_NONE {}\n", dist_none));
+        h_code.push_str("extern const MAP_DIST map_distance[MAP_NODE_COUNT][MAP_NODE_COUNT];\n");
+        c_code.push_str("\nconst MAP_DIST map_distance[MAP_NODE_COUNT][MAP_NODE_COUNT] = {\n");
+        for row in &table.dist {
+            let cells: Vec<String> = row.iter()
+                .map(|d| d.map_or("MAP_DIST_NONE".to_string(), |d| format!("{}{}", d, dist_suffix)))
+                .collect();
+            c_code.push_str(&format!("{}{{{}}},\n", TAB_SPACE, cells.join(", ")));
+        }
+        c_code.push_str("};\n");
+    }
+    h_code.push_str("\n#endif\n");
+    Ok((c_code, h_code))
+}
+
+/// Tauri 命令：計算已保存地圖上兩點間的最短路徑  
+/// Tauri command: compute the shortest route between two locations on the saved map
+#[tauri::command]
+pub fn map_route(app: AppHandle, from: PosType, to: PosType) -> Result<MapRoute, String> {
+    let items = map_mod::map_items_read(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let route = MapGraph::new(&items).shortest(from, to)
+        .and_then(|route| route.ok_or_else(|| format!("No route from {} to {}", from, to)))
+        .map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+    info!("Route {} -> {}: {:?} ({})", from, to, route.path, route.length);
+    Ok(route)
+}
+
+/// Tauri 命令：計算已保存地圖的全點對距離表  
+/// Tauri command: compute the all-pairs distance table of the saved map
+#[tauri::command]
+pub fn map_distance_table(app: AppHandle) -> Result<MapDistanceTable, String> {
+    let items = map_mod::map_items_read(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    Ok(MapGraph::new(&items).all_pairs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOTH: MapRouteOutput = MapRouteOutput { next_hop: true, distance: true };
+
+    /// 3 x 3 格狀地圖（間距 10，邊長 10 ~ 14），加上 1 → 9 單向斜邊與孤立節點 10  
+    /// 3 x 3 grid (spacing 10, edges 10 ~ 14) plus a one-way diagonal 1 → 9 and an isolated node 10
+    fn grid() -> Vec<InItem> {
+        let id = |r: i32, c: i32| (r * 3 + c + 1) as PosType;
+        let mut items = Vec::new();
+        for r in 0..3 {
+            for c in 0..3 {
+                let mut connect = vec![serde_json::json!({ "pos": 0, "len": 0 }); 8];
+                for (slot, dr, dc) in [(0, 1, 0), (2, 0, 1), (4, -1, 0), (6, 0, -1)] {
+                    let (nr, nc) = (r + dr, c + dc);
+                    if (0..3).contains(&nr) && (0..3).contains(&nc) {
+                        let (a, b) = (id(r, c).min(id(nr, nc)), id(r, c).max(id(nr, nc)));
+                        connect[slot] = serde_json::json!({ "pos": id(nr, nc), "len": 10 + (a * 7 + b) % 5 });
+                    }
+                }
+                if id(r, c) == 1 {
+                    connect[1] = serde_json::json!({ "pos": 9, "len": 30, "one_way": true });
+                }
+                items.push(serde_json::json!({ "id": id(r, c), "name": "", "x": c * 10, "y": r * 10, "connect": connect }));
+            }
+        }
+        items.push(serde_json::json!({ "id": 10, "name": "", "x": 50, "y": 50, "connect": [] }));
+        serde_json::from_value(serde_json::Value::Array(items)).unwrap()
+    }
+
+    #[test]
+    fn astar_matches_dijkstra_and_all_pairs() {
+        let items = grid();
+        let graph = MapGraph::new(&items);
+        let table = graph.all_pairs();
+        for (i, &from) in table.ids.iter().enumerate() {
+            for (j, &to) in table.ids.iter().enumerate() {
+                let dijkstra = graph.dijkstra(from, to).unwrap();
+                let astar = graph.astar(from, to, graph.coord_heuristic(to).unwrap()).unwrap();
+                assert_eq!(dijkstra.as_ref().map(|r| r.length), table.dist[i][j], "{} -> {}", from, to);
+                assert_eq!(astar.as_ref().map(|r| r.length), table.dist[i][j], "{} -> {}", from, to);
+                if let Some(route) = astar {
+                    let length: DistType = route.path.windows(2).map(|p| graph.edge(p[0], p[1]).unwrap().0).sum();
+                    assert_eq!(length, route.length);
+                }
+            }
+        }
+        assert_eq!(graph.shortest(1, 9).unwrap().unwrap().path, vec![1, 9]);
+        assert!(graph.shortest(9, 1).unwrap().unwrap().length > 0);
+        assert!(graph.shortest(1, 10).unwrap().is_none());
+        assert!(graph.shortest(1, 11).is_err());
+    }
+
+    #[test]
+    fn all_pairs_next_hops_follow_shortest_paths() {
+        let graph = MapGraph::new(&grid());
+        let table = graph.all_pairs();
+        let index = |id: PosType| table.ids.iter().position(|&x| x == id).unwrap();
+        for (i, &from) in table.ids.iter().enumerate() {
+            for (j, &to) in table.ids.iter().enumerate() {
+                let Some(dist) = table.dist[i][j] else {
+                    assert_eq!(table.next[i][j], None);
+                    continue;
+                };
+                let (mut at, mut walked) = (from, 0);
+                while at != to {
+                    let hop = table.next[index(at)][j].unwrap();
+                    walked += graph.edge(at, hop).unwrap().0;
+                    at = hop;
+                }
+                assert_eq!(walked, dist, "{} -> {}", from, to);
+            }
+        }
+        assert_eq!(table.next[index(1)][index(9)], Some(9));
+        assert_eq!(table.next[index(10)][index(10)], Some(10));
+    }
+
+    #[test]
+    fn turns_follow_headings() {
+        let graph = MapGraph::new(&grid());
+        // 1 → 2 向東，2 → 5 向北：左轉 90 度 / 1 → 2 heads east, 2 → 5 heads north: a 90 degree left turn
+        assert_eq!(graph.turns(&[1, 2, 5]), vec![-90]);
+        assert_eq!(graph.turns(&[1, 2, 3]), vec![0]);
+    }
+
+    #[test]
+    fn route_tables_use_suffixes_and_limits() {
+        let table = MapDistanceTable {
+            ids: vec![1, 2],
+            dist: vec![vec![Some(0), Some(300)], vec![None, Some(0)]],
+            next: vec![vec![None, Some(2)], vec![None, None]],
+        };
+        let (c_code, h_code) = route_tables_c(&table, BOTH).unwrap();
+        assert!(h_code.contains("#define MAP_INDEX_NONE UINT8_MAX\n"));
+        assert!(h_code.contains("typedef uint16_t MAP_DIST;\n#define MAP_DIST_NONE UINT16_MAX\n"));
+        assert!(c_code.contains("{MAP_INDEX_NONE, 1U},"));
+        assert!(c_code.contains("{0U, 300U},"));
+        assert!(c_code.contains("{MAP_DIST_NONE, 0U},"));
+    }
+
+    #[test]
+    fn route_tables_need_nodes() {
+        let table = MapDistanceTable { ids: Vec::new(), dist: Vec::new(), next: Vec::new() };
+        assert!(route_tables_c(&table, BOTH).is_err());
+    }
+}
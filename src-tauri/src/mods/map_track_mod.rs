@@ -0,0 +1,191 @@
+use std::{collections::HashMap, fs, time::{Duration, Instant, SystemTime}};
+use log::{error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{mods::{map_mission_mod, map_mod::{self, InItem, LenType, PosType}, mcu_store_mod::DataType}, GlobalState};
+
+/// 檢查地圖檔案修改時間的最短間隔  
+/// Minimum interval between checks of the map file modification time
+const MAP_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 列舉：車輛相對於地圖的位置狀態  
+/// Enum: vehicle location state relative to the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationState {
+    AtNode,     // 停在節點上／standing on a node
+    OnEdge,     // 沿邊行進中／driving along an edge
+    OffMap,     // 節點或邊不在地圖上／node or edge not on the map
+}
+
+/// 結構：車輛位置報告，傳送給前端  
+/// Struct: vehicle location report sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct VehicleLocation {
+    pub state:      LocationState,
+    pub node:       PosType,
+    pub name:       Option<String>,
+    pub next:       Option<PosType>,
+    pub travelled:  u32,
+    pub edge_len:   Option<LenType>,
+    pub progress:   Option<f64>,    // 沿邊進度 0 ~ 1／progress along the edge, 0 ~ 1
+    pub message:    Option<String>,
+    pub ts_ms:      i64,
+}
+
+struct TrackNode {
+    name:   String,
+    edges:  HashMap<PosType, LenType>,
+}
+
+/// 結構：車輛定位追蹤器，依 map_info.json 的修改時間自動重新載入地圖，每秒最多檢查一次  
+/// Struct: vehicle tracker, reloads the map whenever the modification time of map_info.json changes, checked at most once a second
+pub struct VehicleTracker {
+    nodes:      HashMap<PosType, TrackNode>,
+    modified:   Option<SystemTime>,
+    checked:    Option<Instant>,
+    last:       Option<VehicleLocation>,
+}
+impl VehicleTracker {
+    /// 建立空的追蹤器  
+    /// Creates an empty tracker
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            modified: None,
+            checked: None,
+            last: None,
+        }
+    }
+
+    /// 載入地圖節點與連接  
+    /// Loads the map nodes and connections
+    fn map_load(&mut self, items: &[InItem], modified: Option<SystemTime>) {
+        self.nodes = items.iter()
+            .map(|item| {
+                let edges = item.connect.iter()
+                    .filter(|c| c.pos != 0)
+                    .map(|c| (c.pos, c.len))
+                    .collect();
+                (item.id, TrackNode { name: item.name.clone(), edges })
+            })
+            .collect();
+        self.modified = modified;
+    }
+
+    /// 地圖檔案有變更時重新載入，距上次檢查未滿間隔時略過  
+    /// Reloads the map when the file has changed, skipped within the interval since the last check
+    fn map_refresh(&mut self, app: &AppHandle) -> Result<(), String> {
+        if self.checked.is_some_and(|checked| checked.elapsed() < MAP_REFRESH_INTERVAL) {
+            return Ok(());
+        }
+        self.checked = Some(Instant::now());
+        let modified = fs::metadata(map_mod::map_info_path(app))
+            .and_then(|meta| meta.modified())
+            .ok();
+        if modified == self.modified {
+            return Ok(());
+        }
+        // 讀取失敗時清空地圖，直到檔案再次變更 / Clear the map on failure until the file changes again
+        self.modified = modified;
+        self.nodes.clear();
+        let items = map_mod::map_items_read(app)?;
+        self.map_load(&items, modified);
+        info!("Vehicle tracker loaded {} map nodes", self.nodes.len());
+        Ok(())
+    }
+
+    /// 依位置回報計算車輛在地圖上的位置，next 為 0 表示停在節點上  
+    /// Locates the vehicle on the map from a position report, next 0 means standing on the node
+    fn locate(&self, node: PosType, next: PosType, travelled: u32) -> VehicleLocation {
+        let mut location = VehicleLocation {
+            state: LocationState::OffMap,
+            node,
+            name: None,
+            next: (next != 0).then_some(next),
+            travelled,
+            edge_len: None,
+            progress: None,
+            message: None,
+            ts_ms: chrono::Utc::now().timestamp_millis(),
+        };
+        let Some(current) = self.nodes.get(&node) else {
+            location.message = Some(format!("Node {} is not on the map", node));
+            return location;
+        };
+        location.name = Some(current.name.clone());
+        if next == 0 {
+            location.state = LocationState::AtNode;
+            return location;
+        }
+        let Some(&len) = current.edges.get(&next) else {
+            location.message = Some(format!("Node {} has no connection to {}", node, next));
+            return location;
+        };
+        location.state = LocationState::OnEdge;
+        location.edge_len = Some(len);
+        location.progress = Some(if len == 0 { 1.0 } else { (travelled as f64 / len as f64).min(1.0) });
+        if travelled > len {
+            location.message = Some(format!("Travelled {} exceeds edge length {}", travelled, len));
+        }
+        location
+    }
+
+    /// 最新的位置報告  
+    /// The latest location report
+    pub fn last(&self) -> Option<VehicleLocation> {
+        self.last.clone()
+    }
+}
+
+impl Default for VehicleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析 MCU 位置封包，存入 VehicelPos、通知前端並更新任務進度  
+/// Parses an MCU position packet, stores it as VehicelPos, notifies the frontend and updates the mission progress
+pub async fn re_pkt_position(app: AppHandle, data: Vec<u8>) {
+    if data.len() < 8 {
+        error!("Position packet too short: need 8 bytes, got {}", data.len());
+        return;
+    }
+    let node = u16::from_be_bytes([data[0], data[1]]);
+    let next = u16::from_be_bytes([data[2], data[3]]);
+    let travelled = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+
+    let global_state = app.state::<GlobalState>();
+    global_state.store_datas.lock().await.push(DataType::VehicelPos, node);
+
+    let (location, left_map) = {
+        let mut tracker = global_state.vehicle_tracker.lock().await;
+        if let Err(e) = tracker.map_refresh(&app) {
+            warn!("Vehicle tracker map unavailable: {}", e);
+        }
+        let location = tracker.locate(node, next, travelled);
+        let was_off = tracker.last.as_ref().is_some_and(|last| last.state == LocationState::OffMap);
+        let left_map = location.state == LocationState::OffMap && !was_off;
+        tracker.last = Some(location.clone());
+        (location, left_map)
+    };
+    if let Err(e) = app.emit("vehicle-location", &location) {
+        error!("Emit vehicle-location failed: {}", e);
+    }
+    if left_map {
+        warn!("Vehicle left the map: {:?}", location.message);
+        if let Err(e) = app.emit("vehicle-off-map", &location) {
+            error!("Emit vehicle-off-map failed: {}", e);
+        }
+    }
+    map_mission_mod::mission_locate(&app, &location).await;
+}
+
+/// Tauri 命令：取得最新的車輛位置  
+/// Tauri command: get the latest vehicle location
+#[tauri::command]
+pub async fn map_vehicle_location(app: AppHandle) -> Result<Option<VehicleLocation>, String> {
+    let global_state = app.state::<GlobalState>();
+    let tracker = global_state.vehicle_tracker.lock().await;
+    Ok(tracker.last())
+}
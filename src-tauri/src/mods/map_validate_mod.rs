@@ -0,0 +1,244 @@
+use std::{collections::{HashMap, HashSet}, fmt};
+use serde::Serialize;
+use crate::mods::map_mod::{InItem, PosType, MAX_CONNECTIONS};
+
+/// 列舉：問題嚴重程度，錯誤會阻止產生檔案  
+/// Enum: issue severity, errors block file generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapIssueLevel {
+    Error,
+    Warning,
+}
+
+/// 列舉：問題種類  
+/// Enum: issue kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapIssueCode {
+    ReservedId,         // id 0 保留為「無連接」／id 0 is reserved for "no connection"
+    DuplicateId,        // id 重複／duplicated id
+    TooManyConnections, // 超過 MAX_CONNECTIONS／more than MAX_CONNECTIONS
+    DanglingConnection, // 連到不存在的 id／connects to a missing id
+    ZeroLength,         // 連接長度為 0／connection length is zero
+    SelfLoop,           // 連到自己／connects to itself
+    DuplicateConnection,// 重複連到同一節點／connects to the same node twice
+    UnusedLength,       // 空連接卻有長度／empty slot with a length
+    EmptyName,          // 名稱為空／name is empty
+    Isolated,           // 沒有任何連接／no connections at all
+    PartialCoordinates, // 只設定 x 或 y／only one of x and y is set
+    AsymmetricEdge,     // 缺少反向連接／reverse connection is missing
+    LengthMismatch,     // 雙向長度不一致／lengths differ between directions
+    NoFreeSlot,         // 無空位可補反向連接／no free slot for the reverse connection
+    CoordRounded,       // 匯入座標含小數，已四捨五入／imported coordinate had a fraction and was rounded
+}
+
+/// 結構：單一驗證問題，node 為出問題的節點 id  
+/// Struct: one validation issue, node is the offending node id
+#[derive(Debug, Clone, Serialize)]
+pub struct MapIssue {
+    pub level:      MapIssueLevel,
+    pub code:       MapIssueCode,
+    pub node:       Option<PosType>,
+    pub slot:       Option<usize>,
+    pub message:    String,
+}
+impl fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.node, self.slot) {
+            (Some(node), Some(slot)) => write!(f, "[node {} slot {}] {}", node, slot, self.message),
+            (Some(node), None) => write!(f, "[node {}] {}", node, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// 結構：驗證結果  
+/// Struct: validation report
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MapReport {
+    pub issues: Vec<MapIssue>,
+}
+impl MapReport {
+    fn push(&mut self, level: MapIssueLevel, code: MapIssueCode, node: Option<PosType>, slot: Option<usize>, message: String) {
+        self.issues.push(MapIssue { level, code, node, slot, message });
+    }
+
+    pub(crate) fn error(&mut self, code: MapIssueCode, node: PosType, slot: Option<usize>, message: String) {
+        self.push(MapIssueLevel::Error, code, Some(node), slot, message);
+    }
+
+    pub(crate) fn warning(&mut self, code: MapIssueCode, node: PosType, slot: Option<usize>, message: String) {
+        self.push(MapIssueLevel::Warning, code, Some(node), slot, message);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.level == MapIssueLevel::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &MapIssue> {
+        self.issues.iter().filter(|i| i.level == MapIssueLevel::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &MapIssue> {
+        self.issues.iter().filter(|i| i.level == MapIssueLevel::Warning)
+    }
+
+    /// 將所有錯誤組成多行文字  
+    /// Joins all errors into multi-line text
+    pub fn error_text(&self) -> String {
+        let lines: Vec<String> = self.errors().map(|i| i.to_string()).collect();
+        format!("Map validation failed ({} errors):\n{}", lines.len(), lines.join("\n"))
+    }
+}
+
+/// 驗證地圖：pos 為 0 的連接視為空位  
+/// Validates the map: connections with pos 0 are empty slots
+pub(crate) fn validate(items: &[InItem]) -> MapReport {
+    let mut report = MapReport::default();
+    let mut counts: HashMap<PosType, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(item.id).or_default() += 1;
+    }
+    let mut linked: HashSet<PosType> = HashSet::new();
+    for item in items {
+        for conn in item.connect.iter().filter(|c| c.pos != 0) {
+            linked.insert(item.id);
+            linked.insert(conn.pos);
+        }
+    }
+
+    let mut reported: HashSet<PosType> = HashSet::new();
+    for item in items {
+        let id = item.id;
+        if id == 0 {
+            report.error(MapIssueCode::ReservedId, id, None, "Id 0 is reserved for empty connections".into());
+        }
+        if counts[&id] > 1 && reported.insert(id) {
+            report.error(MapIssueCode::DuplicateId, id, None, format!("Id used by {} nodes", counts[&id]));
+        }
+        if item.connect.len() > MAX_CONNECTIONS {
+            let message = format!("{} connections exceed MAX_CONNECTIONS ({})", item.connect.len(), MAX_CONNECTIONS);
+            report.error(MapIssueCode::TooManyConnections, id, None, message);
+        }
+        if item.name.trim().is_empty() {
+            report.warning(MapIssueCode::EmptyName, id, None, "Name is empty".into());
+        }
+        if item.x.is_some() != item.y.is_some() {
+            report.warning(MapIssueCode::PartialCoordinates, id, None, "Only one of x and y is set, coordinates are ignored".into());
+        }
+        if id != 0 && !linked.contains(&id) {
+            report.warning(MapIssueCode::Isolated, id, None, "Node has no connections".into());
+        }
+
+        let mut targets: HashSet<PosType> = HashSet::new();
+        for (slot, conn) in item.connect.iter().enumerate() {
+            if conn.pos == 0 {
+                if conn.len != 0 {
+                    report.warning(MapIssueCode::UnusedLength, id, Some(slot), format!("Empty slot has length {}", conn.len));
+                }
+                continue;
+            }
+            if !counts.contains_key(&conn.pos) {
+                report.error(MapIssueCode::DanglingConnection, id, Some(slot), format!("Connects to missing node {}", conn.pos));
+            }
+            if conn.len == 0 {
+                report.error(MapIssueCode::ZeroLength, id, Some(slot), format!("Connection to {} has zero length", conn.pos));
+            }
+            if conn.pos == id {
+                report.warning(MapIssueCode::SelfLoop, id, Some(slot), "Connects to itself".into());
+            }
+            if !targets.insert(conn.pos) {
+                report.warning(MapIssueCode::DuplicateConnection, id, Some(slot), format!("Connects to {} more than once", conn.pos));
+            }
+        }
+    }
+    report
+}
+
+/// Tauri 命令：驗證地圖 JSON，回傳錯誤與警告清單  
+/// Tauri command: validate map JSON, returns the list of errors and warnings
+#[tauri::command]
+pub fn map_validate(data: String) -> Result<MapReport, String> {
+    let items: Vec<InItem> = serde_json::from_str(&data)
+        .map_err(|e| format!("JSON parse error: {}", e))?;
+    Ok(validate(&items))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn items(value: serde_json::Value) -> Vec<InItem> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn errors(report: &MapReport) -> Vec<(MapIssueCode, Option<PosType>, Option<usize>)> {
+        report.errors().map(|i| (i.code, i.node, i.slot)).collect()
+    }
+
+    #[test]
+    fn valid_map_has_no_issues() {
+        let report = validate(&items(json!([
+            { "id": 1, "name": "A", "connect": [{ "pos": 2, "len": 5 }] },
+            { "id": 2, "name": "B", "connect": [{ "pos": 1, "len": 5 }] }
+        ])));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn duplicate_id_reported_once() {
+        let report = validate(&items(json!([
+            { "id": 1, "name": "A", "connect": [{ "pos": 2, "len": 5 }] },
+            { "id": 2, "name": "B", "connect": [{ "pos": 1, "len": 5 }] },
+            { "id": 2, "name": "C", "connect": [{ "pos": 1, "len": 5 }] }
+        ])));
+        assert_eq!(errors(&report), vec![(MapIssueCode::DuplicateId, Some(2), None)]);
+    }
+
+    #[test]
+    fn dangling_connection() {
+        let report = validate(&items(json!([
+            { "id": 1, "name": "A", "connect": [{ "pos": 0, "len": 0 }, { "pos": 9, "len": 5 }] }
+        ])));
+        assert_eq!(errors(&report), vec![(MapIssueCode::DanglingConnection, Some(1), Some(1))]);
+    }
+
+    #[test]
+    fn too_many_connections() {
+        let connect: Vec<_> = (0..=MAX_CONNECTIONS).map(|_| json!({ "pos": 0, "len": 0 })).collect();
+        let report = validate(&items(json!([{ "id": 1, "name": "A", "connect": connect }])));
+        assert_eq!(errors(&report), vec![(MapIssueCode::TooManyConnections, Some(1), None)]);
+    }
+
+    #[test]
+    fn zero_length() {
+        let report = validate(&items(json!([
+            { "id": 1, "name": "A", "connect": [{ "pos": 2, "len": 0 }] },
+            { "id": 2, "name": "B", "connect": [{ "pos": 1, "len": 5 }] }
+        ])));
+        assert_eq!(errors(&report), vec![(MapIssueCode::ZeroLength, Some(1), Some(0))]);
+        assert!(report.error_text().contains("[node 1 slot 0]"));
+    }
+
+    #[test]
+    fn warnings_do_not_block() {
+        let report = validate(&items(json!([
+            { "id": 1, "name": " ", "x": 3, "connect": [{ "pos": 1, "len": 5 }, { "pos": 1, "len": 5 }, { "pos": 0, "len": 4 }] },
+            { "id": 2, "name": "B", "connect": [] }
+        ])));
+        assert!(!report.has_errors());
+        let codes: Vec<MapIssueCode> = report.warnings().map(|i| i.code).collect();
+        for code in [
+            MapIssueCode::EmptyName,
+            MapIssueCode::PartialCoordinates,
+            MapIssueCode::SelfLoop,
+            MapIssueCode::DuplicateConnection,
+            MapIssueCode::UnusedLength,
+            MapIssueCode::Isolated,
+        ] {
+            assert!(codes.contains(&code), "missing {:?} in {:?}", code, codes);
+        }
+    }
+}
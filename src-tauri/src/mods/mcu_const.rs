@@ -12,6 +12,95 @@ macro_rules! define_cmd {
 /* #region define_cmd */
 pub const CMD_CODE_DATA_TRRE: u8 = 0x10;
 pub const CMD_CODE_VECH_CONTROL: u8 = 0x20;
+pub const CMD_CODE_MOTOR_CONTROL: u8 = 0x30;
+pub const CMD_CODE_HEARTBEAT: u8 = 0x40;
+pub const CMD_CODE_POSITION: u8 = 0x50;
+pub const CMD_CODE_MISSION: u8 = 0x60;
+
+// 心跳：[CMD_CODE_HEARTBEAT, seq u8]，雙方每 HEARTBEAT_PERIOD_MS 送出一次
+// MCU 超過 HEARTBEAT_TIMEOUT_MS 未收到上位機心跳時須自行停車
+// Heartbeat: [CMD_CODE_HEARTBEAT, seq u8], sent by both sides every HEARTBEAT_PERIOD_MS
+// The MCU must stop the vehicle when no host heartbeat arrives within HEARTBEAT_TIMEOUT_MS
+pub const HEARTBEAT_PERIOD_MS: u16 = 200;
+pub const HEARTBEAT_TIMEOUT_MS: u16 = 1000;
+
+// 位置回報（大端序）：[CMD_CODE_POSITION, node u16, next u16, travelled u32]
+// node 為最後經過的地圖節點，next 為正前往的節點，停在節點上時為 0
+// travelled 為離開 node 後沿邊行進的距離，單位與地圖連接長度相同
+// Position report (big-endian): [CMD_CODE_POSITION, node u16, next u16, travelled u32]
+// node is the last map node passed, next is the node being driven to, 0 while standing on a node
+// travelled is the distance driven along the edge since leaving node, in the same unit as map lengths
+
+// 任務（大端序）：
+// 開始：[CMD_CODE_MISSION, MISSION_CMD_BEGIN, mission_id u16, start u16, count u16]
+// 航點：[CMD_CODE_MISSION, MISSION_CMD_WAYPOINT, mission_id u16, index u16, node u16, heading i16, len u32, wait_ms u32]
+//       heading 與 len 為由前一航點前往 node 的邊，len 為 0 表示原地，抵達後等待 wait_ms
+// 暫停／繼續／中止：[CMD_CODE_MISSION, MISSION_CMD_PAUSE | MISSION_CMD_RESUME | MISSION_CMD_ABORT, mission_id u16]
+// 上位機最多領先車輛 MISSION_WINDOW 個航點送出，MCU 需能暫存同樣數量；進度由位置回報判斷
+// Mission (big-endian):
+// Begin:    [CMD_CODE_MISSION, MISSION_CMD_BEGIN, mission_id u16, start u16, count u16]
+// Waypoint: [CMD_CODE_MISSION, MISSION_CMD_WAYPOINT, mission_id u16, index u16, node u16, heading i16, len u32, wait_ms u32]
+//           heading and len describe the edge from the previous waypoint to node, len 0 stays in place, waits wait_ms on arrival
+// Pause / resume / abort: [CMD_CODE_MISSION, MISSION_CMD_PAUSE | MISSION_CMD_RESUME | MISSION_CMD_ABORT, mission_id u16]
+// The host sends at most MISSION_WINDOW waypoints ahead of the vehicle, the MCU must queue as many; progress follows the position reports
+pub const MISSION_CMD_BEGIN: u8 = 0x01;
+pub const MISSION_CMD_WAYPOINT: u8 = 0x02;
+pub const MISSION_CMD_PAUSE: u8 = 0x03;
+pub const MISSION_CMD_RESUME: u8 = 0x04;
+pub const MISSION_CMD_ABORT: u8 = 0x05;
+pub const MISSION_WINDOW: u8 = 4;
+
+// 裝置探索（UDP，大端序）：
+// 探詢：[DISCOVERY_MAGIC u32, DISCOVERY_PROBE, seq u8]，上位機廣播至 UDP 埠
+// 回覆：[DISCOVERY_MAGIC u32, DISCOVERY_REPLY, seq u8, device_id u16,
+//       fw_major u8, fw_minor u8, fw_patch u8, capabilities u16, tcp_port u16, name_len u8, name]
+// Device discovery (UDP, big-endian):
+// Probe: [DISCOVERY_MAGIC u32, DISCOVERY_PROBE, seq u8], broadcast by the host to the UDP port
+// Reply: [DISCOVERY_MAGIC u32, DISCOVERY_REPLY, seq u8, device_id u16,
+//        fw_major u8, fw_minor u8, fw_patch u8, capabilities u16, tcp_port u16, name_len u8, name]
+pub const DISCOVERY_MAGIC: u32 = 0x54415044;
+pub const DISCOVERY_PROBE: u8 = 0x01;
+pub const DISCOVERY_REPLY: u8 = 0x02;
+
+// 裝置能力位元／device capability bits
+pub const DISCOVERY_CAP_MOTOR: u16 = 0x0001;
+pub const DISCOVERY_CAP_POSITION: u16 = 0x0002;
+pub const DISCOVERY_CAP_OTA: u16 = 0x0004;
+pub const DISCOVERY_CAP_TCP: u16 = 0x0008;
+
+// WiFi 訊框（大端序）：[device_id u16, seq u16, type u8, len u16, checksum u16, payload]
+// checksum 為檢查碼欄位填 0 時，標頭與資料所有位元組之 16 位元累加
+// type 為 WIFI_FRAME_TYPE_MCU 時，payload 與 UART 封包 `{` `}` 之間的資料相同
+// WiFi frame (big-endian): [device_id u16, seq u16, type u8, len u16, checksum u16, payload]
+// checksum is the 16-bit sum of all header and payload bytes with the checksum field zeroed
+// With type WIFI_FRAME_TYPE_MCU the payload equals the data between `{` `}` of a UART packet
+pub const WIFI_FRAME_HEADER_SIZE: u8 = 9;
+pub const WIFI_FRAME_TYPE_RAW: u8 = 0x00;
+pub const WIFI_FRAME_TYPE_MCU: u8 = 0x01;
+pub const WIFI_FRAME_TYPE_OTA: u8 = 0x02;
+
+// 韌體更新（TCP，WIFI_FRAME_TYPE_OTA 訊框資料，大端序）：
+// 開始：[OTA_CMD_BEGIN, size u32, chunk_size u16, sha256 [u8; 32]]
+// 區塊：[OTA_CMD_CHUNK, index u32, crc32 u32, data]，crc32 為 IEEE CRC-32
+// 結束：[OTA_CMD_END]，裝置比對整體映像的 SHA-256
+// 回覆：[OTA_CMD_ACK, cmd u8, index u32, status u8]，每個指令都需回覆
+// Firmware update (TCP, payload of WIFI_FRAME_TYPE_OTA frames, big-endian):
+// Begin: [OTA_CMD_BEGIN, size u32, chunk_size u16, sha256 [u8; 32]]
+// Chunk: [OTA_CMD_CHUNK, index u32, crc32 u32, data], crc32 is IEEE CRC-32
+// End:   [OTA_CMD_END], the device checks the SHA-256 of the whole image
+// Reply: [OTA_CMD_ACK, cmd u8, index u32, status u8], every command is acknowledged
+pub const OTA_CMD_BEGIN: u8 = 0x01;
+pub const OTA_CMD_CHUNK: u8 = 0x02;
+pub const OTA_CMD_END: u8 = 0x03;
+pub const OTA_CMD_ACK: u8 = 0x80;
+pub const OTA_CHUNK_HEADER_SIZE: u8 = 9;
+
+pub const OTA_STATUS_OK: u8 = 0x00;
+pub const OTA_STATUS_CRC: u8 = 0x01;
+pub const OTA_STATUS_INDEX: u8 = 0x02;
+pub const OTA_STATUS_SIZE: u8 = 0x03;
+pub const OTA_STATUS_HASH: u8 = 0x04;
+pub const OTA_STATUS_BUSY: u8 = 0x05;
 
 pub const CMD_CODE_LOOP_STOP: u8 = 0x00;
 pub const CMD_CODE_ONLY_ONCE: u8 = 0x01;
@@ -21,8 +110,21 @@ pub const CMD_CODE_MOTOR_LEFT: u8 = 0x00;
 pub const CMD_CODE_MOTOR_RIGHT: u8 = 0x01;
 
 pub const CMD_CODE_SPEED: u8  = 0x00;
+pub const CMD_CODE_SPEED_SETPOINT: u8  = 0x01;
+pub const CMD_CODE_ROTATE_DIRECTION: u8  = 0x02;
 pub const CMD_CODE_ADC: u8  = 0x05;
 
+pub const CMD_CODE_DIRECTION_FORWARD: u8 = 0x00;
+pub const CMD_CODE_DIRECTION_REVERSE: u8 = 0x01;
+
+pub const CMD_CODE_MOVE_STOP: u8 = 0x00;
+pub const CMD_CODE_MOVE_FORWARD: u8 = 0x01;
+pub const CMD_CODE_MOVE_BACKWARD: u8 = 0x02;
+pub const CMD_CODE_MOVE_LEFT: u8 = 0x03;
+pub const CMD_CODE_MOVE_RIGHT: u8 = 0x04;
+pub const CMD_CODE_MOVE_DIFFERENTIAL: u8 = 0x05;
+pub const CMD_CODE_MOVE_EMERGENCY_STOP: u8 = 0x0F;
+
 define_cmd!(
     CMD_LEFT_SPEED_STORE,
     [CMD_CODE_MOTOR_LEFT, CMD_CODE_SPEED]
@@ -89,24 +191,61 @@ define_cmd!(
     [CMD_CODE_MOTOR_RIGHT, CMD_CODE_ADC, CMD_CODE_LOOP_START]
 );
 
+// 馬達設定指令 / motor set commands
+// [CMD_CODE_MOTOR_CONTROL, motor, CMD_CODE_SPEED_SETPOINT, setpoint u8]
+// [CMD_CODE_MOTOR_CONTROL, motor, CMD_CODE_ROTATE_DIRECTION, CMD_CODE_DIRECTION_*]
+define_cmd!(
+    CMD_LEFT_SPEED_SET,
+    [CMD_CODE_MOTOR_CONTROL, CMD_CODE_MOTOR_LEFT, CMD_CODE_SPEED_SETPOINT]
+);
+define_cmd!(
+    CMD_LEFT_DIRECTION_SET,
+    [CMD_CODE_MOTOR_CONTROL, CMD_CODE_MOTOR_LEFT, CMD_CODE_ROTATE_DIRECTION]
+);
+define_cmd!(
+    CMD_RIGHT_SPEED_SET,
+    [CMD_CODE_MOTOR_CONTROL, CMD_CODE_MOTOR_RIGHT, CMD_CODE_SPEED_SETPOINT]
+);
+define_cmd!(
+    CMD_RIGHT_DIRECTION_SET,
+    [CMD_CODE_MOTOR_CONTROL, CMD_CODE_MOTOR_RIGHT, CMD_CODE_ROTATE_DIRECTION]
+);
+
+/// 每單位速度設定值對應的回報速度，回報速度 = setpoint × SPEED_PER_SETPOINT <br>
+/// Reported speed per setpoint step, reported speed = setpoint × SPEED_PER_SETPOINT
+pub const SPEED_PER_SETPOINT: f32 = 1.0;
+
+// 移動指令參數（大端序）/ move command parameters (big-endian)
+// FORWARD / BACKWARD:  speed f32, duration_ms u32
+// LEFT / RIGHT:        speed f32, radius f32, duration_ms u32
+// DIFFERENTIAL:        left f32, right f32, duration_ms u32
+// duration_ms = 0 表示持續到下一個指令 / duration_ms = 0 keeps moving until the next command
 define_cmd!(
     CMD_MOVE_STOP,
-    [CMD_CODE_VECH_CONTROL, 0x00]
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_STOP]
 );
 define_cmd!(
     CMD_MOVE_FORWARD,
-    [CMD_CODE_VECH_CONTROL, 0x01]
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_FORWARD]
 );
 define_cmd!(
     CMD_MOVE_BACKWARD,
-    [CMD_CODE_VECH_CONTROL, 0x02]
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_BACKWARD]
 );
 define_cmd!(
     CMD_MOVE_LEFT,
-    [CMD_CODE_VECH_CONTROL, 0x03]
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_LEFT]
 );
 define_cmd!(
     CMD_MOVE_RIGHT,
-    [CMD_CODE_VECH_CONTROL, 0x04]
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_RIGHT]
+);
+define_cmd!(
+    CMD_MOVE_DIFFERENTIAL,
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_DIFFERENTIAL]
+);
+define_cmd!(
+    CMD_MOVE_EMERGENCY_STOP,
+    [CMD_CODE_VECH_CONTROL, CMD_CODE_MOVE_EMERGENCY_STOP]
 );
 /* #endregion */
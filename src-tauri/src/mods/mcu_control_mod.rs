@@ -1,6 +1,8 @@
-use log::error;
-use tauri::{AppHandle, Manager};
-use crate::{mods::{uart_packet_mod::UartPacket, mcu_const}, GlobalState};
+use std::{net::SocketAddr, time::Instant};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{mods::{uart_packet_mod::UartPacket, wifi_mod::WifiProtocol, wifi_packet_mod::{WifiFrame, WifiPacket}, mcu_const, mcu_store_mod::{MotorDataType, MotorSide, MotorTracking}}, GlobalState};
 
 #[tauri::command]
 pub async fn cmd_send_spd_stop(app: AppHandle) -> Result<(), String> {
@@ -52,3 +54,445 @@ pub async fn cmd_send_spd_start(app: AppHandle) -> Result<(), String> {
     transmit_buffer.push(packet).map_err(|e| format!("{}", e))?;
     Ok(())
 }
+
+/// 車輛移動指令，由前端以 `{ "mode": ..., ... }` 形式傳入<br>
+/// Vehicle motion command, passed from the frontend as `{ "mode": ..., ... }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum VehicleMotion {
+    Stop,
+    Forward { speed: f32, duration_ms: u32 },
+    Backward { speed: f32, duration_ms: u32 },
+    Left { speed: f32, radius: f32, duration_ms: u32 },
+    Right { speed: f32, radius: f32, duration_ms: u32 },
+    Differential { left: f32, right: f32, duration_ms: u32 },
+}
+impl VehicleMotion {
+    /// 編碼為 `CMD_CODE_VECH_CONTROL` 封包資料（大端序）<br>
+    /// Encodes into `CMD_CODE_VECH_CONTROL` packet data (big-endian)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut cmd = Vec::<u8>::new();
+        match *self {
+            VehicleMotion::Stop => {
+                cmd.extend(mcu_const::CMD_MOVE_STOP.payload);
+            }
+            VehicleMotion::Forward { speed, duration_ms } => {
+                cmd.extend(mcu_const::CMD_MOVE_FORWARD.payload);
+                cmd.extend(speed.to_be_bytes());
+                cmd.extend(duration_ms.to_be_bytes());
+            }
+            VehicleMotion::Backward { speed, duration_ms } => {
+                cmd.extend(mcu_const::CMD_MOVE_BACKWARD.payload);
+                cmd.extend(speed.to_be_bytes());
+                cmd.extend(duration_ms.to_be_bytes());
+            }
+            VehicleMotion::Left { speed, radius, duration_ms } => {
+                cmd.extend(mcu_const::CMD_MOVE_LEFT.payload);
+                cmd.extend(speed.to_be_bytes());
+                cmd.extend(radius.to_be_bytes());
+                cmd.extend(duration_ms.to_be_bytes());
+            }
+            VehicleMotion::Right { speed, radius, duration_ms } => {
+                cmd.extend(mcu_const::CMD_MOVE_RIGHT.payload);
+                cmd.extend(speed.to_be_bytes());
+                cmd.extend(radius.to_be_bytes());
+                cmd.extend(duration_ms.to_be_bytes());
+            }
+            VehicleMotion::Differential { left, right, duration_ms } => {
+                cmd.extend(mcu_const::CMD_MOVE_DIFFERENTIAL.payload);
+                cmd.extend(left.to_be_bytes());
+                cmd.extend(right.to_be_bytes());
+                cmd.extend(duration_ms.to_be_bytes());
+            }
+        }
+        cmd
+    }
+
+    /// 檢查參數是否為有限值且非負<br>
+    /// Checks that all parameters are finite and speeds/radius are non-negative
+    fn check(&self) -> Result<(), String> {
+        let values: Vec<f32> = match *self {
+            VehicleMotion::Stop => Vec::new(),
+            VehicleMotion::Forward { speed, .. } |
+            VehicleMotion::Backward { speed, .. } => vec![speed],
+            VehicleMotion::Left { speed, radius, .. } |
+            VehicleMotion::Right { speed, radius, .. } => vec![speed, radius],
+            VehicleMotion::Differential { left, right, .. } => {
+                if !left.is_finite() || !right.is_finite() {
+                    return Err(format!("Invalid differential setpoint: {} / {}", left, right));
+                }
+                Vec::new()
+            }
+        };
+        if let Some(v) = values.iter().find(|v| !v.is_finite() || **v < 0.0) {
+            return Err(format!("Invalid motion parameter: {}", v));
+        }
+        Ok(())
+    }
+}
+
+/// MCU 通訊鏈路，由前端以 `{ "kind": ... }` 形式傳入<br>
+/// MCU communication link, passed from the frontend as `{ "kind": ... }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum McuLink {
+    Uart,
+    Wifi { addr: SocketAddr, protocol: WifiProtocol, device_id: u16 },
+}
+
+/// MCU 鏈路管理：目前鏈路與 WiFi 訊框序號<br>
+/// MCU link manager: current link and WiFi frame sequence
+pub struct McuLinkManager {
+    link: McuLink,
+    tx_seq: u16,
+}
+impl McuLinkManager {
+    /// 建立預設為 UART 的鏈路管理<br>
+    /// Creates a link manager defaulting to UART
+    pub fn new() -> Self {
+        Self {
+            link: McuLink::Uart,
+            tx_seq: 0,
+        }
+    }
+
+    /// 取得目前鏈路<br>
+    /// Returns the current link
+    pub fn link(&self) -> McuLink {
+        self.link
+    }
+
+    /// 切換鏈路<br>
+    /// Switches the link
+    pub fn set(&mut self, link: McuLink) {
+        self.link = link;
+    }
+
+    /// 取得下一個 WiFi 訊框序號<br>
+    /// Returns the next WiFi frame sequence number
+    pub fn next_seq(&mut self) -> u16 {
+        self.tx_seq = self.tx_seq.wrapping_add(1);
+        self.tx_seq
+    }
+}
+impl Default for McuLinkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 檢查目前鏈路是否可傳送：UART 埠已開啟或 WiFi 已啟動<br>
+/// Checks whether the current link can transmit: UART port open or WiFi started
+pub async fn link_ready(app: &AppHandle) -> bool {
+    let global_state = app.state::<GlobalState>();
+    let link = global_state.mcu_link.lock().await.link();
+    match link {
+        McuLink::Uart => global_state.uart_manager.lock().await.check_open().await.is_ok(),
+        McuLink::Wifi { .. } => global_state.wifi_manager.lock().await.is_started(),
+    }
+}
+
+/// 將指令資料依目前鏈路封裝並推入傳送緩衝；clear 為 true 時先清除尚未送出的封包，回傳清除數量<br>
+/// Wraps command data for the current link and pushes it into the transmit buffer;
+/// when clear is true queued packets are dropped first, returns the dropped count
+async fn link_transmit(app: &AppHandle, cmd: Vec<u8>, clear: bool) -> Result<usize, String> {
+    let global_state = app.state::<GlobalState>();
+    let (link, seq) = {
+        let mut mcu_link = global_state.mcu_link.lock().await;
+        (mcu_link.link(), mcu_link.next_seq())
+    };
+    let result = match link {
+        McuLink::Uart => {
+            let packet = UartPacket::new(cmd).map_err(|e| format!("{}", e))?;
+            let mut transmit_buffer = global_state.uart_transmit_buffer.lock().await;
+            let dropped = if clear { transmit_buffer.take_all().len() } else { 0 };
+            transmit_buffer.push(packet).map(|_| dropped).map_err(|e| format!("{}", e))
+        }
+        McuLink::Wifi { addr, protocol, device_id } => {
+            let frame = WifiFrame::new(device_id, seq, mcu_const::WIFI_FRAME_TYPE_MCU, cmd)
+                .map_err(|e| format!("{}", e))?;
+            let packet = WifiPacket::from_frame(addr, &frame).map_err(|e| format!("{}", e))?;
+            let mut transmit_buffer = match protocol {
+                WifiProtocol::Tcp => global_state.wifi_tcp_transmit_buffer.lock().await,
+                WifiProtocol::Udp => global_state.wifi_udp_transmit_buffer.lock().await,
+            };
+            let dropped = if clear { transmit_buffer.take_all().len() } else { 0 };
+            transmit_buffer.push(packet).map(|_| dropped).map_err(|e| format!("{}", e))
+        }
+    };
+    result.map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// 將指令資料依目前鏈路封裝為 UartPacket 或 WiFi 訊框並推入傳送緩衝<br>
+/// Wraps command data into a UartPacket or WiFi frame for the current link and pushes it into the transmit buffer
+pub async fn transmit_push(app: &AppHandle, cmd: Vec<u8>) -> Result<(), String> {
+    link_transmit(app, cmd, false).await.map(|_| ())
+}
+
+/// Tauri 指令：送出車輛移動指令<br>
+/// Tauri command: send a vehicle motion command
+#[tauri::command]
+pub async fn cmd_vehicle_control(app: AppHandle, motion: VehicleMotion) -> Result<(), String> {
+    motion.check().map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    transmit_push(&app, motion.encode()).await?;
+    info!("Vehicle control: {:?}", motion);
+    Ok(())
+}
+
+/// Tauri 指令：緊急停止，清除傳送緩衝中尚未送出的封包後立即送出停止<br>
+/// Tauri command: emergency stop, drops queued packets in the transmit buffer and sends stop first
+#[tauri::command]
+pub async fn cmd_vehicle_emergency_stop(app: AppHandle) -> Result<(), String> {
+    let dropped = link_transmit(&app, mcu_const::CMD_MOVE_EMERGENCY_STOP.payload.to_vec(), true).await?;
+    warn!("Emergency stop sent, {} queued packets dropped", dropped);
+    Ok(())
+}
+
+/// Tauri 指令：切換 MCU 通訊鏈路<br>
+/// Tauri command: switch the MCU communication link
+#[tauri::command]
+pub async fn cmd_mcu_link_set(app: AppHandle, link: McuLink) -> Result<McuLink, String> {
+    let global_state = app.state::<GlobalState>();
+    global_state.mcu_link.lock().await.set(link);
+    info!("MCU link: {:?}", link);
+    Ok(link)
+}
+
+/// Tauri 指令：取得目前 MCU 通訊鏈路<br>
+/// Tauri command: get the current MCU communication link
+#[tauri::command]
+pub async fn cmd_mcu_link_get(app: AppHandle) -> Result<McuLink, String> {
+    let global_state = app.state::<GlobalState>();
+    let link = global_state.mcu_link.lock().await.link();
+    Ok(link)
+}
+
+/// 取得馬達側的協定碼<br>
+/// Returns the protocol code of a motor side
+pub fn motor_side_code(side: MotorSide) -> u8 {
+    match side {
+        MotorSide::Left => mcu_const::CMD_CODE_MOTOR_LEFT,
+        MotorSide::Right => mcu_const::CMD_CODE_MOTOR_RIGHT,
+    }
+}
+
+/// 由協定碼取得馬達側<br>
+/// Returns the motor side of a protocol code
+pub fn motor_side_from_code(code: u8) -> Option<MotorSide> {
+    match code {
+        mcu_const::CMD_CODE_MOTOR_LEFT => Some(MotorSide::Left),
+        mcu_const::CMD_CODE_MOTOR_RIGHT => Some(MotorSide::Right),
+        _ => None,
+    }
+}
+
+/// 取得資料種類的協定碼，僅存於上位機的通道回傳 None<br>
+/// Returns the protocol code of a motor data quantity, None for host-only channels
+pub fn motor_data_code(kind: MotorDataType) -> Option<u8> {
+    match kind {
+        MotorDataType::SpeedPresent => Some(mcu_const::CMD_CODE_SPEED),
+        MotorDataType::SpeedSetpoint => Some(mcu_const::CMD_CODE_SPEED_SETPOINT),
+        MotorDataType::RotateDirection => Some(mcu_const::CMD_CODE_ROTATE_DIRECTION),
+        MotorDataType::AdcValue => Some(mcu_const::CMD_CODE_ADC),
+        MotorDataType::SpeedCommand | MotorDataType::DirectionCommand => None,
+    }
+}
+
+/// 由協定碼取得資料種類<br>
+/// Returns the motor data quantity of a protocol code
+pub fn motor_data_from_code(code: u8) -> Option<MotorDataType> {
+    match code {
+        mcu_const::CMD_CODE_SPEED => Some(MotorDataType::SpeedPresent),
+        mcu_const::CMD_CODE_SPEED_SETPOINT => Some(MotorDataType::SpeedSetpoint),
+        mcu_const::CMD_CODE_ROTATE_DIRECTION => Some(MotorDataType::RotateDirection),
+        mcu_const::CMD_CODE_ADC => Some(MotorDataType::AdcValue),
+        _ => None,
+    }
+}
+
+/// 資料回傳模式<br>
+/// Data report mode
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataReportMode {
+    Stop,
+    Once,
+    Start,
+}
+impl DataReportMode {
+    fn code(self) -> u8 {
+        match self {
+            DataReportMode::Stop => mcu_const::CMD_CODE_LOOP_STOP,
+            DataReportMode::Once => mcu_const::CMD_CODE_ONLY_ONCE,
+            DataReportMode::Start => mcu_const::CMD_CODE_LOOP_START,
+        }
+    }
+}
+
+/// 單一資料串流選擇：馬達、資料種類與回傳模式<br>
+/// One data stream selection: motor, quantity and report mode
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DataSelection {
+    pub motor: MotorSide,
+    pub quantity: MotorDataType,
+    pub mode: DataReportMode,
+}
+
+/// 依選擇清單建立 `CMD_CODE_DATA_TRRE` 封包資料<br>
+/// Builds `CMD_CODE_DATA_TRRE` packet data from a list of selections
+pub fn data_select_encode(selections: &[DataSelection]) -> Result<Vec<u8>, String> {
+    let mut cmd = Vec::<u8>::new();
+    cmd.push(mcu_const::CMD_CODE_DATA_TRRE);
+    for sel in selections {
+        let quantity = motor_data_code(sel.quantity)
+            .ok_or_else(|| format!("{:?} is not reported by the MCU", sel.quantity))?;
+        cmd.push(motor_side_code(sel.motor));
+        cmd.push(quantity);
+        cmd.push(sel.mode.code());
+    }
+    Ok(cmd)
+}
+
+/// Tauri 指令：設定各馬達各資料串流的回傳模式<br>
+/// Tauri command: set the report mode of each motor data stream
+#[tauri::command]
+pub async fn cmd_send_data_select(app: AppHandle, selections: Vec<DataSelection>) -> Result<(), String> {
+    if selections.is_empty() {
+        let message = "No data stream selected".to_string();
+        error!("{}", message);
+        return Err(message);
+    }
+    let cmd = data_select_encode(&selections).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    transmit_push(&app, cmd).await?;
+    info!("Data select: {:?}", selections);
+    Ok(())
+}
+
+/// 馬達旋轉方向<br>
+/// Motor rotate direction
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotorDirection {
+    Forward,
+    Reverse,
+}
+impl MotorDirection {
+    fn code(self) -> u8 {
+        match self {
+            MotorDirection::Forward => mcu_const::CMD_CODE_DIRECTION_FORWARD,
+            MotorDirection::Reverse => mcu_const::CMD_CODE_DIRECTION_REVERSE,
+        }
+    }
+}
+
+/// 預設追蹤誤差容許值<br>
+/// Default tolerance of the speed tracking error
+pub const TRACKING_TOLERANCE: f32 = 5.0;
+
+/// Tauri 指令：設定馬達速度設定值，並記錄於 SpeedCommand 通道<br>
+/// Tauri command: set a motor speed setpoint and record it in the SpeedCommand channel
+#[tauri::command]
+pub async fn cmd_motor_set_speed(app: AppHandle, motor: MotorSide, setpoint: u8) -> Result<(), String> {
+    let global_state = app.state::<GlobalState>();
+    let mut cmd = match motor {
+        MotorSide::Left => mcu_const::CMD_LEFT_SPEED_SET.payload.to_vec(),
+        MotorSide::Right => mcu_const::CMD_RIGHT_SPEED_SET.payload.to_vec(),
+    };
+    cmd.push(setpoint);
+    transmit_push(&app, cmd).await?;
+    let mut store_datas = global_state.store_datas.lock().await;
+    store_datas.push(motor.data_type(MotorDataType::SpeedCommand), setpoint);
+    info!("Motor {:?} speed setpoint: {}", motor, setpoint);
+    Ok(())
+}
+
+/// Tauri 指令：設定馬達旋轉方向，並記錄於 DirectionCommand 通道<br>
+/// Tauri command: set a motor rotate direction and record it in the DirectionCommand channel
+#[tauri::command]
+pub async fn cmd_motor_set_direction(app: AppHandle, motor: MotorSide, direction: MotorDirection) -> Result<(), String> {
+    let global_state = app.state::<GlobalState>();
+    let mut cmd = match motor {
+        MotorSide::Left => mcu_const::CMD_LEFT_DIRECTION_SET.payload.to_vec(),
+        MotorSide::Right => mcu_const::CMD_RIGHT_DIRECTION_SET.payload.to_vec(),
+    };
+    cmd.push(direction.code());
+    transmit_push(&app, cmd).await?;
+    let mut store_datas = global_state.store_datas.lock().await;
+    store_datas.push(motor.data_type(MotorDataType::DirectionCommand), direction.code());
+    info!("Motor {:?} direction: {:?}", motor, direction);
+    Ok(())
+}
+
+/// 取得兩側馬達的追蹤結果<br>
+/// Returns the tracking results of both motors
+pub async fn motor_tracking(app: &AppHandle, tolerance: f32) -> Vec<MotorTracking> {
+    let global_state = app.state::<GlobalState>();
+    let store_datas = global_state.store_datas.lock().await;
+    [MotorSide::Left, MotorSide::Right].into_iter()
+        .map(|side| store_datas.tracking(side, tolerance, Instant::now()))
+        .collect()
+}
+
+/// 更新追蹤標記，狀態改變時發出 `motor-tracking` 事件<br>
+/// Updates the tracking flags and emits a `motor-tracking` event when the flag state changes
+pub async fn motor_tracking_check(app: &AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    let now = Instant::now();
+    let changed: Vec<MotorTracking> = {
+        let mut store_datas = global_state.store_datas.lock().await;
+        [MotorSide::Left, MotorSide::Right].into_iter()
+            .filter_map(|side| store_datas.tracking_update(side, TRACKING_TOLERANCE, now))
+            .collect()
+    };
+    for result in &changed {
+        if result.flagged {
+            warn!("Motor tracking error: {:?}", result);
+        } else {
+            info!("Motor tracking recovered: {:?}", result.motor);
+        }
+        if let Err(e) = app.emit("motor-tracking", result) {
+            error!("Emit motor-tracking failed: {}", e);
+        }
+    }
+}
+
+/// Tauri 指令：取得兩側馬達的追蹤結果<br>
+/// Tauri command: get the tracking results of both motors
+#[tauri::command]
+pub async fn cmd_motor_tracking(app: AppHandle, tolerance: Option<f32>) -> Result<Vec<MotorTracking>, String> {
+    Ok(motor_tracking(&app, tolerance.unwrap_or(TRACKING_TOLERANCE)).await)
+}
+
+/// 外部介面（WebSocket／HTTP／MQTT）允許執行的指令白名單，以 `{ "cmd": ..., ... }` 形式傳入<br>
+/// Whitelist of commands allowed from external interfaces (WebSocket / HTTP / MQTT), passed as `{ "cmd": ..., ... }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum McuCommand {
+    VehicleControl { motion: VehicleMotion },
+    EmergencyStop,
+    MotorSetSpeed { motor: MotorSide, setpoint: u8 },
+    MotorSetDirection { motor: MotorSide, direction: MotorDirection },
+    DataSelect { selections: Vec<DataSelection> },
+}
+
+/// 執行白名單指令<br>
+/// Executes a whitelisted command
+pub async fn command_execute(app: AppHandle, command: McuCommand) -> Result<(), String> {
+    info!("External command: {:?}", command);
+    match command {
+        McuCommand::VehicleControl { motion } => cmd_vehicle_control(app, motion).await,
+        McuCommand::EmergencyStop => cmd_vehicle_emergency_stop(app).await,
+        McuCommand::MotorSetSpeed { motor, setpoint } => cmd_motor_set_speed(app, motor, setpoint).await,
+        McuCommand::MotorSetDirection { motor, direction } => cmd_motor_set_direction(app, motor, direction).await,
+        McuCommand::DataSelect { selections } => cmd_send_data_select(app, selections).await,
+    }
+}
@@ -1,9 +1,39 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use crate::mods::mcu_const;
+
+/// 更新通知的佇列長度，訂閱者落後超過此數量會遺失舊資料 <br>
+/// Queue length of update notifications, subscribers lagging further lose old updates
+const UPDATE_CHANNEL_SIZE: usize = 256;
+
+/// 最後一次下達指令後的穩定時間，期間不判定追蹤誤差（ms）<br>
+/// Settling time after the last command, no tracking error is judged meanwhile (ms)
+pub const TRACKING_SETTLE_MS: u64 = 1000;
+/// 誤差需持續超過此時間才標記（ms）<br>
+/// The error must persist this long before it is flagged (ms)
+pub const TRACKING_HOLD_MS: u64 = 500;
+/// 標記後誤差需低於容許值乘上此比例才恢復<br>
+/// Once flagged, the error must drop below the tolerance times this ratio to recover
+pub const TRACKING_RECOVER_RATIO: f32 = 0.5;
+
 #[derive(Debug)]
 pub enum DataSlice<'a> {
     U8(&'a [u8]),
     U16(&'a [u16]),
     F32(&'a [f32]),
 }
+impl DataSlice<'_> {
+    /// 轉為 f64 陣列 <br>
+    /// Converts into an f64 vector
+    pub fn to_f64(&self) -> Vec<f64> {
+        match self {
+            DataSlice::U8(v) => v.iter().map(|x| *x as f64).collect(),
+            DataSlice::U16(v) => v.iter().map(|x| *x as f64).collect(),
+            DataSlice::F32(v) => v.iter().map(|x| *x as f64).collect(),
+        }
+    }
+}
 
 pub struct MotorDataStore {
     max_length: usize,
@@ -11,12 +41,34 @@ pub struct MotorDataStore {
     speed_setpoint: Vec<u8>,
     speed_present: Vec<f32>,
     rotate_direction: Vec<u8>,
+    speed_command: Vec<u8>,
+    direction_command: Vec<u8>,
+    command_at: Option<Instant>,
+    monitor: TrackingMonitor,
 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MotorDataType {
     AdcValue,
     SpeedSetpoint,
     SpeedPresent,
     RotateDirection,
+    /// 上位機下達的設定值 / setpoint commanded by the host
+    SpeedCommand,
+    /// 上位機下達的方向 / direction commanded by the host
+    DirectionCommand,
+}
+impl MotorDataType {
+    /// 所有資料種類 <br>
+    /// All data quantities
+    pub const ALL: [MotorDataType; 6] = [
+        MotorDataType::AdcValue,
+        MotorDataType::SpeedSetpoint,
+        MotorDataType::SpeedPresent,
+        MotorDataType::RotateDirection,
+        MotorDataType::SpeedCommand,
+        MotorDataType::DirectionCommand,
+    ];
 }
 impl MotorDataStore {
     pub fn new(max_length: usize) -> Self {
@@ -26,10 +78,14 @@ impl MotorDataStore {
             speed_setpoint: Vec::new(),
             speed_present: Vec::new(),
             rotate_direction: Vec::new(),
+            speed_command: Vec::new(),
+            direction_command: Vec::new(),
+            command_at: None,
+            monitor: TrackingMonitor::default(),
         }
     }
 
-    pub fn push(&mut self, kind: MotorDataType, value: impl Into<u64>) {
+    pub fn push(&mut self, kind: MotorDataType, value: impl Into<f64>) {
         match kind {
             MotorDataType::AdcValue => {
                 let v = value.into() as u16;
@@ -55,6 +111,20 @@ impl MotorDataStore {
                 self.rotate_direction.drain(0..overflow);
                 self.rotate_direction.push(v);
             }
+            MotorDataType::SpeedCommand => {
+                let v = value.into() as u8;
+                let overflow = self.speed_command.len().saturating_sub(self.max_length);
+                self.speed_command.drain(0..overflow);
+                self.speed_command.push(v);
+                self.command_at = Some(Instant::now());
+            }
+            MotorDataType::DirectionCommand => {
+                let v = value.into() as u8;
+                let overflow = self.direction_command.len().saturating_sub(self.max_length);
+                self.direction_command.drain(0..overflow);
+                self.direction_command.push(v);
+                self.command_at = Some(Instant::now());
+            }
         }
     }
 
@@ -64,6 +134,8 @@ impl MotorDataStore {
             MotorDataType::SpeedSetpoint => DataSlice::U8(&self.speed_setpoint),
             MotorDataType::SpeedPresent => DataSlice::F32(&self.speed_present),
             MotorDataType::RotateDirection => DataSlice::U8(&self.rotate_direction),
+            MotorDataType::SpeedCommand => DataSlice::U8(&self.speed_command),
+            MotorDataType::DirectionCommand => DataSlice::U8(&self.direction_command),
         }
     }
 
@@ -73,30 +145,212 @@ impl MotorDataStore {
             MotorDataType::SpeedSetpoint => self.speed_setpoint.clear(),
             MotorDataType::SpeedPresent => self.speed_present.clear(),
             MotorDataType::RotateDirection => self.rotate_direction.clear(),
+            MotorDataType::SpeedCommand => self.speed_command.clear(),
+            MotorDataType::DirectionCommand => self.direction_command.clear(),
+        }
+    }
+
+    /// 比較下達值與 MCU 回報值，計算追蹤誤差；設定值先換算為回報速度的單位，下達指令後的穩定時間內不判定<br>
+    /// Compares commanded values with MCU reports and computes the tracking error;
+    /// the setpoint is converted into the unit of the reported speed and nothing is judged while settling after a command
+    pub fn tracking(&self, side: MotorSide, tolerance: f32, now: Instant) -> MotorTracking {
+        let speed_command = self.speed_command.last().copied();
+        let speed_setpoint = self.speed_setpoint.last().copied();
+        let speed_present = self.speed_present.last().copied();
+        let direction_command = self.direction_command.last().copied();
+        let rotate_direction = self.rotate_direction.last().copied();
+        let setpoint_echo_ok = match (speed_command, speed_setpoint) {
+            (Some(cmd), Some(echo)) => Some(cmd == echo),
+            _ => None,
+        };
+        let direction_echo_ok = match (direction_command, rotate_direction) {
+            (Some(cmd), Some(echo)) => Some(cmd == echo),
+            _ => None,
+        };
+        let speed_target = speed_command.map(|cmd| cmd as f32 * mcu_const::SPEED_PER_SETPOINT);
+        let tracking_error = match (speed_target, speed_present) {
+            (Some(target), Some(present)) => Some(present - target),
+            _ => None,
+        };
+        let settling = self.command_at
+            .is_some_and(|at| now.saturating_duration_since(at) < Duration::from_millis(TRACKING_SETTLE_MS));
+        let exceeded = !settling && (
+            setpoint_echo_ok == Some(false)
+            || direction_echo_ok == Some(false)
+            || tracking_error.is_some_and(|e| e.abs() > tolerance)
+        );
+        MotorTracking {
+            motor: side,
+            speed_command,
+            speed_setpoint,
+            speed_target,
+            speed_present,
+            direction_command,
+            rotate_direction,
+            setpoint_echo_ok,
+            direction_echo_ok,
+            tracking_error,
+            settling,
+            exceeded,
+            flagged: self.monitor.flagged,
         }
     }
+
+    /// 更新追蹤標記，標記狀態改變時回傳結果<br>
+    /// Updates the tracking flag, returns the result when the flag state changes
+    pub fn tracking_update(&mut self, side: MotorSide, tolerance: f32, now: Instant) -> Option<MotorTracking> {
+        let mut result = self.tracking(side, tolerance, now);
+        let was_flagged = self.monitor.flagged;
+        result.flagged = self.monitor.update(&result, tolerance, now);
+        (result.flagged != was_flagged).then_some(result)
+    }
+}
+
+/// 追蹤誤差的持續與遲滯判定<br>
+/// Persistence and hysteresis of the tracking error flag
+#[derive(Debug, Default)]
+struct TrackingMonitor {
+    exceeded_since: Option<Instant>,
+    flagged: bool,
+}
+impl TrackingMonitor {
+    /// 超出容許值持續 `TRACKING_HOLD_MS` 才標記；標記後需回到容許值的 `TRACKING_RECOVER_RATIO` 內或下達新指令才恢復<br>
+    /// Flags once the tolerance is exceeded for `TRACKING_HOLD_MS`; once flagged,
+    /// recovers within `TRACKING_RECOVER_RATIO` of the tolerance or on a new command
+    fn update(&mut self, tracking: &MotorTracking, tolerance: f32, now: Instant) -> bool {
+        if self.flagged {
+            let echo_ok = tracking.setpoint_echo_ok != Some(false) && tracking.direction_echo_ok != Some(false);
+            let error = tracking.tracking_error.map_or(0.0, f32::abs);
+            if tracking.settling || (echo_ok && error <= tolerance * TRACKING_RECOVER_RATIO) {
+                self.flagged = false;
+                self.exceeded_since = None;
+            }
+        } else if tracking.exceeded {
+            let since = *self.exceeded_since.get_or_insert(now);
+            self.flagged = now.saturating_duration_since(since) >= Duration::from_millis(TRACKING_HOLD_MS);
+        } else {
+            self.exceeded_since = None;
+        }
+        self.flagged
+    }
+}
+
+/// 單一馬達的追蹤結果<br>
+/// Tracking result of one motor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MotorTracking {
+    pub motor: MotorSide,
+    pub speed_command: Option<u8>,
+    pub speed_setpoint: Option<u8>,
+    /// 換算為回報速度單位的設定值 / setpoint in the unit of the reported speed
+    pub speed_target: Option<f32>,
+    pub speed_present: Option<f32>,
+    pub direction_command: Option<u8>,
+    pub rotate_direction: Option<u8>,
+    pub setpoint_echo_ok: Option<bool>,
+    pub direction_echo_ok: Option<bool>,
+    pub tracking_error: Option<f32>,
+    /// 仍在下達指令後的穩定時間內 / still settling after a command
+    pub settling: bool,
+    /// 目前超出容許值 / currently outside the tolerance
+    pub exceeded: bool,
+    /// 持續超出後標記，含遲滯 / flagged after persisting, with hysteresis
+    pub flagged: bool,
 }
 
 pub struct DataStore {
+    max_length: usize,
     motor_left: MotorDataStore,
     motor_right: MotorDataStore,
     vehicel_pos: Vec<u16>,
+    updates: broadcast::Sender<DataUpdate>,
 }
+/// 資料通道，序列化為 `{ "motor_left": "speed_present" }` 或 `"vehicel_pos"` <br>
+/// Data channel, serialized as `{ "motor_left": "speed_present" }` or `"vehicel_pos"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DataType {
     MotorLeft(MotorDataType),
     MotorRight(MotorDataType),
     VehicelPos,
 }
+/// 馬達左右側 <br>
+/// Motor side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotorSide {
+    Left,
+    Right,
+}
+impl MotorSide {
+    /// 轉為對應的 DataType <br>
+    /// Converts into the matching DataType
+    pub fn data_type(self, kind: MotorDataType) -> DataType {
+        match self {
+            MotorSide::Left => DataType::MotorLeft(kind),
+            MotorSide::Right => DataType::MotorRight(kind),
+        }
+    }
+}
+
+/// 單筆資料更新通知 <br>
+/// Notification of one data update
+#[derive(Debug, Clone, Serialize)]
+pub struct DataUpdate {
+    pub channel: DataType,
+    pub value: f64,
+    pub ts_ms: i64,
+}
+
+impl DataType {
+    /// 所有資料通道 <br>
+    /// All data channels
+    pub fn all() -> Vec<DataType> {
+        [MotorSide::Left, MotorSide::Right].into_iter()
+            .flat_map(|side| MotorDataType::ALL.map(|kind| side.data_type(kind)))
+            .chain([DataType::VehicelPos])
+            .collect()
+    }
+
+    /// 以 sep 連接的通道名稱，例：`motor_left/speed_present`、`vehicel_pos` <br>
+    /// Channel name joined by sep, e.g. `motor_left/speed_present`, `vehicel_pos`
+    pub fn path(self, sep: &str) -> String {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => name,
+            Ok(serde_json::Value::Object(map)) => map.into_iter()
+                .map(|(side, kind)| format!("{}{}{}", side, sep, kind.as_str().unwrap_or_default()))
+                .collect(),
+            _ => format!("{:?}", self),
+        }
+    }
+}
+
 impl DataStore {
     pub fn new(max_length: usize) -> Self {
         Self {
+            max_length,
             motor_left:  MotorDataStore::new(max_length),
             motor_right: MotorDataStore::new(max_length),
             vehicel_pos: Vec::new(),
+            updates:     broadcast::channel(UPDATE_CHANNEL_SIZE).0,
         }
     }
 
-    pub fn push(&mut self, dt: DataType, value: impl Into<u64>) {
+    /// 訂閱資料更新通知 <br>
+    /// Subscribes to data update notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<DataUpdate> {
+        self.updates.subscribe()
+    }
+
+    pub fn push(&mut self, dt: DataType, value: impl Into<f64>) {
+        let value = value.into();
+        // 沒有訂閱者時 send 會失敗，可忽略
+        // send fails when there is no subscriber, which is fine
+        let _ = self.updates.send(DataUpdate {
+            channel: dt,
+            value,
+            ts_ms: chrono::Utc::now().timestamp_millis(),
+        });
         match dt {
             DataType::MotorLeft(kind) => {
                 self.motor_left.push(kind, value);
@@ -105,7 +359,9 @@ impl DataStore {
                 self.motor_right.push(kind, value);
             }
             DataType::VehicelPos => {
-                let v = value.into() as u16;
+                let v = value as u16;
+                let overflow = self.vehicel_pos.len().saturating_sub(self.max_length);
+                self.vehicel_pos.drain(0..overflow);
                 self.vehicel_pos.push(v);
             }
         }
@@ -129,4 +385,98 @@ impl DataStore {
             DataType::VehicelPos => self.vehicel_pos.clear(),
         }
     }
-}
\ No newline at end of file
+
+    /// 取得指定馬達的追蹤結果<br>
+    /// Returns the tracking result of the given motor
+    pub fn tracking(&self, side: MotorSide, tolerance: f32, now: Instant) -> MotorTracking {
+        match side {
+            MotorSide::Left => self.motor_left.tracking(side, tolerance, now),
+            MotorSide::Right => self.motor_right.tracking(side, tolerance, now),
+        }
+    }
+
+    /// 更新指定馬達的追蹤標記，標記狀態改變時回傳結果<br>
+    /// Updates the tracking flag of the given motor, returns the result when the flag state changes
+    pub fn tracking_update(&mut self, side: MotorSide, tolerance: f32, now: Instant) -> Option<MotorTracking> {
+        match side {
+            MotorSide::Left => self.motor_left.tracking_update(side, tolerance, now),
+            MotorSide::Right => self.motor_right.tracking_update(side, tolerance, now),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 5.0;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    /// 下達設定值並回報相同的回應與指定的目前速度 <br>
+    /// Commands a setpoint, echoes it back and reports the given present speed
+    fn store(setpoint: u8, present: f32) -> (MotorDataStore, Instant) {
+        let mut store = MotorDataStore::new(100);
+        store.push(MotorDataType::SpeedCommand, setpoint);
+        let commanded = store.command_at.unwrap();
+        store.push(MotorDataType::SpeedSetpoint, setpoint);
+        store.push(MotorDataType::SpeedPresent, present);
+        (store, commanded)
+    }
+
+    #[test]
+    fn new_command_is_settling() {
+        let (store, commanded) = store(50, 0.0);
+        let result = store.tracking(MotorSide::Left, TOLERANCE, commanded + ms(100));
+        assert!(result.settling);
+        assert!(!result.exceeded);
+        assert_eq!(result.tracking_error, Some(-50.0));
+    }
+
+    #[test]
+    fn flag_after_hold_only() {
+        let (mut store, commanded) = store(50, 20.0);
+        let after_settle = commanded + ms(TRACKING_SETTLE_MS);
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, commanded + ms(200)).is_none());
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, after_settle).is_none());
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, after_settle + ms(TRACKING_HOLD_MS - 100)).is_none());
+        let flagged = store.tracking_update(MotorSide::Left, TOLERANCE, after_settle + ms(TRACKING_HOLD_MS)).unwrap();
+        assert!(flagged.flagged && flagged.exceeded);
+    }
+
+    #[test]
+    fn recovery_with_hysteresis() {
+        let (mut store, commanded) = store(50, 20.0);
+        let mut now = commanded + ms(TRACKING_SETTLE_MS);
+        store.tracking_update(MotorSide::Left, TOLERANCE, now);
+        now += ms(TRACKING_HOLD_MS);
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, now).is_some_and(|r| r.flagged));
+        // 回到容許值內但未低於恢復門檻 / back within tolerance but not below the recovery threshold
+        store.push(MotorDataType::SpeedPresent, 46.0);
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, now + ms(100)).is_none());
+        store.push(MotorDataType::SpeedPresent, 48.0);
+        let recovered = store.tracking_update(MotorSide::Left, TOLERANCE, now + ms(200)).unwrap();
+        assert!(!recovered.flagged);
+    }
+
+    #[test]
+    fn brief_error_not_flagged() {
+        let (mut store, commanded) = store(50, 20.0);
+        let now = commanded + ms(TRACKING_SETTLE_MS);
+        store.tracking_update(MotorSide::Left, TOLERANCE, now);
+        store.push(MotorDataType::SpeedPresent, 50.0);
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, now + ms(100)).is_none());
+        store.push(MotorDataType::SpeedPresent, 20.0);
+        assert!(store.tracking_update(MotorSide::Left, TOLERANCE, now + ms(TRACKING_HOLD_MS)).is_none());
+    }
+
+    #[test]
+    fn setpoint_in_reported_speed_unit() {
+        let (store, commanded) = store(40, 40.0 * mcu_const::SPEED_PER_SETPOINT);
+        let result = store.tracking(MotorSide::Left, TOLERANCE, commanded + ms(TRACKING_SETTLE_MS));
+        assert_eq!(result.speed_target, Some(40.0 * mcu_const::SPEED_PER_SETPOINT));
+        assert_eq!(result.tracking_error, Some(0.0));
+        assert!(!result.exceeded);
+    }
+}
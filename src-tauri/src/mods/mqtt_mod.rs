@@ -0,0 +1,530 @@
+use std::{future::Future, sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc}, time::Duration};
+use log::{debug, error, info, warn};
+use rumqttc::{AsyncClient, ClientError, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle, time::{sleep, timeout}};
+use crate::{mods::{heartbeat_mod::{LinkReport, LinkStatus}, mcu_control_mod::{self, McuCommand}, mcu_store_mod::DataUpdate}, GlobalState};
+
+/// 請求佇列長度，遙測於佇列滿時丟棄 <br>
+/// Request queue length, telemetry is dropped when the queue is full
+const REQUEST_QUEUE_SIZE: usize = 256;
+const KEEP_ALIVE_SECS: u64 = 10;
+const RECONNECT_DELAY_MS: u64 = 1000;
+/// 停止時等待事件迴圈送出離線與中斷封包的時間（ms） <br>
+/// Time in ms given to the event loop to flush the offline and disconnect packets on stop
+const STOP_FLUSH_MS: u64 = 200;
+
+/// MQTT 設定，未填欄位使用預設值 <br>
+/// MQTT configuration, missing fields use the defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    /// 遙測發布於 `{telemetry_topic}/motor_left/speed_present` 等子主題 <br>
+    /// Telemetry is published to subtopics such as `{telemetry_topic}/motor_left/speed_present`
+    pub telemetry_topic: String,
+    /// 鏈路狀態（保留訊息），客戶端上下線發布於 `{status_topic}/client` <br>
+    /// Link status (retained), client online / offline is published to `{status_topic}/client`
+    pub status_topic: String,
+    /// 指令主題，執行結果發布於 `{command_topic}/result` <br>
+    /// Command topic, results are published to `{command_topic}/result`
+    pub command_topic: String,
+}
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 1883,
+            client_id: "tauri-app".into(),
+            username: None,
+            password: None,
+            telemetry_topic: "tauri-app/telemetry".into(),
+            status_topic: "tauri-app/link".into(),
+            command_topic: "tauri-app/command".into(),
+        }
+    }
+}
+impl MqttConfig {
+    fn client_topic(&self) -> String {
+        format!("{}/client", self.status_topic)
+    }
+
+    fn result_topic(&self) -> String {
+        format!("{}/result", self.command_topic)
+    }
+
+    /// 檢查主機與主題，主題不可為空或含萬用字元 <br>
+    /// Checks the host and topics, topics must not be empty or contain wildcards
+    fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("MQTT host is empty".into());
+        }
+        for topic in [&self.telemetry_topic, &self.status_topic, &self.command_topic] {
+            if topic.is_empty() || topic.contains(['+', '#']) {
+                return Err(format!("Invalid MQTT topic: {:?}", topic));
+            }
+        }
+        Ok(())
+    }
+
+    /// 連線選項，遺囑為 `{status_topic}/client` 的保留離線訊息 <br>
+    /// Connection options, the last will is a retained offline message on `{status_topic}/client`
+    fn options(&self) -> MqttOptions {
+        let mut options = MqttOptions::new(&self.client_id, self.host.trim(), self.port);
+        options.set_keep_alive(Duration::from_secs(KEEP_ALIVE_SECS));
+        options.set_last_will(LastWill::new(self.client_topic(), "offline", QoS::AtLeastOnce, true));
+        if let Some(username) = self.username.as_ref().filter(|u| !u.is_empty()) {
+            options.set_credentials(username, self.password.clone().unwrap_or_default());
+        }
+        options
+    }
+}
+
+/// MQTT 狀態 <br>
+/// MQTT status
+#[derive(Debug, Clone, Serialize)]
+pub struct MqttStatus {
+    pub running: bool,
+    pub connected: bool,
+    pub config: MqttConfig,
+    pub published: u64,
+    pub dropped: u64,
+    pub commands: u64,
+}
+
+/// 任務間共用的計數 <br>
+/// Counters shared between tasks
+#[derive(Default)]
+struct MqttCounters {
+    connected: AtomicBool,
+    published: AtomicU64,
+    dropped: AtomicU64,
+    commands: AtomicU64,
+}
+
+/// 指令執行結果 <br>
+/// Command execution result
+#[derive(Debug, Clone, Serialize)]
+struct CommandResult {
+    ok: bool,
+    error: Option<String>,
+}
+impl From<Result<(), String>> for CommandResult {
+    fn from(result: Result<(), String>) -> Self {
+        Self { ok: result.is_ok(), error: result.err() }
+    }
+}
+
+/// MQTT 客戶端：發布遙測與鏈路狀態，並訂閱指令主題 <br>
+/// MQTT client: publishes telemetry and link status, subscribes to the command topic
+pub struct MqttManager {
+    config: MqttConfig,
+    client: Option<AsyncClient>,
+    tasks: Vec<JoinHandle<()>>,
+    counters: Arc<MqttCounters>,
+    link_status: Option<LinkStatus>,
+}
+impl MqttManager {
+    pub fn new() -> Self {
+        Self {
+            config: MqttConfig::default(),
+            client: None,
+            tasks: Vec::new(),
+            counters: Arc::new(MqttCounters::default()),
+            link_status: None,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// 建立客戶端並啟動事件迴圈與遙測任務，連線失敗時自動重連 <br>
+    /// Creates the client and starts the event loop and telemetry tasks, reconnects automatically
+    pub async fn start(&mut self, app: AppHandle, config: MqttConfig) -> Result<(), String> {
+        if self.is_started() {
+            return Err(format!("MQTT already started ({}:{})", self.config.host, self.config.port));
+        }
+        config.validate()?;
+        let (client, eventloop) = AsyncClient::new(config.options(), REQUEST_QUEUE_SIZE);
+        let counters = Arc::new(MqttCounters::default());
+        let updates = {
+            let global_state = app.state::<GlobalState>();
+            let store_datas = global_state.store_datas.lock().await;
+            store_datas.subscribe()
+        };
+        self.tasks = vec![
+            tokio::spawn(eventloop_run(app.clone(), eventloop, client.clone(), config.clone(), counters.clone())),
+            tokio::spawn(telemetry_run(updates, client.clone(), config.telemetry_topic.clone(), counters.clone())),
+        ];
+        self.config = config;
+        self.client = Some(client);
+        self.counters = counters;
+        self.link_status = None;
+        Ok(())
+    }
+
+    /// 發布離線訊息後中斷連線，未啟動時不做任何事；請求佇列已滿或未連線時不等待 <br>
+    /// Publishes the offline message and disconnects, does nothing when not started; never waits on a full request queue or a missing connection
+    pub async fn stop(&mut self) {
+        if let Some(client) = self.client.take() {
+            if self.counters.connected.load(Ordering::Relaxed) {
+                offline(&client, &self.config);
+                // 給事件迴圈時間送出離線與中斷封包
+                // give the event loop time to flush the offline and disconnect packets
+                sleep(Duration::from_millis(STOP_FLUSH_MS)).await;
+            }
+        }
+        for task in self.tasks.drain(..) {
+            task.abort();
+            if timeout(Duration::from_millis(STOP_FLUSH_MS), task).await.is_err() {
+                warn!("MQTT task did not stop in {} ms", STOP_FLUSH_MS);
+            }
+        }
+        self.counters.connected.store(false, Ordering::Relaxed);
+    }
+
+    /// 鏈路狀態改變時發布（保留訊息）；未連線時略過，重新連線後會發布目前狀態 <br>
+    /// Publishes the link status when it changes (retained); skipped while disconnected, the current status is published on reconnect
+    pub fn link_publish(&mut self, report: &LinkReport) {
+        let Some(client) = &self.client else { return; };
+        if !self.counters.connected.load(Ordering::Relaxed) || self.link_status == Some(report.status) {
+            return;
+        }
+        let Ok(payload) = serde_json::to_vec(report) else { return; };
+        match client.try_publish(&self.config.status_topic, QoS::AtLeastOnce, true, payload) {
+            Ok(()) => self.link_status = Some(report.status),
+            Err(e) => warn!("MQTT link status publish failed: {}", e),
+        }
+    }
+
+    pub fn status(&self) -> MqttStatus {
+        MqttStatus {
+            running: self.is_started(),
+            connected: self.counters.connected.load(Ordering::Relaxed),
+            config: self.config.clone(),
+            published: self.counters.published.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            commands: self.counters.commands.load(Ordering::Relaxed),
+        }
+    }
+}
+impl Default for MqttManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 驅動 MQTT 事件迴圈：連線後訂閱指令主題，收到指令時執行並回報結果 <br>
+/// Drives the MQTT event loop: subscribes to the command topic on connect, executes received commands and reports the result
+async fn eventloop_run(
+    app: AppHandle,
+    mut eventloop: EventLoop,
+    client: AsyncClient,
+    config: MqttConfig,
+    counters: Arc<MqttCounters>,
+) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                info!("MQTT connected to {}:{}", config.host, config.port);
+                counters.connected.store(true, Ordering::Relaxed);
+                if let Err(e) = connected(&app, &client, &config).await {
+                    error!("MQTT subscribe failed: {}", e);
+                }
+                status_emit(&app).await;
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == config.command_topic => {
+                counters.commands.fetch_add(1, Ordering::Relaxed);
+                command_handle(&client, &config, &publish.payload, |command| {
+                    mcu_control_mod::command_execute(app.clone(), command)
+                }).await;
+            }
+            Ok(event) => debug!("MQTT event: {:?}", event),
+            Err(e) => {
+                if counters.connected.swap(false, Ordering::Relaxed) {
+                    status_emit(&app).await;
+                }
+                warn!("MQTT connection error: {}", e);
+                sleep(Duration::from_millis(RECONNECT_DELAY_MS)).await;
+            }
+        }
+    }
+}
+
+/// 解析指令主題的訊息 <br>
+/// Parses a message of the command topic
+fn command_parse(payload: &[u8]) -> Result<McuCommand, String> {
+    serde_json::from_slice(payload).map_err(|e| format!("Invalid command: {}", e))
+}
+
+/// 解析並執行指令，結果發布於 `{command_topic}/result` <br>
+/// Parses and executes a command, the result is published to `{command_topic}/result`
+async fn command_handle<F, Fut>(client: &AsyncClient, config: &MqttConfig, payload: &[u8], execute: F)
+where
+    F: FnOnce(McuCommand) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let result = match command_parse(payload) {
+        Ok(command) => execute(command).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &result {
+        warn!("MQTT command failed: {}", e);
+    }
+    if let Ok(payload) = serde_json::to_vec(&CommandResult::from(result)) {
+        let _ = client.try_publish(config.result_topic(), QoS::AtLeastOnce, false, payload);
+    }
+}
+
+/// 訂閱指令主題並發布保留的上線訊息 <br>
+/// Subscribes to the command topic and publishes the retained online message
+fn announce(client: &AsyncClient, config: &MqttConfig) -> Result<(), ClientError> {
+    client.try_subscribe(&config.command_topic, QoS::AtLeastOnce)?;
+    client.try_publish(config.client_topic(), QoS::AtLeastOnce, true, "online")
+}
+
+/// 發布保留的離線訊息並中斷連線，不等待請求佇列 <br>
+/// Publishes the retained offline message and disconnects without waiting on the request queue
+fn offline(client: &AsyncClient, config: &MqttConfig) {
+    let _ = client.try_publish(config.client_topic(), QoS::AtLeastOnce, true, "offline");
+    let _ = client.try_disconnect();
+}
+
+/// 連線後訂閱指令主題並發布上線與目前鏈路狀態；在事件迴圈內執行，故不可等待請求佇列 <br>
+/// After connecting, subscribes to the command topic and publishes online and the current link status;
+/// runs inside the event loop, so it must not wait on the request queue
+async fn connected(app: &AppHandle, client: &AsyncClient, config: &MqttConfig) -> Result<(), ClientError> {
+    announce(client, config)?;
+    let global_state = app.state::<GlobalState>();
+    let report = global_state.link_monitor.lock().await.report();
+    if let Ok(payload) = serde_json::to_vec(&report) {
+        client.try_publish(&config.status_topic, QoS::AtLeastOnce, true, payload)?;
+    }
+    global_state.mqtt_manager.lock().await.link_status = Some(report.status);
+    Ok(())
+}
+
+/// 將資料更新發布到各通道子主題 <br>
+/// Publishes data updates to the subtopic of each channel
+async fn telemetry_run(
+    mut updates: tokio::sync::broadcast::Receiver<DataUpdate>,
+    client: AsyncClient,
+    telemetry_topic: String,
+    counters: Arc<MqttCounters>,
+) {
+    loop {
+        let update = match updates.recv().await {
+            Ok(update) => update,
+            Err(RecvError::Lagged(skipped)) => {
+                counters.dropped.fetch_add(skipped, Ordering::Relaxed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if !counters.connected.load(Ordering::Relaxed) {
+            continue;
+        }
+        let (topic, payload) = telemetry_message(&telemetry_topic, &update);
+        match client.try_publish(topic, QoS::AtMostOnce, false, payload) {
+            Ok(_) => counters.published.fetch_add(1, Ordering::Relaxed),
+            Err(_) => counters.dropped.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+}
+
+/// 遙測主題與內容：`{telemetry_topic}/{通道路徑}`，`{ "value", "ts_ms" }` <br>
+/// Telemetry topic and payload: `{telemetry_topic}/{channel path}`, `{ "value", "ts_ms" }`
+fn telemetry_message(telemetry_topic: &str, update: &DataUpdate) -> (String, String) {
+    let topic = format!("{}/{}", telemetry_topic, update.channel.path("/"));
+    let payload = serde_json::json!({ "value": update.value, "ts_ms": update.ts_ms }).to_string();
+    (topic, payload)
+}
+
+async fn status_emit(app: &AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    let status = global_state.mqtt_manager.lock().await.status();
+    if let Err(e) = app.emit("mqtt-status", &status) {
+        error!("Emit mqtt-status failed: {}", e);
+    }
+}
+
+/// Tauri 指令：以設定啟動 MQTT 客戶端 <br>
+/// Tauri command: start the MQTT client with the configuration
+#[tauri::command]
+pub async fn cmd_mqtt_start(app: AppHandle, config: MqttConfig) -> Result<MqttStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let mut mqtt = global_state.mqtt_manager.lock().await;
+    mqtt.start(app.clone(), config).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    info!("MQTT client started");
+    Ok(mqtt.status())
+}
+
+/// Tauri 指令：停止 MQTT 客戶端 <br>
+/// Tauri command: stop the MQTT client
+#[tauri::command]
+pub async fn cmd_mqtt_stop(app: AppHandle) -> Result<MqttStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let mut mqtt = global_state.mqtt_manager.lock().await;
+    mqtt.stop().await;
+    info!("MQTT client stopped");
+    Ok(mqtt.status())
+}
+
+/// Tauri 指令：取得 MQTT 狀態 <br>
+/// Tauri command: get the MQTT status
+#[tauri::command]
+pub async fn cmd_mqtt_status(app: AppHandle) -> Result<MqttStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let mqtt = global_state.mqtt_manager.lock().await;
+    Ok(mqtt.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use rumqttc::Publish;
+    use crate::mods::mcu_store_mod::{DataType, MotorDataType, MotorSide};
+    use super::*;
+
+    #[test]
+    fn validate_rejects_wildcard_and_empty_topics() {
+        assert!(MqttConfig::default().validate().is_ok());
+        let invalid = [
+            MqttConfig { telemetry_topic: "robot/+/telemetry".into(), ..Default::default() },
+            MqttConfig { status_topic: "robot/#".into(), ..Default::default() },
+            MqttConfig { command_topic: String::new(), ..Default::default() },
+            MqttConfig { host: "  ".into(), ..Default::default() },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn command_payload_parse() {
+        let command = command_parse(br#"{ "cmd": "motor_set_speed", "motor": "left", "setpoint": 40 }"#).unwrap();
+        assert!(matches!(command, McuCommand::MotorSetSpeed { motor: MotorSide::Left, setpoint: 40 }));
+        assert!(matches!(command_parse(br#"{ "cmd": "emergency_stop" }"#), Ok(McuCommand::EmergencyStop)));
+        assert!(command_parse(br#"{ "cmd": "reboot" }"#).unwrap_err().starts_with("Invalid command"));
+        assert!(command_parse(br#"{ "cmd": "motor_set_speed", "motor": "left", "setpoint": 300 }"#).is_err());
+        assert!(command_parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn command_result_payload() {
+        let config = MqttConfig::default();
+        assert_eq!(config.result_topic(), "tauri-app/command/result");
+        assert_eq!(serde_json::to_value(CommandResult::from(Ok(()))).unwrap(), serde_json::json!({ "ok": true, "error": null }));
+        assert_eq!(
+            serde_json::to_value(CommandResult::from(Err("Link not ready".to_string()))).unwrap(),
+            serde_json::json!({ "ok": false, "error": "Link not ready" })
+        );
+    }
+
+    #[test]
+    fn telemetry_subtopics() {
+        let update = |channel| DataUpdate { channel, value: 1.5, ts_ms: 42 };
+        let (topic, payload) = telemetry_message("robot/telemetry", &update(MotorSide::Right.data_type(MotorDataType::SpeedPresent)));
+        assert_eq!(topic, "robot/telemetry/motor_right/speed_present");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&payload).unwrap(), serde_json::json!({ "value": 1.5, "ts_ms": 42 }));
+        let (topic, _) = telemetry_message("robot/telemetry", &update(DataType::VehicelPos));
+        assert_eq!(topic, "robot/telemetry/vehicel_pos");
+    }
+
+    /// 等待指定主題的下一則訊息 <br>
+    /// Waits for the next message on the given topic
+    async fn next_publish(eventloop: &mut EventLoop, topic: &str) -> Publish {
+        timeout(Duration::from_secs(5), async {
+            loop {
+                if let Event::Incoming(Packet::Publish(publish)) = eventloop.poll().await.expect("MQTT broker connection") {
+                    if publish.topic == topic {
+                        return publish;
+                    }
+                }
+            }
+        }).await.unwrap_or_else(|_| panic!("No message on {}", topic))
+    }
+
+    /// 以新客戶端訂閱並取得保留訊息 <br>
+    /// Subscribes with a fresh client and returns the retained message
+    async fn retained(client_id: &str, topic: &str) -> Publish {
+        let (client, mut eventloop) = AsyncClient::new(MqttOptions::new(client_id, "127.0.0.1", 1883), 16);
+        client.subscribe(topic, QoS::AtLeastOnce).await.unwrap();
+        let publish = next_publish(&mut eventloop, topic).await;
+        let _ = client.try_disconnect();
+        publish
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an MQTT broker such as mosquitto at 127.0.0.1:1883"]
+    async fn broker_command_round_trip() {
+        let id = format!("tauri-app-test-{}", std::process::id());
+        let config = MqttConfig {
+            client_id: id.clone(),
+            telemetry_topic: format!("{}/telemetry", id),
+            status_topic: format!("{}/link", id),
+            command_topic: format!("{}/command", id),
+            ..Default::default()
+        };
+        let (observer, mut observer_loop) = AsyncClient::new(MqttOptions::new(format!("{}-observer", id), "127.0.0.1", 1883), 16);
+        observer.subscribe(config.result_topic(), QoS::AtLeastOnce).await.unwrap();
+        observer.subscribe(config.client_topic(), QoS::AtLeastOnce).await.unwrap();
+
+        // 模擬本程式的事件迴圈，指令只接受緊急停止
+        // stands in for the app event loop, only emergency stop is accepted
+        let (client, mut eventloop) = AsyncClient::new(config.options(), 16);
+        let device = {
+            let (client, config) = (client.clone(), config.clone());
+            tokio::spawn(async move {
+                while let Ok(event) = eventloop.poll().await {
+                    match event {
+                        Event::Incoming(Packet::ConnAck(_)) => announce(&client, &config).unwrap(),
+                        Event::Incoming(Packet::Publish(publish)) if publish.topic == config.command_topic => {
+                            command_handle(&client, &config, &publish.payload, |command| async move {
+                                match command {
+                                    McuCommand::EmergencyStop => Ok(()),
+                                    other => Err(format!("Unexpected command: {:?}", other)),
+                                }
+                            }).await;
+                        }
+                        _ => {}
+                    }
+                }
+            })
+        };
+
+        assert_eq!(&next_publish(&mut observer_loop, &config.client_topic()).await.payload[..], b"online");
+        let online = retained(&format!("{}-retained-online", id), &config.client_topic()).await;
+        assert!(online.retain);
+        assert_eq!(&online.payload[..], b"online");
+
+        observer.publish(&config.command_topic, QoS::AtLeastOnce, false, r#"{ "cmd": "emergency_stop" }"#).await.unwrap();
+        let result = next_publish(&mut observer_loop, &config.result_topic()).await;
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&result.payload).unwrap(), serde_json::json!({ "ok": true, "error": null }));
+
+        observer.publish(&config.command_topic, QoS::AtLeastOnce, false, r#"{ "cmd": "reboot" }"#).await.unwrap();
+        let result = next_publish(&mut observer_loop, &config.result_topic()).await;
+        let result: serde_json::Value = serde_json::from_slice(&result.payload).unwrap();
+        assert_eq!(result["ok"], false);
+
+        offline(&client, &config);
+        assert_eq!(&next_publish(&mut observer_loop, &config.client_topic()).await.payload[..], b"offline");
+        let offline = retained(&format!("{}-retained-offline", id), &config.client_topic()).await;
+        assert!(offline.retain);
+        assert_eq!(&offline.payload[..], b"offline");
+
+        // 清除保留訊息 / clear the retained message
+        observer.publish(config.client_topic(), QoS::AtLeastOnce, true, Vec::new()).await.unwrap();
+        let _ = timeout(Duration::from_millis(500), observer_loop.poll()).await;
+        device.abort();
+    }
+}
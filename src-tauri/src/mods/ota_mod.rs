@@ -0,0 +1,739 @@
+use std::{error::Error, fs, net::{IpAddr, Ipv4Addr, SocketAddr}, path::PathBuf, sync::Arc, time::Duration};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::timeout,
+};
+use crate::{mods::{mcu_const, mcu_control_mod::McuLink, wifi_mod::WifiProtocol, wifi_packet_mod::{self, WifiFrame, WifiPacket}, wifi_tcp_mod::WifiTcpConns}, GlobalState};
+
+/// 預設區塊大小 <br>
+/// Default chunk size
+pub const OTA_CHUNK_SIZE_DEFAULT: u16 = 512;
+/// 區塊大小上限，區塊指令需放入單一訊框 <br>
+/// Maximum chunk size, a chunk command must fit in one frame
+pub const OTA_CHUNK_SIZE_MAX: usize = wifi_packet_mod::WIFI_FRAME_PAYLOAD_MAX_SIZE - mcu_const::OTA_CHUNK_HEADER_SIZE as usize;
+
+const OTA_ACK_TIMEOUT_MS: u64 = 2000;
+/// 裝置計算整體雜湊需較長時間 <br>
+/// The device needs longer to hash the whole image
+const OTA_VERIFY_TIMEOUT_MS: u64 = 10000;
+/// 單一指令最多重送次數 <br>
+/// Maximum resends of one command
+const OTA_RETRY_MAX: u32 = 3;
+/// 回覆佇列長度 <br>
+/// Depth of the reply queue
+const OTA_REPLY_QUEUE: usize = 16;
+
+type OtaResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// OTA 訊框資料，格式見 `mcu_const::OTA_CMD_BEGIN` <br>
+/// OTA frame payload, see `mcu_const::OTA_CMD_BEGIN` for the layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OtaMessage {
+    Begin { size: u32, chunk_size: u16, sha256: [u8; 32] },
+    Chunk { index: u32, crc: u32, data: Vec<u8> },
+    End,
+    Ack { cmd: u8, index: u32, status: u8 },
+}
+impl OtaMessage {
+    /// 序列化為訊框資料 <br>
+    /// Serializes into a frame payload
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        match self {
+            OtaMessage::Begin { size, chunk_size, sha256 } => {
+                buffer.push(mcu_const::OTA_CMD_BEGIN);
+                buffer.extend(size.to_be_bytes());
+                buffer.extend(chunk_size.to_be_bytes());
+                buffer.extend(sha256);
+            }
+            OtaMessage::Chunk { index, crc, data } => {
+                buffer.push(mcu_const::OTA_CMD_CHUNK);
+                buffer.extend(index.to_be_bytes());
+                buffer.extend(crc.to_be_bytes());
+                buffer.extend(data);
+            }
+            OtaMessage::End => buffer.push(mcu_const::OTA_CMD_END),
+            OtaMessage::Ack { cmd, index, status } => {
+                buffer.push(mcu_const::OTA_CMD_ACK);
+                buffer.push(*cmd);
+                buffer.extend(index.to_be_bytes());
+                buffer.push(*status);
+            }
+        }
+        buffer
+    }
+
+    /// 從訊框資料解析 <br>
+    /// Parses a frame payload
+    pub fn decode(data: &[u8]) -> OtaResult<Self> {
+        let too_short = || format!("OTA message too short: {:?}", data);
+        let (&cmd, rest) = data.split_first().ok_or_else(too_short)?;
+        let message = match cmd {
+            mcu_const::OTA_CMD_BEGIN => {
+                if rest.len() != 38 {
+                    return Err(too_short().into());
+                }
+                OtaMessage::Begin {
+                    size: u32::from_be_bytes(rest[0..4].try_into()?),
+                    chunk_size: u16::from_be_bytes(rest[4..6].try_into()?),
+                    sha256: rest[6..38].try_into()?,
+                }
+            }
+            mcu_const::OTA_CMD_CHUNK => {
+                if rest.len() < 8 {
+                    return Err(too_short().into());
+                }
+                OtaMessage::Chunk {
+                    index: u32::from_be_bytes(rest[0..4].try_into()?),
+                    crc: u32::from_be_bytes(rest[4..8].try_into()?),
+                    data: rest[8..].to_vec(),
+                }
+            }
+            mcu_const::OTA_CMD_END => OtaMessage::End,
+            mcu_const::OTA_CMD_ACK => {
+                if rest.len() != 6 {
+                    return Err(too_short().into());
+                }
+                OtaMessage::Ack {
+                    cmd: rest[0],
+                    index: u32::from_be_bytes(rest[1..5].try_into()?),
+                    status: rest[5],
+                }
+            }
+            other => return Err(format!("OTA command unknown: {:#04x}", other).into()),
+        };
+        Ok(message)
+    }
+}
+
+fn status_name(status: u8) -> &'static str {
+    match status {
+        mcu_const::OTA_STATUS_OK => "ok",
+        mcu_const::OTA_STATUS_CRC => "crc mismatch",
+        mcu_const::OTA_STATUS_INDEX => "unexpected chunk index",
+        mcu_const::OTA_STATUS_SIZE => "size invalid",
+        mcu_const::OTA_STATUS_HASH => "hash mismatch",
+        mcu_const::OTA_STATUS_BUSY => "device busy",
+        _ => "unknown status",
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 韌體更新階段 <br>
+/// Firmware update stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum OtaState {
+    Idle,
+    Connecting,
+    Sending,
+    Verifying,
+    Done,
+    Failed { error: String },
+    Cancelled,
+}
+
+/// 韌體更新進度，以 `ota-progress` 事件送往前端 <br>
+/// Firmware update progress, sent to the frontend as `ota-progress` events
+#[derive(Debug, Clone, Serialize)]
+pub struct OtaProgress {
+    #[serde(flatten)]
+    pub state: OtaState,
+    pub target: Option<SocketAddr>,
+    pub total_bytes: u32,
+    pub sent_bytes: u32,
+    pub chunk: u32,
+    pub chunks: u32,
+    pub retries: u32,
+    pub sha256: String,
+}
+impl OtaProgress {
+    fn idle() -> Self {
+        Self {
+            state: OtaState::Idle,
+            target: None,
+            total_bytes: 0,
+            sent_bytes: 0,
+            chunk: 0,
+            chunks: 0,
+            retries: 0,
+            sha256: String::new(),
+        }
+    }
+}
+
+/// 韌體映像與分塊設定 <br>
+/// Firmware image and chunking
+struct OtaImage {
+    data: Vec<u8>,
+    chunk_size: usize,
+    sha256: [u8; 32],
+}
+impl OtaImage {
+    fn new(data: Vec<u8>, chunk_size: usize) -> OtaResult<Self> {
+        if data.is_empty() {
+            return Err("Firmware image is empty".into());
+        }
+        if u32::try_from(data.len()).is_err() {
+            return Err(format!("Firmware image too large: {} bytes", data.len()).into());
+        }
+        if chunk_size == 0 || chunk_size > OTA_CHUNK_SIZE_MAX {
+            return Err(format!("Chunk size must be 1..={}: {}", OTA_CHUNK_SIZE_MAX, chunk_size).into());
+        }
+        let sha256 = Sha256::digest(&data).into();
+        Ok(Self { data, chunk_size, sha256 })
+    }
+
+    fn chunks(&self) -> u32 {
+        self.data.len().div_ceil(self.chunk_size) as u32
+    }
+}
+
+/// OTA 訊框的收發通道 <br>
+/// Transport exchanging OTA frames
+trait OtaTransport {
+    async fn frame_send(&mut self, frame: WifiFrame) -> OtaResult<()>;
+    async fn frame_recv(&mut self) -> OtaResult<WifiFrame>;
+}
+
+/// 經由既有 WiFi TCP 連線收發，回覆由接收流程轉交 <br>
+/// Exchanges frames over the existing WiFi TCP session, replies are handed over by the receive flow
+struct OtaSession {
+    app: AppHandle,
+    conns: Arc<WifiTcpConns>,
+    target: SocketAddr,
+    replies: mpsc::Receiver<WifiFrame>,
+}
+impl OtaTransport for OtaSession {
+    async fn frame_send(&mut self, frame: WifiFrame) -> OtaResult<()> {
+        let packet = WifiPacket::from_frame(self.target, &frame)?;
+        self.conns.send(&self.app, packet).await
+    }
+
+    async fn frame_recv(&mut self) -> OtaResult<WifiFrame> {
+        self.replies.recv().await.ok_or_else(|| "OTA reply channel closed".into())
+    }
+}
+
+/// 進行中更新的回覆路由 <br>
+/// Reply route of the running update
+struct OtaRoute {
+    target: SocketAddr,
+    device_id: u16,
+    replies: mpsc::Sender<WifiFrame>,
+}
+impl OtaRoute {
+    /// 連入裝置的來源埠可能與目標不同，故只比對 IP 與裝置 ID <br>
+    /// An inbound device may use another source port than the target, so only the IP and device id are compared
+    fn accepts(&self, addr: SocketAddr, device_id: u16) -> bool {
+        addr.ip() == self.target.ip() && device_id == self.device_id && !self.replies.is_closed()
+    }
+}
+
+/// 單次 OTA 更新的指令收發 <br>
+/// Command exchange of one OTA update
+struct OtaLink<T> {
+    transport: T,
+    device_id: u16,
+    seq: u16,
+}
+impl<T: OtaTransport> OtaLink<T> {
+    async fn send(&mut self, message: &OtaMessage) -> OtaResult<()> {
+        self.seq = self.seq.wrapping_add(1);
+        let frame = WifiFrame::new(self.device_id, self.seq, mcu_const::WIFI_FRAME_TYPE_OTA, message.encode())?;
+        self.transport.frame_send(frame).await
+    }
+
+    /// 等待指定指令的回覆，略過其他種類訊框與過期回覆；逾時回傳 None <br>
+    /// Waits for the reply to a command, skipping other frame types and stale replies; None on timeout
+    async fn ack(&mut self, cmd: u8, index: u32, timeout_ms: u64) -> OtaResult<Option<u8>> {
+        let wait = async {
+            loop {
+                let frame = self.transport.frame_recv().await?;
+                if frame.frame_type() != mcu_const::WIFI_FRAME_TYPE_OTA {
+                    debug!("OTA skipped frame type {:#04x}", frame.frame_type());
+                    continue;
+                }
+                match OtaMessage::decode(frame.payload())? {
+                    OtaMessage::Ack { cmd: c, index: i, status } if c == cmd && i == index => {
+                        return Ok::<u8, Box<dyn Error + Send + Sync>>(status);
+                    }
+                    other => debug!("OTA skipped reply: {:?}", other),
+                }
+            }
+        };
+        match timeout(Duration::from_millis(timeout_ms), wait).await {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// 送出指令並等待成功回覆，逾時或 CRC 錯誤時重送 <br>
+    /// Sends a command and waits for a successful reply, resends on timeout or CRC error
+    async fn request(&mut self, message: &OtaMessage, index: u32, timeout_ms: u64, progress: &watch::Sender<OtaProgress>) -> OtaResult<()> {
+        let cmd = message.encode()[0];
+        let mut attempt = 0;
+        loop {
+            self.send(message).await?;
+            let error = match self.ack(cmd, index, timeout_ms).await? {
+                Some(mcu_const::OTA_STATUS_OK) => return Ok(()),
+                Some(status @ mcu_const::OTA_STATUS_CRC) => status_name(status),
+                Some(status) => return Err(format!("OTA rejected (cmd {:#04x}, index {}): {}", cmd, index, status_name(status)).into()),
+                None => "reply timed out",
+            };
+            attempt += 1;
+            if attempt > OTA_RETRY_MAX {
+                return Err(format!("OTA gave up after {} retries: {}", OTA_RETRY_MAX, error).into());
+            }
+            warn!("OTA resend (cmd {:#04x}, index {}, attempt {}): {}", cmd, index, attempt, error);
+            progress.send_modify(|p| p.retries += 1);
+        }
+    }
+}
+
+/// 將進度變化以 `ota-progress` 事件送往前端，直到更新結束 <br>
+/// Forwards progress changes to the frontend as `ota-progress` events until the update ends
+async fn progress_forward(app: AppHandle, mut progress: watch::Receiver<OtaProgress>) {
+    while progress.changed().await.is_ok() {
+        let current = progress.borrow_and_update().clone();
+        if let Err(e) = app.emit("ota-progress", &current) {
+            error!("Emit ota-progress failed: {}", e);
+        }
+        if matches!(current.state, OtaState::Done | OtaState::Failed { .. } | OtaState::Cancelled) {
+            break;
+        }
+    }
+}
+
+/// 完整更新流程：開始、逐塊傳送、結束並驗證雜湊 <br>
+/// Full update flow: begin, send every chunk, end and verify the hash
+async fn ota_run<T: OtaTransport>(transport: T, device_id: u16, image: &OtaImage, progress: &watch::Sender<OtaProgress>) -> OtaResult<()> {
+    let mut link = OtaLink { transport, device_id, seq: 0 };
+    let begin = OtaMessage::Begin {
+        size: image.data.len() as u32,
+        chunk_size: image.chunk_size as u16,
+        sha256: image.sha256,
+    };
+    link.request(&begin, 0, OTA_ACK_TIMEOUT_MS, progress).await?;
+    progress.send_modify(|p| p.state = OtaState::Sending);
+    for (index, data) in image.data.chunks(image.chunk_size).enumerate() {
+        let index = index as u32;
+        let chunk = OtaMessage::Chunk { index, crc: crc32fast::hash(data), data: data.to_vec() };
+        link.request(&chunk, index, OTA_ACK_TIMEOUT_MS, progress).await?;
+        progress.send_modify(|p| {
+            p.chunk = index + 1;
+            p.sent_bytes += data.len() as u32;
+        });
+    }
+    progress.send_modify(|p| p.state = OtaState::Verifying);
+    link.request(&OtaMessage::End, 0, OTA_VERIFY_TIMEOUT_MS, progress).await
+}
+
+/// 將 TCP 收到的 OTA 訊框轉交進行中的更新；不屬於該更新時回傳 false <br>
+/// Hands an OTA frame received over TCP to the running update; false when it does not belong to it
+pub async fn re_pkt_ota(app: &AppHandle, packet: &WifiPacket, frame: WifiFrame) -> bool {
+    let global_state = app.state::<GlobalState>();
+    let ota = global_state.ota_manager.lock().await;
+    let Some(route) = ota.route.as_ref().filter(|route| route.accepts(packet.addr(), frame.device_id())) else {
+        debug!("WiFi OTA frame from {} device {} outside an update ignored", packet.addr(), frame.device_id());
+        return false;
+    };
+    if let Err(e) = route.replies.try_send(frame) {
+        warn!("OTA reply from {} dropped: {}", packet.addr(), e);
+    }
+    true
+}
+
+/// 韌體更新管理，同時只允許一個更新 <br>
+/// Firmware update manager, one update at a time
+pub struct OtaManager {
+    progress: watch::Sender<OtaProgress>,
+    route: Option<OtaRoute>,
+    task: Option<JoinHandle<()>>,
+}
+impl OtaManager {
+    pub fn new() -> Self {
+        Self {
+            progress: watch::Sender::new(OtaProgress::idle()),
+            route: None,
+            task: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn progress(&self) -> OtaProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// 啟動背景更新任務，訊框經由既有 TCP 連線收發 <br>
+    /// Starts the background update task, frames go over the existing TCP session
+    fn start(&mut self, app: AppHandle, conns: Arc<WifiTcpConns>, target: SocketAddr, device_id: u16, image: OtaImage) -> Result<(), String> {
+        if self.is_running() {
+            return Err("OTA update already running".into());
+        }
+        self.progress.send_replace(OtaProgress {
+            state: OtaState::Connecting,
+            target: Some(target),
+            total_bytes: image.data.len() as u32,
+            chunks: image.chunks(),
+            sha256: hex(&image.sha256),
+            ..OtaProgress::idle()
+        });
+        let (replies_tx, replies_rx) = mpsc::channel(OTA_REPLY_QUEUE);
+        self.route = Some(OtaRoute { target, device_id, replies: replies_tx });
+        let session = OtaSession { app: app.clone(), conns, target, replies: replies_rx };
+        let progress = self.progress.clone();
+        let forward = tokio::spawn(progress_forward(app, progress.subscribe()));
+        self.task = Some(tokio::spawn(async move {
+            let state = match ota_run(session, device_id, &image, &progress).await {
+                Ok(_) => {
+                    info!("OTA update of {} done", target);
+                    OtaState::Done
+                }
+                Err(e) => {
+                    error!("OTA update of {} failed: {}", target, e);
+                    OtaState::Failed { error: e.to_string() }
+                }
+            };
+            progress.send_modify(|p| p.state = state);
+            let _ = forward.await;
+        }));
+        Ok(())
+    }
+
+    /// 中止進行中的更新 <br>
+    /// Aborts the running update
+    fn cancel(&mut self) -> bool {
+        self.route = None;
+        let Some(task) = self.task.take().filter(|task| !task.is_finished()) else {
+            return false;
+        };
+        task.abort();
+        self.progress.send_modify(|p| p.state = OtaState::Cancelled);
+        true
+    }
+}
+impl Default for OtaManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 模擬裝置的單次連線：回應 keepalive，接收映像並以 CRC 與 SHA-256 驗證，nak_every 時對每 n 個區塊的首次傳送回覆 CRC 錯誤 <br>
+/// One stand-in device connection: answers keepalives, receives the image and checks CRC and SHA-256, with nak_every the first send of every n-th chunk gets a CRC error
+async fn mock_session(stream: TcpStream, peer: SocketAddr, nak_every: Option<u32>) -> OtaResult<()> {
+    let mut stream = stream;
+    let mut begin: Option<(u32, [u8; 32])> = None;
+    let mut image = Vec::<u8>::new();
+    let mut next_index = 0u32;
+    let mut nak_sent = None::<u32>;
+    loop {
+        let data = WifiTcpConns::read_frame(&mut stream).await?;
+        if data.is_empty() {
+            WifiTcpConns::write_frame(&mut stream, &[]).await?;
+            continue;
+        }
+        let frame = WifiFrame::pack(&data)?;
+        if frame.frame_type() != mcu_const::WIFI_FRAME_TYPE_OTA {
+            continue;
+        }
+        let message = OtaMessage::decode(frame.payload())?;
+        let (cmd, index, status) = match message {
+            OtaMessage::Begin { size, sha256, .. } => {
+                image.clear();
+                next_index = 0;
+                begin = Some((size, sha256));
+                let status = if size == 0 { mcu_const::OTA_STATUS_SIZE } else { mcu_const::OTA_STATUS_OK };
+                (mcu_const::OTA_CMD_BEGIN, 0, status)
+            }
+            OtaMessage::Chunk { index, crc, data } => {
+                let inject = nak_every.is_some_and(|n| n > 0 && index % n == n - 1) && nak_sent != Some(index);
+                let status = if begin.is_none() {
+                    mcu_const::OTA_STATUS_BUSY
+                } else if inject || crc32fast::hash(&data) != crc {
+                    nak_sent = Some(index);
+                    mcu_const::OTA_STATUS_CRC
+                } else if index + 1 == next_index {
+                    // 重送的區塊已寫入，再回覆一次即可
+                    // a resent chunk is already stored, just acknowledge again
+                    mcu_const::OTA_STATUS_OK
+                } else if index != next_index {
+                    mcu_const::OTA_STATUS_INDEX
+                } else {
+                    image.extend_from_slice(&data);
+                    next_index += 1;
+                    mcu_const::OTA_STATUS_OK
+                };
+                (mcu_const::OTA_CMD_CHUNK, index, status)
+            }
+            OtaMessage::End => {
+                let status = match begin.take() {
+                    None => mcu_const::OTA_STATUS_BUSY,
+                    Some((size, _)) if size as usize != image.len() => mcu_const::OTA_STATUS_SIZE,
+                    Some((_, sha256)) if Sha256::digest(&image)[..] != sha256[..] => mcu_const::OTA_STATUS_HASH,
+                    Some(_) => {
+                        info!("OTA mock device received {} bytes from {}, hash ok", image.len(), peer);
+                        mcu_const::OTA_STATUS_OK
+                    }
+                };
+                (mcu_const::OTA_CMD_END, 0, status)
+            }
+            OtaMessage::Ack { .. } => continue,
+        };
+        let reply = OtaMessage::Ack { cmd, index, status };
+        let frame = WifiFrame::new(frame.device_id(), frame.seq(), mcu_const::WIFI_FRAME_TYPE_OTA, reply.encode())?;
+        WifiTcpConns::write_frame(&mut stream, &frame.unpack()).await?;
+    }
+}
+
+/// 本機模擬 OTA 裝置 <br>
+/// Local stand-in OTA device
+pub struct OtaMockDevice {
+    addr: Option<SocketAddr>,
+    shutdown: Option<watch::Sender<bool>>,
+}
+impl OtaMockDevice {
+    pub fn new() -> Self {
+        Self {
+            addr: None,
+            shutdown: None,
+        }
+    }
+
+    /// 綁定 TCP 埠並開始接受更新 <br>
+    /// Binds the TCP port and starts accepting updates
+    pub async fn start(&mut self, bind: SocketAddr, nak_every: Option<u32>) -> Result<SocketAddr, String> {
+        if let Some(addr) = self.addr {
+            return Err(format!("OTA mock device already running at {}", addr));
+        }
+        let listener = TcpListener::bind(bind).await
+            .map_err(|e| format!("OTA mock device bind {} failed: {}", bind, e))?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        tokio::spawn(async move {
+            loop {
+                let (stream, peer) = tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    result = listener.accept() => match result {
+                        Ok(pair) => pair,
+                        Err(e) => {
+                            error!("OTA mock device accept failed: {}", e);
+                            continue;
+                        }
+                    },
+                };
+                let mut session_shutdown = shutdown_rx.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = session_shutdown.changed() => {}
+                        result = mock_session(stream, peer, nak_every) => if let Err(e) = result {
+                            debug!("OTA mock session {} ended: {}", peer, e);
+                        },
+                    }
+                });
+            }
+            info!("OTA mock device stopped");
+        });
+        self.addr = Some(addr);
+        self.shutdown = Some(shutdown_tx);
+        Ok(addr)
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(true);
+        }
+        self.addr = None;
+    }
+}
+impl Default for OtaMockDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tauri 指令：開始韌體更新，經由 WiFi 監聽器的 TCP 連線收發；未指定位址時使用目前的 TCP WiFi 鏈路 <br>
+/// Tauri command: start a firmware update over the TCP sessions of the WiFi listener; uses the current TCP WiFi link when no address is given
+#[tauri::command]
+pub async fn cmd_ota_start(
+    app: AppHandle,
+    path: String,
+    addr: Option<String>,
+    device_id: Option<u16>,
+    chunk_size: Option<u16>,
+) -> Result<OtaProgress, String> {
+    let global_state = app.state::<GlobalState>();
+    let link = global_state.mcu_link.lock().await.link();
+    let link_device = match link {
+        McuLink::Wifi { device_id, .. } => Some(device_id),
+        McuLink::Uart => None,
+    };
+    let target = match (addr.filter(|s| !s.trim().is_empty()), link) {
+        (Some(addr), _) => addr.trim().parse::<SocketAddr>()
+            .map_err(|e| format!("Invalid OTA address {}: {}", addr, e))?,
+        (None, McuLink::Wifi { addr, protocol: WifiProtocol::Tcp, .. }) => addr,
+        (None, _) => return Err("No OTA target: enter an address or link a device over TCP".into()),
+    };
+    let device_id = device_id.or(link_device).unwrap_or(1);
+    let conns = {
+        let wifi = global_state.wifi_manager.lock().await;
+        if !wifi.is_started() {
+            return Err("WiFi listener not running, start it before an OTA update".into());
+        }
+        wifi.tcp_conns()
+    };
+    let data = fs::read(PathBuf::from(&path)).map_err(|e| format!("Read firmware {} failed: {}", path, e))?;
+    let chunk_size = chunk_size.unwrap_or(OTA_CHUNK_SIZE_DEFAULT) as usize;
+    let image = OtaImage::new(data, chunk_size).map_err(|e| e.to_string())?;
+    info!("OTA update of {} ({} bytes, {} chunks) to {} device {}", path, image.data.len(), image.chunks(), target, device_id);
+    let progress = {
+        let mut ota = global_state.ota_manager.lock().await;
+        ota.start(app.clone(), conns, target, device_id, image).map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+        ota.progress()
+    };
+    if let Err(e) = app.emit("ota-progress", &progress) {
+        error!("Emit ota-progress failed: {}", e);
+    }
+    Ok(progress)
+}
+
+/// Tauri 指令：中止韌體更新 <br>
+/// Tauri command: cancel the firmware update
+#[tauri::command]
+pub async fn cmd_ota_cancel(app: AppHandle) -> Result<OtaProgress, String> {
+    let global_state = app.state::<GlobalState>();
+    let mut ota = global_state.ota_manager.lock().await;
+    if ota.cancel() {
+        info!("OTA update cancelled");
+    }
+    Ok(ota.progress())
+}
+
+/// Tauri 指令：取得韌體更新進度 <br>
+/// Tauri command: get the firmware update progress
+#[tauri::command]
+pub async fn cmd_ota_status(app: AppHandle) -> Result<OtaProgress, String> {
+    let global_state = app.state::<GlobalState>();
+    let ota = global_state.ota_manager.lock().await;
+    Ok(ota.progress())
+}
+
+/// Tauri 指令：啟動本機模擬 OTA 裝置，回傳其位址 <br>
+/// Tauri command: start the local stand-in OTA device, returns its address
+#[tauri::command]
+pub async fn cmd_ota_mock_start(app: AppHandle, port: Option<u16>, nak_every: Option<u32>) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    let bind = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port.unwrap_or(0));
+    let mut mock = global_state.ota_mock_device.lock().await;
+    let addr = mock.start(bind, nak_every).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    info!("OTA mock device listening at {}", addr);
+    Ok(addr.to_string())
+}
+
+/// Tauri 指令：停止本機模擬 OTA 裝置 <br>
+/// Tauri command: stop the local stand-in OTA device
+#[tauri::command]
+pub async fn cmd_ota_mock_stop(app: AppHandle) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    global_state.ota_mock_device.lock().await.stop();
+    Ok("OTA mock device stopped".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncRead, AsyncWrite};
+    use super::*;
+
+    /// 直接以串流收發訊框，略過 keepalive <br>
+    /// Exchanges frames directly over a stream, skipping keepalives
+    struct OtaStream<S>(S);
+    impl<S: AsyncRead + AsyncWrite + Unpin> OtaTransport for OtaStream<S> {
+        async fn frame_send(&mut self, frame: WifiFrame) -> OtaResult<()> {
+            WifiTcpConns::write_frame(&mut self.0, &frame.unpack()).await
+        }
+
+        async fn frame_recv(&mut self) -> OtaResult<WifiFrame> {
+            loop {
+                let data = WifiTcpConns::read_frame(&mut self.0).await?;
+                if !data.is_empty() {
+                    return WifiFrame::pack(&data);
+                }
+            }
+        }
+    }
+
+    fn firmware(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    /// 對模擬裝置執行更新，回傳結果與最終進度 <br>
+    /// Runs an update against the stand-in device, returns the result and the final progress
+    async fn ota_mock_run(image: &OtaImage, nak_every: Option<u32>) -> (OtaResult<()>, OtaProgress) {
+        let mut mock = OtaMockDevice::new();
+        let addr = mock.start(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0), nak_every).await.unwrap();
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let progress = watch::Sender::new(OtaProgress::idle());
+        let result = ota_run(OtaStream(stream), 1, image, &progress).await;
+        mock.stop();
+        let progress = progress.borrow().clone();
+        (result, progress)
+    }
+
+    #[tokio::test]
+    async fn update_mock_device() {
+        let image = OtaImage::new(firmware(1000), 128).unwrap();
+        let (result, progress) = ota_mock_run(&image, None).await;
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(progress.state, OtaState::Verifying);
+        assert_eq!((progress.chunk, progress.sent_bytes, progress.retries), (8, 1000, 0));
+    }
+
+    #[tokio::test]
+    async fn update_resends_on_crc_nak() {
+        let image = OtaImage::new(firmware(1000), 128).unwrap();
+        let (result, progress) = ota_mock_run(&image, Some(3)).await;
+        assert!(result.is_ok(), "{:?}", result);
+        // 區塊 2 與 5 各被拒絕一次 / chunks 2 and 5 are each rejected once
+        assert_eq!(progress.retries, 2);
+        assert_eq!(progress.sent_bytes, 1000);
+    }
+
+    #[tokio::test]
+    async fn update_hash_mismatch() {
+        let mut image = OtaImage::new(firmware(300), 100).unwrap();
+        image.sha256[0] ^= 0xff;
+        let (result, progress) = ota_mock_run(&image, None).await;
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("hash mismatch"), "{}", error);
+        assert_eq!(progress.chunk, 3);
+    }
+
+    #[test]
+    fn route_accepts_inbound_port() {
+        let (replies, rx) = mpsc::channel(1);
+        let route = OtaRoute { target: "192.168.1.20:5000".parse().unwrap(), device_id: 7, replies };
+        assert!(route.accepts("192.168.1.20:51234".parse().unwrap(), 7));
+        assert!(!route.accepts("192.168.1.21:5000".parse().unwrap(), 7));
+        assert!(!route.accepts("192.168.1.20:5000".parse().unwrap(), 8));
+        drop(rx);
+        assert!(!route.accepts("192.168.1.20:5000".parse().unwrap(), 7));
+    }
+}
@@ -1,10 +1,19 @@
-use std::{error::Error, fs, iter, path::{self, PathBuf}};
+use std::{collections::{HashMap, HashSet, VecDeque}, error::Error, fs, iter, path::{self, PathBuf}};
 use log::error;
-use plotters::prelude::*;
+use plotters::{coord::Shift, prelude::*};
 use rand::{self, Rng};
 use base64::{engine::general_purpose, Engine};
+use serde::Deserialize;
 use tauri::{AppHandle, Manager};
-use crate::{mods::directory_mod, GlobalState, ROOT_GEN_FILES_FOLDER};
+use crate::{
+    mods::{
+        directory_mod,
+        map_mod::{self, InItem, NodeKind, PosType},
+        map_route_mod::MapGraph,
+        mcu_store_mod::{DataSlice, DataType},
+    },
+    GlobalState, ROOT_GEN_FILES_FOLDER,
+};
 
 fn store_folder() -> String {
     format!("{}/chart", ROOT_GEN_FILES_FOLDER)
@@ -50,9 +59,7 @@ impl ChartRandDatas {
     }
     
     pub fn send_to_front(&self) -> Result<String, String> {
-        let bytes = fs::read(self.file_path.clone())
-            .map_err(|e| format!("Cannot read file: {}", e))?;
-        Ok(general_purpose::STANDARD.encode(bytes))
+        file_to_base64(&self.file_path)
     }
 
     pub fn line_chart_generate(&self) -> Result<(), String> {
@@ -263,6 +270,203 @@ pub fn line_chart_generate<P: AsRef<path::Path>>(
     Ok(root.present().map_err(|e| e)?)
 }
 
+/// 讀取檔案並轉為 base64，供前端以 data URL 顯示
+pub fn file_to_base64<P: AsRef<path::Path>>(file_path: P) -> Result<String, String> {
+    let bytes = fs::read(file_path)
+        .map_err(|e| format!("Cannot read file: {}", e))?;
+    Ok(general_purpose::STANDARD.encode(bytes))
+}
+
+/// 地圖圖片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapChartFormat {
+    Png,
+    Svg,
+}
+
+/// 地圖繪製內容：路徑與車輛所在節點
+struct MapChartData<'a> {
+    items:      &'a [InItem],
+    route:      &'a [PosType],
+    vehicle:    Option<PosType>,
+}
+
+/// 節點位置：全部節點皆有座標時直接使用，否則由各連通區塊的第一個節點依連接方位與長度展開
+fn map_layout(items: &[InItem]) -> HashMap<PosType, (f64, f64)> {
+    if let Some(coords) = items.iter()
+        .map(|item| item.coord().map(|(x, y)| (item.id, (x as f64, y as f64))))
+        .collect::<Option<HashMap<_, _>>>()
+    {
+        return coords;
+    }
+    let index: HashMap<PosType, &InItem> = items.iter().map(|item| (item.id, item)).collect();
+    let gap = items.iter().flat_map(|item| item.connect.iter().map(|c| c.len)).max().unwrap_or(0).max(10) as f64;
+    let mut pos: HashMap<PosType, (f64, f64)> = HashMap::new();
+    let mut offset_x = 0.0;
+    for start in items {
+        if pos.contains_key(&start.id) {
+            continue;
+        }
+        let mut block = vec![start.id];
+        let mut queue = VecDeque::from([start.id]);
+        pos.insert(start.id, (0.0, 0.0));
+        while let Some(id) = queue.pop_front() {
+            let (x, y) = pos[&id];
+            for (slot, conn) in index[&id].connect.iter().enumerate() {
+                if conn.pos == 0 || pos.contains_key(&conn.pos) || !index.contains_key(&conn.pos) {
+                    continue;
+                }
+                // 方位北方為 0 順時針
+                let angle = (conn.heading_or_slot(slot) as f64).to_radians();
+                let len = conn.len.max(1) as f64;
+                pos.insert(conn.pos, (x + len * angle.sin(), y + len * angle.cos()));
+                block.push(conn.pos);
+                queue.push_back(conn.pos);
+            }
+        }
+        // 各區塊左右排開
+        let min_x = block.iter().map(|id| pos[id].0).fold(f64::INFINITY, f64::min);
+        let max_x = block.iter().map(|id| pos[id].0).fold(f64::NEG_INFINITY, f64::max);
+        for id in &block {
+            pos.get_mut(id).unwrap().0 += offset_x - min_x;
+        }
+        offset_x += max_x - min_x + gap;
+    }
+    pos
+}
+
+fn map_chart_draw<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, data: &MapChartData) -> Result<(), String> {
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+    let pos = map_layout(data.items);
+
+    // 計算座標範圍並留邊
+    let xs = pos.values().map(|p| p.0);
+    let ys = pos.values().map(|p| p.1);
+    let (min_x, max_x) = (xs.clone().fold(f64::INFINITY, f64::min), xs.fold(f64::NEG_INFINITY, f64::max));
+    let (min_y, max_y) = (ys.clone().fold(f64::INFINITY, f64::min), ys.fold(f64::NEG_INFINITY, f64::max));
+    let (min_x, max_x, min_y, max_y) = if pos.is_empty() { (0.0, 1.0, 0.0, 1.0) } else { (min_x, max_x, min_y, max_y) };
+    let pad_x = ((max_x - min_x) * 0.1).max(1.0);
+    let pad_y = ((max_y - min_y) * 0.1).max(1.0);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption("Map", ("Serif", 40))
+        .margin(40)
+        .build_cartesian_2d((min_x - pad_x)..(max_x + pad_x), (min_y - pad_y)..(max_y + pad_y))
+        .map_err(|e| e.to_string())?;
+
+    let route_edges: HashSet<(PosType, PosType)> = data.route.windows(2).map(|w| (w[0], w[1])).collect();
+    let route_nodes: HashSet<PosType> = data.route.iter().copied().collect();
+    let route_color = RED;
+    let vehicle_color = RGBColor(255, 140, 0);
+    let label_style = ("Serif", 28).into_font().color(&BLACK);
+    let length_style = ("Serif", 24).into_font().color(&RGBColor(90, 90, 90));
+
+    // 邊與長度，雙向且等長的邊只標一次
+    for item in data.items {
+        for conn in item.connect.iter().filter(|c| c.pos != 0) {
+            let (Some(&a), Some(&b)) = (pos.get(&item.id), pos.get(&conn.pos)) else { continue };
+            let on_route = route_edges.contains(&(item.id, conn.pos)) || route_edges.contains(&(conn.pos, item.id));
+            let style = if on_route { route_color.stroke_width(5) } else { RGBColor(160, 160, 160).stroke_width(2) };
+            chart.draw_series(iter::once(PathElement::new(vec![a, b], style)))
+                .map_err(|e| e.to_string())?;
+            let mirrored = data.items.iter()
+                .find(|other| other.id == conn.pos)
+                .is_some_and(|other| other.connect.iter().any(|c| c.pos == item.id && c.len == conn.len));
+            if !mirrored || item.id < conn.pos {
+                let mid = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                chart.draw_series(iter::once(Text::new(conn.len.to_string(), mid, length_style.clone())))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    // 節點：依種類上色，路徑上的節點加外框，車輛位置以三角形標示
+    for item in data.items {
+        let Some(&p) = pos.get(&item.id) else { continue };
+        let color = match item.kind {
+            NodeKind::Junction => RGBColor(113, 128, 150),
+            NodeKind::Station => BLUE,
+            NodeKind::Charger => GREEN,
+        };
+        let label = if item.name.is_empty() { item.id.to_string() } else { format!("{} {}", item.id, item.name) };
+        let node = EmptyElement::at(p)
+            + Circle::new((0, 0), 12, color.filled())
+            + Text::new(label, (16, -28), label_style.clone());
+        chart.draw_series(iter::once(node)).map_err(|e| e.to_string())?;
+        if route_nodes.contains(&item.id) {
+            chart.draw_series(iter::once(Circle::new(p, 16, route_color.stroke_width(3))))
+                .map_err(|e| e.to_string())?;
+        }
+        if data.vehicle == Some(item.id) {
+            chart.draw_series(iter::once(TriangleMarker::new(p, 18, vehicle_color.filled())))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    root.present().map_err(|e| e.to_string())
+}
+
+/// 繪製地圖到 generate/chart/map.png 或 map.svg，回傳檔案路徑
+fn map_chart_generate(
+    items: &[InItem],
+    route: &[PosType],
+    vehicle: Option<PosType>,
+    format: MapChartFormat,
+) -> Result<PathBuf, String> {
+    let (root_size_x, root_size_y) = (1600, 1200);
+    let file_name = match format {
+        MapChartFormat::Png => "map.png",
+        MapChartFormat::Svg => "map.svg",
+    };
+    let file_path = directory_mod::create_file(store_folder(), file_name).map_err(|e| e.to_string())?;
+    let data = MapChartData { items, route, vehicle };
+    match format {
+        MapChartFormat::Png => {
+            let root = BitMapBackend::new(&file_path, (root_size_x, root_size_y)).into_drawing_area();
+            map_chart_draw(&root, &data)?;
+        }
+        MapChartFormat::Svg => {
+            let root = SVGBackend::new(&file_path, (root_size_x, root_size_y)).into_drawing_area();
+            map_chart_draw(&root, &data)?;
+        }
+    }
+    Ok(file_path)
+}
+
+/// Tauri 命令：繪製已保存的地圖，from/to 皆有值時標示最短路徑，並標示 VehicelPos 最新的節點，回傳 base64
+#[tauri::command]
+pub async fn map_chart(
+    app: AppHandle,
+    format: MapChartFormat,
+    from: Option<PosType>,
+    to: Option<PosType>,
+) -> Result<String, String> {
+    let items = map_mod::map_items_read(&app).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let route = match (from, to) {
+        (Some(from), Some(to)) => MapGraph::new(&items).dijkstra(from, to)?
+            .map(|route| route.path)
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let vehicle = {
+        let global_state = app.state::<GlobalState>();
+        let store = global_state.store_datas.lock().await;
+        match store.get(DataType::VehicelPos) {
+            DataSlice::U16(positions) => positions.last().copied(),
+            _ => None,
+        }
+    };
+    let file_path = map_chart_generate(&items, &route, vehicle, format).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    file_to_base64(file_path)
+}
+
 #[tauri::command]
 pub async fn chart_generate(app: AppHandle) -> Result<String, String> {
     let global_state = app.state::<GlobalState>();
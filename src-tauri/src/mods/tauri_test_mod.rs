@@ -15,7 +15,7 @@ pub async fn mytest(app: AppHandle) -> Result<(), String> {
     // let _ = packet_proc_mod::gen_h_file(app.clone()).map_err(|e| {
     //     error!("{}", e);
     // });
-    let _ = wifi_mod::cmd_wifi_start(app.clone()).await;
+    let _ = wifi_mod::cmd_wifi_start(app.clone(), None, None, None, None).await;
     info!("Ok");
     Ok(())
 }
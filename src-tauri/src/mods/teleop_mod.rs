@@ -0,0 +1,282 @@
+use std::{thread, time::{Duration, Instant}};
+use gilrs::{Axis, Button, Gilrs};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::{mods::mcu_control_mod::{self, VehicleMotion}, GlobalState};
+
+/// 遙控處理週期（ms）<br>
+/// Teleoperation process period in ms
+pub const TELEOP_PERIOD_MS: u64 = 50;
+
+/// 失效開關逾時（ms），超過此時間未收到輸入即停車<br>
+/// Dead-man timeout in ms, the vehicle stops when no input arrives within it
+pub const TELEOP_DEADMAN_MS: u64 = 300;
+
+/// 搖桿死區<br>
+/// Gamepad stick dead zone
+const GAMEPAD_DEAD_ZONE: f32 = 0.1;
+
+/// 遙控輸入來源<br>
+/// Teleoperation input source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeleopSource {
+    Keyboard,
+    Gamepad,
+}
+
+/// 遙控狀態報告<br>
+/// Teleoperation status report
+#[derive(Debug, Clone, Serialize)]
+pub struct TeleopReport {
+    pub enabled: bool,
+    pub source: Option<TeleopSource>,
+    pub active: bool,
+    pub left: f32,
+    pub right: f32,
+    pub max_speed: f32,
+}
+
+/// 遙控狀態：最新輸入、輸出與速率限制<br>
+/// Teleoperation state: latest input, output and rate limit
+pub struct TeleopState {
+    source: Option<TeleopSource>,
+    throttle: f32,
+    steer: f32,
+    last_input: Option<Instant>,
+    left: f32,
+    right: f32,
+    stopped: bool,
+    max_speed: f32,
+    max_step: f32,
+    gamepad_gen: u64,
+}
+impl TeleopState {
+    /// 建立停用中的遙控狀態<br>
+    /// Creates a disabled teleoperation state
+    pub fn new() -> Self {
+        Self {
+            source: None,
+            throttle: 0.0,
+            steer: 0.0,
+            last_input: None,
+            left: 0.0,
+            right: 0.0,
+            stopped: true,
+            max_speed: 100.0,
+            max_step: 10.0,
+            gamepad_gen: 0,
+        }
+    }
+
+    /// 啟用遙控，每週期輸出變化不超過 max_speed 的 1/10<br>
+    /// Enables teleoperation, output changes at most 1/10 of max_speed per period
+    pub fn enable(&mut self, source: TeleopSource, max_speed: f32) {
+        self.source = Some(source);
+        self.max_speed = max_speed;
+        self.max_step = max_speed / 10.0;
+        self.last_input = None;
+    }
+
+    /// 停用遙控<br>
+    /// Disables teleoperation
+    pub fn disable(&mut self) {
+        self.source = None;
+        self.last_input = None;
+        self.left = 0.0;
+        self.right = 0.0;
+        self.stopped = true;
+    }
+
+    /// 開始新的搖桿執行緒世代，舊執行緒看到世代改變後自行結束<br>
+    /// Starts a new gamepad thread generation, older threads exit once they see it changed
+    fn gamepad_next(&mut self) -> u64 {
+        self.gamepad_gen = self.gamepad_gen.wrapping_add(1);
+        self.gamepad_gen
+    }
+
+    /// 取得目前輸入來源<br>
+    /// Returns the current input source
+    pub fn source(&self) -> Option<TeleopSource> {
+        self.source
+    }
+
+    /// 更新輸入，未按下失效開關時不視為有效輸入<br>
+    /// Updates the input, ignored as activity when the dead-man switch is released
+    pub fn input(&mut self, throttle: f32, steer: f32, deadman: bool) {
+        if !deadman || !throttle.is_finite() || !steer.is_finite() {
+            self.last_input = None;
+            return;
+        }
+        self.throttle = throttle.clamp(-1.0, 1.0);
+        self.steer = steer.clamp(-1.0, 1.0);
+        self.last_input = Some(Instant::now());
+    }
+
+    fn active(&self) -> bool {
+        self.source.is_some() && self.last_input
+            .is_some_and(|t| t.elapsed() <= Duration::from_millis(TELEOP_DEADMAN_MS))
+    }
+
+    /// 計算本週期要送出的移動指令；輸入中斷時只送出一次停止<br>
+    /// Computes the motion to send this period; sends stop once when input stops
+    pub fn step(&mut self) -> Option<VehicleMotion> {
+        if !self.active() {
+            if self.stopped {
+                return None;
+            }
+            self.left = 0.0;
+            self.right = 0.0;
+            self.stopped = true;
+            return Some(VehicleMotion::Stop);
+        }
+        let target_left = (self.throttle + self.steer).clamp(-1.0, 1.0) * self.max_speed;
+        let target_right = (self.throttle - self.steer).clamp(-1.0, 1.0) * self.max_speed;
+        self.left += (target_left - self.left).clamp(-self.max_step, self.max_step);
+        self.right += (target_right - self.right).clamp(-self.max_step, self.max_step);
+        self.stopped = false;
+        Some(VehicleMotion::Differential {
+            left: self.left,
+            right: self.right,
+            duration_ms: TELEOP_DEADMAN_MS as u32,
+        })
+    }
+
+    /// 取得目前狀態報告<br>
+    /// Returns the current status report
+    pub fn report(&self) -> TeleopReport {
+        TeleopReport {
+            enabled: self.source.is_some(),
+            source: self.source,
+            active: self.active(),
+            left: self.left,
+            right: self.right,
+            max_speed: self.max_speed,
+        }
+    }
+}
+impl Default for TeleopState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 每週期計算並送出遙控指令<br>
+/// Computes and sends the teleoperation command every period
+pub async fn teleop_proccess(app: AppHandle) {
+    // 鏈路未就緒時不送出，避免封包堆積後在重新連線時一次送出
+    // Skip while the link is down so stale packets are not flushed on reconnect
+    if !mcu_control_mod::link_ready(&app).await {
+        return;
+    }
+    let global_state = app.state::<GlobalState>();
+    let motion = {
+        let mut teleop = global_state.teleop.lock().await;
+        teleop.step()
+    };
+    let Some(motion) = motion else { return; };
+    if let VehicleMotion::Stop = motion {
+        info!("Teleop input stopped, vehicle stop");
+    }
+    if let Err(e) = mcu_control_mod::transmit_push(&app, motion.encode()).await {
+        error!("Teleop send failed: {}", e);
+    }
+}
+
+/// 啟動搖桿讀取執行緒，左搖桿 Y 為油門、右搖桿 X 為轉向、RB 為失效開關；世代 generation 過期時結束<br>
+/// Starts the gamepad thread: left stick Y is throttle, right stick X is steer, RB is the dead-man switch; exits once generation is outdated
+fn gamepad_spawn(app: AppHandle, generation: u64) {
+    thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(g) => g,
+            Err(e) => {
+                error!("Gamepad init failed: {}", e);
+                return;
+            }
+        };
+        let dead_zone = |v: f32| if v.abs() < GAMEPAD_DEAD_ZONE { 0.0 } else { v };
+        loop {
+            while gilrs.next_event().is_some() {}
+            let global_state = app.state::<GlobalState>();
+            let mut teleop = global_state.teleop.blocking_lock();
+            if teleop.source() != Some(TeleopSource::Gamepad) || teleop.gamepad_gen != generation {
+                break;
+            }
+            match gilrs.gamepads().next() {
+                Some((_, gamepad)) => teleop.input(
+                    dead_zone(gamepad.value(Axis::LeftStickY)),
+                    dead_zone(gamepad.value(Axis::RightStickX)),
+                    gamepad.is_pressed(Button::RightTrigger),
+                ),
+                None => teleop.input(0.0, 0.0, false),
+            }
+            drop(teleop);
+            thread::sleep(Duration::from_millis(TELEOP_PERIOD_MS / 2));
+        }
+        info!("Gamepad thread stopped");
+    });
+}
+
+/// Tauri 指令：啟用遙控模式<br>
+/// Tauri command: enable teleoperation
+#[tauri::command]
+pub async fn cmd_teleop_start(app: AppHandle, source: TeleopSource, max_speed: f32) -> Result<TeleopReport, String> {
+    if !max_speed.is_finite() || max_speed <= 0.0 {
+        let message = format!("Invalid max speed: {}", max_speed);
+        error!("{}", message);
+        return Err(message);
+    }
+    let global_state = app.state::<GlobalState>();
+    let report = {
+        let mut teleop = global_state.teleop.lock().await;
+        let previous = teleop.source();
+        teleop.enable(source, max_speed);
+        if source == TeleopSource::Gamepad && previous != Some(TeleopSource::Gamepad) {
+            let generation = teleop.gamepad_next();
+            gamepad_spawn(app.clone(), generation);
+        }
+        teleop.report()
+    };
+    info!("Teleop started: {:?}, max speed {}", source, max_speed);
+    Ok(report)
+}
+
+/// Tauri 指令：停用遙控模式並停車<br>
+/// Tauri command: disable teleoperation and stop the vehicle
+#[tauri::command]
+pub async fn cmd_teleop_stop(app: AppHandle) -> Result<(), String> {
+    let global_state = app.state::<GlobalState>();
+    global_state.teleop.lock().await.disable();
+    mcu_control_mod::transmit_push(&app, VehicleMotion::Stop.encode()).await?;
+    info!("Teleop stopped");
+    Ok(())
+}
+
+/// Tauri 指令：由前端鍵盤送入遙控輸入（-1.0 ~ 1.0）<br>
+/// Tauri command: teleoperation input from the frontend keyboard (-1.0 ~ 1.0)
+#[tauri::command]
+pub async fn cmd_teleop_input(app: AppHandle, throttle: f32, steer: f32, deadman: bool) -> Result<(), String> {
+    let global_state = app.state::<GlobalState>();
+    let mut teleop = global_state.teleop.lock().await;
+    match teleop.source() {
+        Some(TeleopSource::Keyboard) => {
+            teleop.input(throttle, steer, deadman);
+            Ok(())
+        }
+        source => {
+            let message = format!("Keyboard input ignored, teleop source: {:?}", source);
+            warn!("{}", message);
+            Err(message)
+        }
+    }
+}
+
+/// Tauri 指令：取得遙控狀態<br>
+/// Tauri command: get the teleoperation status
+#[tauri::command]
+pub async fn cmd_teleop_status(app: AppHandle) -> Result<TeleopReport, String> {
+    let global_state = app.state::<GlobalState>();
+    let teleop = global_state.teleop.lock().await;
+    Ok(teleop.report())
+}
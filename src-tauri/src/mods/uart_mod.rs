@@ -1,8 +1,9 @@
 use std::{error::Error, sync::Arc, time::Duration};
 use log::{debug, error, info, trace};
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 use serialport::{available_ports, SerialPortInfo};
-use tokio_serial::{SerialPortBuilderExt, SerialStream};
+use tokio_serial::{Parity, SerialPortBuilderExt, SerialStream};
 use tokio::{io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf}, sync::{watch::{channel, Receiver, Sender}, Mutex}, time::{sleep, timeout}};
 use crate::{mods::{log_mod::CODE_TRACE, uart_packet_mod::{self, UartPacket}}, GlobalState};
 
@@ -14,12 +15,33 @@ const   UART_READ_TIMEOUT_MS:       u64     = 10;
 /// Maximum receive buffer size (including start and end codes)
 const   MAX_RECEIVE_BUFFER_SIZE:    usize   = uart_packet_mod::UART_PACKET_MAX_SIZE;
 
+/// 序列埠同位元設定 <br>
+/// Serial port parity setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UartParity {
+    #[default]
+    None,
+    Even,
+    Odd,
+}
+impl From<UartParity> for Parity {
+    fn from(parity: UartParity) -> Self {
+        match parity {
+            UartParity::None => Parity::None,
+            UartParity::Even => Parity::Even,
+            UartParity::Odd => Parity::Odd,
+        }
+    }
+}
+
 /// 非同步序列埠管理器 <br>
 /// Asynchronous serial port manager
 pub struct UartAsyncManager {
     port_name: Option<String>,          // 序列埠名稱／port name
     inner: Arc<UartAsyncManagerInner>,  // 內部管理結構／inner manager
     shutdown: Option<Sender<bool>>,  // 停止訊號傳送者／shutdown signal sender
+    raw: bool,                          // 已交由開機載入程式直接使用／taken for raw bootloader access
 }
 impl UartAsyncManager {
     /// 建立新的 UART 非同步管理器，初始化讀寫緩衝<br>
@@ -29,6 +51,7 @@ impl UartAsyncManager {
             port_name: None,
             inner: Arc::new(UartAsyncManagerInner::new()),
             shutdown: None,
+            raw: false,
         }
     }
 
@@ -39,20 +62,36 @@ impl UartAsyncManager {
         Ok(ports)
     }
 
-    /// 開啟指定序列埠並設定波特率<br>
-    /// Opens the given serial port with the specified baud rate
+    /// 開啟指定序列埠並設定波特率與同位元<br>
+    /// Opens the given serial port with the specified baud rate and parity
     pub async fn open(
         &mut self,
         app: AppHandle,
         port_name: &str,
         baudrate: u32,
+        parity: UartParity,
         timeout_ms: u64,
     ) -> Result<(), String> {
+        if self.raw {
+            return Err("Port is in use by the bootloader".into());
+        }
         let stream = tokio_serial::new(port_name, baudrate)
+            .parity(parity.into())
             .timeout(Duration::from_millis(timeout_ms))
             .open_native_async()
             .map_err(|e| format!("Port open failed: {}", e))?;
         self.port_name = Some(port_name.to_string());
+        self.attach(app, stream).await;
+        Ok(())
+    }
+
+    /// 清除連線前殘留的待送封包，分離讀寫並啟動封包讀寫任務<br>
+    /// Drops packets queued before the connection, splits the stream and starts the packet read / write tasks
+    async fn attach(&mut self, app: AppHandle, stream: SerialStream) {
+        let dropped = app.state::<GlobalState>().uart_transmit_buffer.lock().await.take_all().len();
+        if dropped > 0 {
+            info!("Dropped {} stale UART packets", dropped);
+        }
         let (reader, writer) = tokio::io::split(stream);
         *self.inner.reader.lock().await = Some(reader);
         *self.inner.writer.lock().await = Some(writer);
@@ -61,12 +100,39 @@ impl UartAsyncManager {
 
         self.inner.read_spawn(app.clone(), shutdown_rx.clone());
         self.inner.write_spawn(app.clone(), shutdown_rx);
-        Ok(())
+    }
+
+    /// 停止封包讀寫任務並取出序列埠，供開機載入程式直接讀寫；用畢以 raw_restore 歸還<br>
+    /// Stops the packet read / write tasks and takes the port for direct bootloader access; return it with raw_restore
+    pub async fn raw_take(&mut self) -> Result<SerialStream, String> {
+        self.inner.check_open().await?;
+        if let Some(shutdown_tx) = self.shutdown.take() {
+            let _ = shutdown_tx.send(true);
+        }
+        let reader = self.inner.reader.lock().await.take();
+        let writer = self.inner.writer.lock().await.take();
+        match (reader, writer) {
+            (Some(reader), Some(writer)) => {
+                self.raw = true;
+                Ok(reader.unsplit(writer))
+            }
+            _ => Err("Port is not open".into()),
+        }
+    }
+
+    /// 歸還 raw_take 取出的序列埠並重新啟動封包讀寫任務<br>
+    /// Returns the port taken by raw_take and restarts the packet read / write tasks
+    pub async fn raw_restore(&mut self, app: AppHandle, stream: SerialStream) {
+        self.raw = false;
+        self.attach(app, stream).await;
     }
 
     /// 關閉目前已開啟的序列埠<br>
     /// Closes the currently opened serial port
     pub async fn close(&mut self) -> Result<(), String> {
+        if self.raw {
+            return Err("Port is in use by the bootloader".into());
+        }
         if let Some(shutdown_tx) = self.shutdown.take() {
             let _ = shutdown_tx.send(true);
         }
@@ -231,10 +297,10 @@ pub async fn cmd_check_port_open_async(app: AppHandle) -> bool {
 /// Tauri 指令：開啟序列埠<br>
 /// Tauri command: open the serial port
 #[tauri::command]
-pub async fn cmd_open_port_async(app: AppHandle, port_name: String) -> Result<String, String> {
+pub async fn cmd_open_port_async(app: AppHandle, port_name: String, parity: Option<UartParity>) -> Result<String, String> {
     let global_state = app.state::<GlobalState>();
     let mut state = global_state.uart_manager.lock().await;
-    state.open(app.clone(), &port_name, 115200, 1000).await.map_err(|e| {
+    state.open(app.clone(), &port_name, 115200, parity.unwrap_or_default(), 1000).await.map_err(|e| {
         error!("{}", e);
         e.clone()
     })?;
@@ -2,7 +2,7 @@ use std::{error::Error, fs};
 use log::error;
 use regex::Regex;
 use tauri::{AppHandle, Manager};
-use crate::{mods::{directory_mod, mcu_const, mcu_store_mod::{DataType, MotorDataType}}, GlobalState, BASE_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER};
+use crate::{mods::{directory_mod, heartbeat_mod, map_track_mod, mcu_const, mcu_control_mod, mcu_store_mod::MotorDataType}, GlobalState, BASE_GEN_FILES_FOLDER, ROOT_GEN_FILES_FOLDER};
 
 const CONST_RS_PATH: &str = include_str!(
     concat!(env!("CARGO_MANIFEST_DIR"), "/src/mods/mcu_const.rs")
@@ -55,83 +55,76 @@ pub async fn re_pkt_proccess(app: AppHandle) {
             let mut uart_recv_buffer = global_state.uart_receive_buffer.lock().await;
             uart_recv_buffer.pop_front()
         };
-        let mut data = match maybe_pkt {
+        let data = match maybe_pkt {
             Err(_) => break,
             Ok(p) => p.data(),
         };
-        match data.remove(0) {
-            cmd if cmd == mcu_const::CMD_CODE_DATA_TRRE => re_pkt_data_store(app.clone(), data).await,
-            cmd if cmd == mcu_const::CMD_CODE_VECH_CONTROL => break,
-            _ => break,
-        };
+        if !re_pkt_dispatch(app.clone(), data).await {
+            break;
+        }
+    }
+}
+
+/// 依命令碼分派 MCU 封包資料，UART 與 WiFi 共用；無法處理時回傳 false<br>
+/// Dispatches MCU packet data by command code, shared by UART and WiFi; returns false when unhandled
+pub async fn re_pkt_dispatch(app: AppHandle, mut data: Vec<u8>) -> bool {
+    if data.is_empty() {
+        return false;
     }
+    match data.remove(0) {
+        cmd if cmd == mcu_const::CMD_CODE_DATA_TRRE => re_pkt_data_store(app, data).await,
+        cmd if cmd == mcu_const::CMD_CODE_HEARTBEAT => heartbeat_mod::re_pkt_heartbeat(app, data).await,
+        cmd if cmd == mcu_const::CMD_CODE_POSITION => map_track_mod::re_pkt_position(app, data).await,
+        cmd if cmd == mcu_const::CMD_CODE_VECH_CONTROL => return false,
+        _ => return false,
+    };
+    true
 }
 
 /// 解析資料封包並將值存入全域狀態<br>
 /// Parses data packets and stores the extracted values into the global state
+///
+/// 資料格式：重複 `[motor, quantity, value...]`，value 長度依 quantity 而定<br>
+/// Data layout: repeated `[motor, quantity, value...]`, value size depends on quantity
 async fn re_pkt_data_store(app: AppHandle, mut data: Vec<u8>) {
     let global_state = app.state::<GlobalState>();
-    loop {
-        if        data.starts_with(mcu_const::CMD_LEFT_SPEED_STORE.payload) {
-            data.drain(..mcu_const::CMD_LEFT_SPEED_STORE.payload.len());
-            let value = match data[..size_of::<f32>()].try_into() {
-                Ok(bytes) => {
-                    data.drain(..size_of::<f32>());
-                    f32::from_bits(u32::from_be_bytes(bytes))
-                },
-                Err(e) => {
-                    error!("ERR: {}", e);
-                    break;
-                }
-            };
-            let mut store_datas = global_state.store_datas.lock().await;
-            store_datas.push(DataType::MotorLeft(MotorDataType::SpeedPresent), value as u64);
-        }
-        else if data.starts_with(mcu_const::CMD_RIGHT_SPEED_STORE.payload) {
-            data.drain(..mcu_const::CMD_RIGHT_SPEED_STORE.payload.len());
-            let value = match data[..size_of::<f32>()].try_into() {
-                Ok(bytes) => {
-                    data.drain(..size_of::<f32>());
-                    f32::from_bits(u32::from_be_bytes(bytes))
-                },
-                Err(e) => {
-                    error!("ERR: {}", e);
-                    break;
-                }
-            };
-            let mut store_datas = global_state.store_datas.lock().await;
-            store_datas.push(DataType::MotorRight(MotorDataType::SpeedPresent), value as u64);
-        }
-        else if data.starts_with(mcu_const::CMD_LEFT_ADC_STORE.payload) {
-            data.drain(..mcu_const::CMD_LEFT_ADC_STORE.payload.len());
-            let value = match data[..size_of::<u16>()].try_into() {
-                Ok(bytes) => {
-                    data.drain(..size_of::<u16>());
-                    u16::from_be_bytes(bytes)
-                },
-                Err(e) => {
-                    error!("ERR: {}", e);
-                    break;
-                }
-            };
-            let mut store_datas = global_state.store_datas.lock().await;
-            store_datas.push(DataType::MotorLeft(MotorDataType::AdcValue), value);
+    while data.len() >= 2 {
+        let (side, kind) = match (
+            mcu_control_mod::motor_side_from_code(data[0]),
+            mcu_control_mod::motor_data_from_code(data[1]),
+        ) {
+            (Some(side), Some(kind)) => (side, kind),
+            _ => {
+                error!("Unknown data store code: {:?}", &data[..2]);
+                break;
+            }
+        };
+        data.drain(..2);
+        let size = match kind {
+            MotorDataType::SpeedPresent => size_of::<f32>(),
+            MotorDataType::AdcValue => size_of::<u16>(),
+            MotorDataType::SpeedSetpoint | MotorDataType::RotateDirection |
+            MotorDataType::SpeedCommand | MotorDataType::DirectionCommand => size_of::<u8>(),
+        };
+        if data.len() < size {
+            error!("Data store value too short: {:?} need {} bytes, got {}", kind, size, data.len());
+            break;
         }
-        else if data.starts_with(mcu_const::CMD_RIGHT_ADC_STORE.payload) {
-            data.drain(..mcu_const::CMD_RIGHT_ADC_STORE.payload.len());
-            let value = match data[..size_of::<u16>()].try_into() {
-                Ok(bytes) => {
-                    data.drain(..size_of::<u16>());
-                    u16::from_be_bytes(bytes)
-                },
-                Err(e) => {
-                    error!("ERR: {}", e);
-                    break;
-                }
-            };
-            let mut store_datas = global_state.store_datas.lock().await;
-            store_datas.push(DataType::MotorRight(MotorDataType::AdcValue), value);
+        let bytes: Vec<u8> = data.drain(..size).collect();
+        let mut store_datas = global_state.store_datas.lock().await;
+        match kind {
+            MotorDataType::SpeedPresent => {
+                let value = f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                store_datas.push(side.data_type(kind), value);
+            }
+            MotorDataType::AdcValue => {
+                let value = u16::from_be_bytes([bytes[0], bytes[1]]);
+                store_datas.push(side.data_type(kind), value);
+            }
+            MotorDataType::SpeedSetpoint | MotorDataType::RotateDirection |
+            MotorDataType::SpeedCommand | MotorDataType::DirectionCommand => {
+                store_datas.push(side.data_type(kind), bytes[0]);
+            }
         }
-        else { break; }
     }
 }
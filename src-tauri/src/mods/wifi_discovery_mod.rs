@@ -0,0 +1,594 @@
+use std::{collections::{HashMap, HashSet}, net::{IpAddr, Ipv4Addr, SocketAddr}, time::{Duration, Instant}};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::{net::UdpSocket, sync::watch::{channel, Sender}};
+use crate::{mods::{mcu_const, wifi_mod, wifi_packet_mod::{self, WifiPacket}}, GlobalState};
+
+/// 裝置登錄檢查週期（ms），自動探詢亦依此週期送出 <br>
+/// Device registry check period in ms, auto probes are sent at the same period
+pub const DISCOVERY_PERIOD_MS: u64 = 1000;
+
+/// 超過此時間未回覆視為不穩定（ms） <br>
+/// A device is stale after no reply within this time in ms
+pub const DEVICE_STALE_MS: u64 = 3000;
+
+/// 超過此時間未回覆視為離線（ms） <br>
+/// A device is offline after no reply within this time in ms
+pub const DEVICE_OFFLINE_MS: u64 = 10000;
+
+const PROBE_LENGTH: usize = 6;
+const REPLY_MIN_LENGTH: usize = 16;
+
+/// 裝置狀態 <br>
+/// Device status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceStatus {
+    Online,     // 近期有回覆／replied recently
+    Stale,      // 回覆逾時／reply overdue
+    Offline,    // 長時間未回覆／no reply for a long time
+}
+
+/// 探詢目標 <br>
+/// Probe target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeTarget {
+    Broadcast,
+    Unicast(SocketAddr),
+}
+
+/// 裝置對探詢的回覆 <br>
+/// Device reply to a probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryReply {
+    pub seq: u8,
+    pub device_id: u16,
+    pub firmware: [u8; 3],
+    pub capabilities: u16,
+    pub tcp_port: u16,
+    pub name: String,
+}
+impl DiscoveryReply {
+    /// 編碼為回覆封包，名稱超過 255 bytes 時截斷 <br>
+    /// Encodes into a reply packet, the name is truncated beyond 255 bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let name = &self.name.as_bytes()[..self.name.len().min(u8::MAX as usize)];
+        let mut data = Vec::with_capacity(REPLY_MIN_LENGTH + name.len());
+        data.extend_from_slice(&mcu_const::DISCOVERY_MAGIC.to_be_bytes());
+        data.push(mcu_const::DISCOVERY_REPLY);
+        data.push(self.seq);
+        data.extend_from_slice(&self.device_id.to_be_bytes());
+        data.extend_from_slice(&self.firmware);
+        data.extend_from_slice(&self.capabilities.to_be_bytes());
+        data.extend_from_slice(&self.tcp_port.to_be_bytes());
+        data.push(name.len() as u8);
+        data.extend_from_slice(name);
+        data
+    }
+
+    /// 解析回覆封包，非回覆或長度不符時回傳 None <br>
+    /// Decodes a reply packet, returns None when it is not a reply or the length is wrong
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < REPLY_MIN_LENGTH
+            || data[..4] != mcu_const::DISCOVERY_MAGIC.to_be_bytes()
+            || data[4] != mcu_const::DISCOVERY_REPLY
+        {
+            return None;
+        }
+        let name_len = data[15] as usize;
+        let name = data.get(REPLY_MIN_LENGTH..REPLY_MIN_LENGTH + name_len)?;
+        Some(Self {
+            seq: data[5],
+            device_id: u16::from_be_bytes([data[6], data[7]]),
+            firmware: [data[8], data[9], data[10]],
+            capabilities: u16::from_be_bytes([data[11], data[12]]),
+            tcp_port: u16::from_be_bytes([data[13], data[14]]),
+            name: String::from_utf8_lossy(name).into_owned(),
+        })
+    }
+}
+
+/// 編碼探詢封包 <br>
+/// Encodes a probe packet
+pub fn probe_encode(seq: u8) -> Vec<u8> {
+    let mut data = mcu_const::DISCOVERY_MAGIC.to_be_bytes().to_vec();
+    data.push(mcu_const::DISCOVERY_PROBE);
+    data.push(seq);
+    data
+}
+
+/// 解析探詢封包，回傳序號 <br>
+/// Decodes a probe packet, returns its sequence number
+pub fn probe_decode(data: &[u8]) -> Option<u8> {
+    if data.len() != PROBE_LENGTH
+        || data[..4] != mcu_const::DISCOVERY_MAGIC.to_be_bytes()
+        || data[4] != mcu_const::DISCOVERY_PROBE
+    {
+        return None;
+    }
+    Some(data[5])
+}
+
+/// 能力位元轉為名稱 <br>
+/// Converts capability bits to names
+pub fn capability_names(capabilities: u16) -> Vec<String> {
+    [
+        (mcu_const::DISCOVERY_CAP_MOTOR, "motor"),
+        (mcu_const::DISCOVERY_CAP_POSITION, "position"),
+        (mcu_const::DISCOVERY_CAP_OTA, "ota"),
+        (mcu_const::DISCOVERY_CAP_TCP, "tcp"),
+    ]
+        .into_iter()
+        .filter(|(bit, _)| capabilities & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// 傳送給前端的裝置資訊 <br>
+/// Device info sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub id: u16,
+    pub name: String,
+    pub addr: SocketAddr,
+    pub tcp_addr: SocketAddr,
+    pub firmware: String,
+    pub capabilities: Vec<String>,
+    pub status: DeviceStatus,
+    pub last_seen_ms: u64,
+}
+
+struct DeviceEntry {
+    reply: DiscoveryReply,
+    addr: SocketAddr,
+    last_seen: Instant,
+    status: DeviceStatus,
+}
+impl DeviceEntry {
+    fn info(&self) -> DeviceInfo {
+        let [major, minor, patch] = self.reply.firmware;
+        DeviceInfo {
+            id: self.reply.device_id,
+            name: self.reply.name.clone(),
+            addr: self.addr,
+            tcp_addr: SocketAddr::new(self.addr.ip(), self.reply.tcp_port),
+            firmware: format!("{}.{}.{}", major, minor, patch),
+            capabilities: capability_names(self.reply.capabilities),
+            status: self.status,
+            last_seen_ms: self.last_seen.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// 裝置登錄表，以裝置 ID 為鍵 <br>
+/// Device registry keyed by device id
+pub struct DeviceRegistry {
+    devices: HashMap<u16, DeviceEntry>,
+    probe_seq: u8,
+    auto_probe: Option<ProbeTarget>,
+}
+impl DeviceRegistry {
+    /// 建立空的裝置登錄表 <br>
+    /// Creates an empty device registry
+    pub fn new() -> Self {
+        Self {
+            devices: HashMap::new(),
+            probe_seq: 0,
+            auto_probe: None,
+        }
+    }
+
+    /// 取得下一個探詢序號 <br>
+    /// Returns the next probe sequence number
+    pub fn next_seq(&mut self) -> u8 {
+        self.probe_seq = self.probe_seq.wrapping_add(1);
+        self.probe_seq
+    }
+
+    /// 設定自動探詢目標，None 為停用 <br>
+    /// Sets the auto probe target, None disables it
+    pub fn set_auto_probe(&mut self, target: Option<ProbeTarget>) {
+        self.auto_probe = target;
+    }
+
+    /// 取得自動探詢目標 <br>
+    /// Returns the auto probe target
+    pub fn auto_probe(&self) -> Option<ProbeTarget> {
+        self.auto_probe
+    }
+
+    /// 記錄裝置回覆，新裝置或位址、狀態改變時回傳 true <br>
+    /// Records a device reply, returns true for a new device or a changed address or status
+    pub fn update(&mut self, addr: SocketAddr, reply: DiscoveryReply) -> bool {
+        let now = Instant::now();
+        match self.devices.get_mut(&reply.device_id) {
+            Some(entry) => {
+                let changed = entry.addr != addr
+                    || entry.status != DeviceStatus::Online
+                    || entry.reply.firmware != reply.firmware
+                    || entry.reply.capabilities != reply.capabilities
+                    || entry.reply.tcp_port != reply.tcp_port
+                    || entry.reply.name != reply.name;
+                entry.addr = addr;
+                entry.reply = reply;
+                entry.last_seen = now;
+                entry.status = DeviceStatus::Online;
+                changed
+            }
+            None => {
+                self.devices.insert(reply.device_id, DeviceEntry {
+                    reply,
+                    addr,
+                    last_seen: now,
+                    status: DeviceStatus::Online,
+                });
+                true
+            }
+        }
+    }
+
+    /// 依最後回覆時間更新狀態，有狀態改變時回傳 true <br>
+    /// Updates statuses from the last reply time, returns true when any status changed
+    pub fn refresh(&mut self) -> bool {
+        let mut changed = false;
+        for entry in self.devices.values_mut() {
+            let age = entry.last_seen.elapsed();
+            let status = if age <= Duration::from_millis(DEVICE_STALE_MS) {
+                DeviceStatus::Online
+            } else if age <= Duration::from_millis(DEVICE_OFFLINE_MS) {
+                DeviceStatus::Stale
+            } else {
+                DeviceStatus::Offline
+            };
+            if status != entry.status {
+                info!("Device {} status: {:?}", entry.reply.device_id, status);
+                entry.status = status;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// 取得單一裝置資訊 <br>
+    /// Returns info of a single device
+    pub fn get(&self, id: u16) -> Option<DeviceInfo> {
+        self.devices.get(&id).map(DeviceEntry::info)
+    }
+
+    /// 取得所有裝置資訊，依 ID 排序 <br>
+    /// Returns info of all devices sorted by id
+    pub fn devices(&self) -> Vec<DeviceInfo> {
+        let mut devices: Vec<_> = self.devices.values().map(DeviceEntry::info).collect();
+        devices.sort_by_key(|d| d.id);
+        devices
+    }
+
+    /// 移除裝置，不存在時回傳 false <br>
+    /// Removes a device, returns false when it does not exist
+    pub fn remove(&mut self, id: u16) -> bool {
+        self.devices.remove(&id).is_some()
+    }
+
+    /// 清空登錄表 <br>
+    /// Clears the registry
+    pub fn clear(&mut self) {
+        self.devices.clear();
+    }
+}
+impl Default for DeviceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn devices_emit(app: &AppHandle, devices: &[DeviceInfo]) {
+    if let Err(e) = app.emit("wifi-devices", devices) {
+        error!("Emit wifi-devices failed: {}", e);
+    }
+}
+
+/// 取得廣播位址：各介面 IPv4 廣播位址與 255.255.255.255 <br>
+/// Returns broadcast addresses: each interface IPv4 broadcast and 255.255.255.255
+fn broadcast_addrs(port: u16) -> Vec<SocketAddr> {
+    let mut ips: HashSet<IpAddr> = HashSet::from([IpAddr::V4(Ipv4Addr::BROADCAST)]);
+    match wifi_mod::interfaces() {
+        Ok(ifaces) => ips.extend(
+            ifaces.into_iter()
+                .filter(|iface| !iface.loopback)
+                .filter_map(|iface| iface.broadcast)
+        ),
+        Err(e) => warn!("{}", e),
+    }
+    ips.into_iter().map(|ip| SocketAddr::new(ip, port)).collect()
+}
+
+/// 送出探詢封包，回傳送出的目標數；送出前先釋放 wifi_manager 鎖 <br>
+/// Sends probe packets, returns the number of targets sent to; the wifi_manager lock is released before sending
+pub async fn probe_send(app: &AppHandle, target: ProbeTarget) -> Result<usize, String> {
+    let global_state = app.state::<GlobalState>();
+    let seq = global_state.wifi_devices.lock().await.next_seq();
+    let (socket, port) = {
+        let mgr = global_state.wifi_manager.lock().await;
+        let socket = mgr.udp_socket().await
+            .map_err(|_| "Probe send failed, is the WiFi listener started?".to_string())?;
+        (socket, mgr.config().udp_port)
+    };
+    let targets = match target {
+        ProbeTarget::Broadcast => broadcast_addrs(port),
+        ProbeTarget::Unicast(addr) => vec![addr],
+    };
+    probe_send_to(&socket, seq, &targets).await
+}
+
+/// 由指定 socket 對各目標送出探詢封包，回傳送出的目標數 <br>
+/// Sends probe packets to each target through the given socket, returns the number of targets sent to
+async fn probe_send_to(socket: &UdpSocket, seq: u8, targets: &[SocketAddr]) -> Result<usize, String> {
+    let data = probe_encode(seq);
+    let mut sent = 0;
+    for addr in targets {
+        match socket.send_to(&data, addr).await {
+            Ok(_) => sent += 1,
+            Err(e) => debug!("Probe to {} failed: {}", addr, e),
+        }
+    }
+    if sent == 0 {
+        return Err("Probe send failed, no target reachable".into());
+    }
+    debug!("Probe {} sent to {} targets", seq, sent);
+    Ok(sent)
+}
+
+/// 處理 UDP 收到的探索封包，屬探索協定時回傳 true <br>
+/// Handles a discovery packet received over UDP, returns true when it belongs to the discovery protocol
+pub async fn re_pkt_discovery(app: &AppHandle, packet: &WifiPacket) -> bool {
+    let data = packet.data();
+    // 自己送出的廣播探詢也會收到，直接略過
+    // our own broadcast probes are received as well, ignore them
+    if probe_decode(&data).is_some() {
+        return true;
+    }
+    let Some(reply) = DiscoveryReply::decode(&data) else { return false; };
+    let global_state = app.state::<GlobalState>();
+    let (found, info, devices) = {
+        let mut registry = global_state.wifi_devices.lock().await;
+        let found = registry.get(reply.device_id).is_none();
+        let device_id = reply.device_id;
+        let changed = registry.update(packet.addr(), reply);
+        if !changed {
+            return true;
+        }
+        (found, registry.get(device_id), registry.devices())
+    };
+    if let (true, Some(info)) = (found, info) {
+        info!("Device found: {} ({}) at {}", info.id, info.name, info.addr);
+        if let Err(e) = app.emit("wifi-device-found", &info) {
+            error!("Emit wifi-device-found failed: {}", e);
+        }
+    }
+    devices_emit(app, &devices);
+    true
+}
+
+/// 每週期更新裝置狀態，並在啟用時自動探詢 <br>
+/// Updates device statuses every period and probes automatically when enabled
+pub async fn discovery_proccess(app: AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    let (changed, devices, auto_probe) = {
+        let mut registry = global_state.wifi_devices.lock().await;
+        let changed = registry.refresh();
+        (changed, registry.devices(), registry.auto_probe())
+    };
+    if changed {
+        devices_emit(&app, &devices);
+    }
+    if let Some(target) = auto_probe {
+        if let Err(e) = probe_send(&app, target).await {
+            debug!("Auto probe failed: {}", e);
+        }
+    }
+}
+
+/// 本機模擬裝置，回覆探詢以供測試 <br>
+/// Local stand-in device that answers probes for testing
+pub struct MockDevice {
+    addr: Option<SocketAddr>,
+    shutdown: Option<Sender<bool>>,
+}
+impl MockDevice {
+    /// 建立未啟動的模擬裝置 <br>
+    /// Creates a stopped stand-in device
+    pub fn new() -> Self {
+        Self {
+            addr: None,
+            shutdown: None,
+        }
+    }
+
+    /// 取得監聽位址 <br>
+    /// Returns the listening address
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+
+    /// 綁定 UDP 埠並開始回覆探詢，reply 的序號會被替換為探詢序號 <br>
+    /// Binds the UDP port and starts answering probes, the reply seq is replaced by the probe seq
+    pub async fn start(&mut self, bind: SocketAddr, reply: DiscoveryReply) -> Result<SocketAddr, String> {
+        if let Some(addr) = self.addr {
+            return Err(format!("Mock device already running at {}", addr));
+        }
+        let socket = UdpSocket::bind(bind).await
+            .map_err(|e| format!("Mock device bind {} failed: {}", bind, e))?;
+        let addr = socket.local_addr().map_err(|e| e.to_string())?;
+        let (shutdown_tx, mut shutdown_rx) = channel(false);
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; wifi_packet_mod::WIFI_UDP_PACKET_MAX_SIZE];
+            loop {
+                let (count, peer) = tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    result = socket.recv_from(&mut buf) => match result {
+                        Ok(pair) => pair,
+                        Err(e) => {
+                            error!("Mock device recv failed: {}", e);
+                            continue;
+                        }
+                    },
+                };
+                let Some(seq) = probe_decode(&buf[..count]) else { continue; };
+                let data = DiscoveryReply { seq, ..reply.clone() }.encode();
+                if let Err(e) = socket.send_to(&data, peer).await {
+                    error!("Mock device reply to {} failed: {}", peer, e);
+                }
+            }
+            info!("Mock device stopped");
+        });
+        self.addr = Some(addr);
+        self.shutdown = Some(shutdown_tx);
+        Ok(addr)
+    }
+
+    /// 停止模擬裝置 <br>
+    /// Stops the stand-in device
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(true);
+        }
+        self.addr = None;
+    }
+}
+impl Default for MockDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tauri 指令：送出探詢，未指定目標時廣播；auto 為 true 時每週期自動探詢 <br>
+/// Tauri command: send a probe, broadcast when no target is given; probes every period when auto is true
+#[tauri::command]
+pub async fn cmd_wifi_discover(app: AppHandle, target: Option<String>, auto: Option<bool>) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    let target = match target.filter(|s| !s.trim().is_empty()) {
+        Some(target) => {
+            let port = global_state.wifi_manager.lock().await.config().udp_port;
+            ProbeTarget::Unicast(wifi_mod::parse_target(&target, port)?)
+        }
+        None => ProbeTarget::Broadcast,
+    };
+    if let Some(auto) = auto {
+        global_state.wifi_devices.lock().await.set_auto_probe(auto.then_some(target));
+    }
+    let sent = probe_send(&app, target).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let message = format!("Probe sent to {} targets ({:?})", sent, target);
+    info!("{}", message);
+    Ok(message)
+}
+
+/// Tauri 指令：取得裝置登錄表 <br>
+/// Tauri command: get the device registry
+#[tauri::command]
+pub async fn cmd_wifi_devices(app: AppHandle) -> Result<Vec<DeviceInfo>, String> {
+    let global_state = app.state::<GlobalState>();
+    let registry = global_state.wifi_devices.lock().await;
+    Ok(registry.devices())
+}
+
+/// Tauri 指令：移除裝置，未指定 ID 時清空登錄表 <br>
+/// Tauri command: remove a device, clears the registry when no id is given
+#[tauri::command]
+pub async fn cmd_wifi_device_remove(app: AppHandle, id: Option<u16>) -> Result<Vec<DeviceInfo>, String> {
+    let global_state = app.state::<GlobalState>();
+    let devices = {
+        let mut registry = global_state.wifi_devices.lock().await;
+        match id {
+            Some(id) if !registry.remove(id) => {
+                let message = format!("Device not found: {}", id);
+                warn!("{}", message);
+                return Err(message);
+            }
+            Some(_) => {}
+            None => registry.clear(),
+        }
+        registry.devices()
+    };
+    devices_emit(&app, &devices);
+    Ok(devices)
+}
+
+/// Tauri 指令：啟動本機模擬裝置，回傳其位址 <br>
+/// Tauri command: start the local stand-in device, returns its address
+#[tauri::command]
+pub async fn cmd_wifi_mock_device_start(app: AppHandle, port: Option<u16>, device_id: Option<u16>) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    let tcp_port = global_state.wifi_manager.lock().await.config().tcp_port;
+    let reply = DiscoveryReply {
+        seq: 0,
+        device_id: device_id.unwrap_or(1),
+        firmware: [0, 1, 0],
+        capabilities: mcu_const::DISCOVERY_CAP_MOTOR | mcu_const::DISCOVERY_CAP_TCP,
+        tcp_port,
+        name: "mock-device".into(),
+    };
+    let bind = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port.unwrap_or(0));
+    let mut mock = global_state.wifi_mock_device.lock().await;
+    let addr = mock.start(bind, reply).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), addr.port());
+    info!("Mock device listening at {}", addr);
+    Ok(addr.to_string())
+}
+
+/// Tauri 指令：停止本機模擬裝置 <br>
+/// Tauri command: stop the local stand-in device
+#[tauri::command]
+pub async fn cmd_wifi_mock_device_stop(app: AppHandle) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    global_state.wifi_mock_device.lock().await.stop();
+    Ok("Mock device stopped".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply() -> DiscoveryReply {
+        DiscoveryReply {
+            seq: 0,
+            device_id: 0x1234,
+            firmware: [1, 2, 3],
+            capabilities: mcu_const::DISCOVERY_CAP_TCP,
+            tcp_port: 60000,
+            name: "mock".into(),
+        }
+    }
+
+    #[test]
+    fn reply_round_trip() {
+        let reply = DiscoveryReply { seq: 7, ..reply() };
+        assert_eq!(DiscoveryReply::decode(&reply.encode()), Some(reply));
+        assert_eq!(probe_decode(&probe_encode(9)), Some(9));
+        assert_eq!(DiscoveryReply::decode(&probe_encode(9)), None);
+    }
+
+    #[tokio::test]
+    async fn mock_device_answers_probe() {
+        let loopback = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+        let mut mock = MockDevice::new();
+        let mock_addr = mock.start(loopback, reply()).await.unwrap();
+        let socket = UdpSocket::bind(loopback).await.unwrap();
+
+        assert_eq!(probe_send_to(&socket, 42, &[mock_addr]).await, Ok(1));
+        let mut buf = vec![0u8; wifi_packet_mod::WIFI_UDP_PACKET_MAX_SIZE];
+        let (count, from) = tokio::time::timeout(Duration::from_secs(2), socket.recv_from(&mut buf))
+            .await
+            .expect("no reply from mock device")
+            .unwrap();
+        assert_eq!(from, mock_addr);
+        assert_eq!(DiscoveryReply::decode(&buf[..count]), Some(DiscoveryReply { seq: 42, ..reply() }));
+        mock.stop();
+    }
+}
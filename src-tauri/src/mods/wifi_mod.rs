@@ -1,196 +1,302 @@
-use std::{error::Error, net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket as StdUdpSocket}, sync::Arc, time::Duration};
-use log::{debug, error, info};
-use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream, UdpSocket}, sync::{watch::{channel, Receiver, Sender}, Mutex}, time::sleep};
-use tauri::{AppHandle, Manager};
-use crate::{GlobalState, mods::wifi_packet_mod::{self, WifiPacket}};
+use std::{error::Error, net::{IpAddr, Ipv4Addr, SocketAddr}, sync::Arc, time::{Duration, Instant}};
+use if_addrs::{get_if_addrs, IfAddr};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::{net::{TcpListener, UdpSocket}, sync::{watch::{channel, Receiver, Sender}, Mutex}, task::JoinHandle, time::{sleep, timeout}};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{GlobalState, mods::{wifi_discovery_mod, wifi_packet_mod::{self, WifiPacket}, wifi_packet_proc_mod, wifi_tcp_mod::{TcpPeerInfo, WifiTcpConns}}};
 
-const TARGET_IP: IpAddr   = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 20));
-const TCP_PORT: u16 = 60000;
-const UDP_PORT: u16 = 60001;
+/// 預設綁定位址與埠號 <br>
+/// Default bind address and ports
+pub const DEFAULT_BIND_IP: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+pub const DEFAULT_TCP_PORT: u16 = 60000;
+pub const DEFAULT_UDP_PORT: u16 = 60001;
 
 const   MAX_RECEIVE_BUFFER_SIZE: usize = wifi_packet_mod::WIFI_TCP_PACKET_MAX_SIZE;
 
+/// WiFi 綁定位址與埠號設定 <br>
+/// WiFi bind address and port configuration
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct WifiConfig {
+    pub bind_ip: IpAddr,
+    pub tcp_port: u16,
+    pub udp_port: u16,
+}
+impl Default for WifiConfig {
+    fn default() -> Self {
+        Self {
+            bind_ip: DEFAULT_BIND_IP,
+            tcp_port: DEFAULT_TCP_PORT,
+            udp_port: DEFAULT_UDP_PORT,
+        }
+    }
+}
+
+/// 本機網路介面資訊 <br>
+/// Local network interface info
+#[derive(Debug, Clone, Serialize)]
+pub struct WifiInterface {
+    pub name: String,
+    pub ip: IpAddr,
+    pub broadcast: Option<IpAddr>,
+    pub loopback: bool,
+}
+
+/// 列舉本機網路介面 <br>
+/// Lists local network interfaces
+pub fn interfaces() -> Result<Vec<WifiInterface>, String> {
+    let ifaces = get_if_addrs().map_err(|e| format!("Get interfaces failed: {}", e))?;
+    Ok(ifaces.into_iter().map(|iface| WifiInterface {
+        ip: iface.ip(),
+        broadcast: match &iface.addr {
+            IfAddr::V4(v4) => v4.broadcast.map(IpAddr::V4),
+            IfAddr::V6(_) => None,
+        },
+        loopback: iface.is_loopback(),
+        name: iface.name,
+    }).collect())
+}
+
+/// 依介面名稱取得其 IPv4 位址 <br>
+/// Returns the IPv4 address of the named interface
+pub fn interface_ip(name: &str) -> Result<IpAddr, String> {
+    interfaces()?.into_iter()
+        .filter(|iface| iface.name == name)
+        .map(|iface| iface.ip)
+        .find(|ip| ip.is_ipv4())
+        .ok_or_else(|| format!("Interface not found or has no IPv4 address: {}", name))
+}
+
+/// 解析目標位址，可為 `ip` 或 `ip:port`，未指定埠號時使用預設值 <br>
+/// Parses a target address as `ip` or `ip:port`, using the default port when omitted
+pub fn parse_target(target: &str, default_port: u16) -> Result<SocketAddr, String> {
+    let target = target.trim();
+    if let Ok(addr) = target.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    target.parse::<IpAddr>()
+        .map(|ip| SocketAddr::new(ip, default_port))
+        .map_err(|e| format!("Invalid target address {}: {}", target, e))
+}
+
+/// 停止時等待背景 task 結束的上限（ms） <br>
+/// Maximum wait for background tasks to finish on stop (ms)
+const STOP_TIMEOUT_MS: u64 = 1000;
+
+/// WiFi 監聽狀態 <br>
+/// WiFi listener status
+#[derive(Debug, Clone, Serialize)]
+pub struct WifiStatus {
+    pub running: bool,
+    pub config: WifiConfig,
+    pub tcp_addr: Option<SocketAddr>,
+    pub udp_addr: Option<SocketAddr>,
+    pub uptime_secs: Option<u64>,
+    pub peers: Vec<TcpPeerInfo>,
+}
+
 pub struct WifiAsyncManager {
-    device_ip: IpAddr,
+    config: WifiConfig,
     inner: Arc<WifiAsyncManagerInner>,
     shutdown: Option<Sender<bool>>,
+    tasks: Vec<JoinHandle<()>>,
+    local_addrs: Option<(SocketAddr, SocketAddr)>,
+    started_at: Option<Instant>,
 }
 impl WifiAsyncManager {
     pub fn new() -> Self {
-        let mut device_ip: IpAddr = "0.0.0.0".parse()
-            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
-        if let Ok(sock) = StdUdpSocket::bind("0.0.0.0:0") {
-            let _ = sock.connect("8.8.8.8:80");
-            if let Ok(addr) = sock.local_addr() {
-                device_ip = addr.ip();
-            }
-        }
         Self {
-            device_ip,
-            inner: Arc::new(WifiAsyncManagerInner::new(device_ip)),
+            config: WifiConfig::default(),
+            inner: Arc::new(WifiAsyncManagerInner::new()),
             shutdown: None,
+            tasks: Vec::new(),
+            local_addrs: None,
+            started_at: None,
         }
     }
 
-    pub async fn start(&mut self, app: AppHandle) -> Result<(), String> {
+    /// 取得目前設定 <br>
+    /// Returns the current configuration
+    pub fn config(&self) -> WifiConfig {
+        self.config
+    }
+
+    /// 綁定埠號並啟動背景 task，已啟動時回傳錯誤 <br>
+    /// Binds the ports and starts background tasks, errors when already started
+    pub async fn start(&mut self, app: AppHandle, config: WifiConfig) -> Result<(), String> {
+        if let Some((tcp_addr, udp_addr)) = self.local_addrs.filter(|_| self.is_started()) {
+            return Err(format!("WiFi listener already running: TCP {}, UDP {}", tcp_addr, udp_addr));
+        }
+        let addr_tcp = SocketAddr::new(config.bind_ip, config.tcp_port);
+        let addr_udp = SocketAddr::new(config.bind_ip, config.udp_port);
+        let tcp_listener = TcpListener::bind(addr_tcp).await
+            .map_err(|e| format!("TCP bind {} failed: {}", addr_tcp, e))?;
+        let udp_socket = UdpSocket::bind(addr_udp).await
+            .map_err(|e| format!("UDP bind {} failed: {}", addr_udp, e))?;
+        udp_socket.set_broadcast(true)
+            .map_err(|e| format!("UDP set broadcast failed: {}", e))?;
+        let local_tcp = tcp_listener.local_addr().map_err(|e| e.to_string())?;
+        let local_udp = udp_socket.local_addr().map_err(|e| e.to_string())?;
+        *self.inner.udp_socket.lock().await = Some(Arc::new(udp_socket));
+        self.config = config;
+        self.local_addrs = Some((local_tcp, local_udp));
+        self.started_at = Some(Instant::now());
+
+        // 清除啟動前殘留的待送封包 / Drop packets queued before the start
+        let global_state = app.state::<GlobalState>();
+        let dropped = global_state.wifi_tcp_transmit_buffer.lock().await.take_all().len()
+            + global_state.wifi_udp_transmit_buffer.lock().await.take_all().len();
+        if dropped > 0 {
+            info!("Dropped {} stale WiFi packets", dropped);
+        }
+
         // 清除停止旗標
         let (shutdown_tx, shutdown_rx) = channel(false);
         self.shutdown.replace(shutdown_tx);
 
-        let (addr_tcp, addr_udp) = (
-            format!("{}:{}", self.device_ip, TCP_PORT),
-            format!("{}:{}", self.device_ip, UDP_PORT)
-        );
-        let tcp_listener = TcpListener::bind(&addr_tcp).await
-            .map_err(|e| format!("TCP bind failed: {}", e))?;
-        *self.inner.tcp_listener.lock().await = Some(tcp_listener);
-
-        let udp_socket = UdpSocket::bind(&addr_udp).await
-            .map_err(|e| format!("UDP bind failed: {}", e))?;
-        *self.inner.udp_socket.lock().await = Some(udp_socket);
-
         // 啟動背景 task
-        self.inner.tcp_read_start(app.clone(), shutdown_rx.clone());
-        self.inner.udp_read_start(app.clone(), shutdown_rx.clone());
-        self.inner.tcp_write_spawn(app.clone(), shutdown_rx.clone());
-        self.inner.udp_write_spawn(app, shutdown_rx);
+        self.tasks = vec![
+            self.inner.tcp_conns.start(app.clone(), tcp_listener, shutdown_rx.clone()).await,
+            self.inner.udp_read_start(app.clone(), shutdown_rx.clone()),
+            self.inner.tcp_write_spawn(app.clone(), shutdown_rx.clone()),
+            self.inner.udp_write_spawn(app, shutdown_rx),
+        ];
         Ok(())
     }
 
-    /// 設定停止旗標並等所有 task 結束
-    pub async fn stop(&mut self) -> Result<(), String> {
-        if let Some(tx) = self.shutdown.take() {
-            let _ = tx.send(true);
-            // 若要 await JoinHandle，可再設計成儲存 handles
+    /// 設定停止旗標並等所有 task 結束，未啟動時不做任何事 <br>
+    /// Sets the shutdown flag and waits for all tasks to finish, does nothing when not started
+    pub async fn stop(&mut self, app: AppHandle) -> Result<(), String> {
+        let Some(tx) = self.shutdown.take() else {
+            return Ok(());
+        };
+        let _ = tx.send(true);
+        for task in self.tasks.drain(..) {
+            if timeout(Duration::from_millis(STOP_TIMEOUT_MS), task).await.is_err() {
+                warn!("WiFi task did not stop in {} ms", STOP_TIMEOUT_MS);
+            }
         }
+        *self.inner.udp_socket.lock().await = None;
+        self.inner.tcp_conns.clear(&app).await;
+        self.local_addrs = None;
+        self.started_at = None;
         Ok(())
     }
+
+    /// 取得目前狀態 <br>
+    /// Returns the current status
+    pub async fn status(&self) -> WifiStatus {
+        WifiStatus {
+            running: self.is_started(),
+            config: self.config,
+            tcp_addr: self.local_addrs.map(|(tcp, _)| tcp),
+            udp_addr: self.local_addrs.map(|(_, udp)| udp),
+            uptime_secs: self.started_at.map(|t| t.elapsed().as_secs()),
+            peers: self.peers().await,
+        }
+    }
+
+    /// 是否已啟動 <br>
+    /// Returns whether the listener is started
+    pub fn is_started(&self) -> bool {
+        self.shutdown.is_some()
+    }
+
+    /// 取得 UDP socket，供呼叫端在釋放管理器鎖後直接收發 <br>
+    /// Returns the UDP socket so callers can use it after releasing the manager lock
+    pub async fn udp_socket(&self) -> Result<Arc<UdpSocket>, String> {
+        self.inner.udp_socket().await.map_err(|e| e.to_string())
+    }
+
+    /// 取得 TCP 連線集合，供呼叫端在釋放管理器鎖後直接收發 <br>
+    /// Returns the TCP sessions so callers can use them after releasing the manager lock
+    pub fn tcp_conns(&self) -> Arc<WifiTcpConns> {
+        Arc::clone(&self.inner.tcp_conns)
+    }
+
+    /// 立即由 UDP socket 送出封包 <br>
+    /// Sends a packet through the UDP socket immediately
+    pub async fn udp_send(&self, packet: WifiPacket) -> Result<(), String> {
+        self.inner.udp_write(packet).await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 取得目前 TCP 連線資訊 <br>
+    /// Returns info of current TCP connections
+    pub async fn peers(&self) -> Vec<TcpPeerInfo> {
+        self.inner.tcp_conns.peers().await
+    }
+
+    /// 中斷指定 TCP 連線 <br>
+    /// Disconnects the given TCP peer
+    pub async fn disconnect(&self, app: &AppHandle, peer: SocketAddr) -> Result<(), String> {
+        self.inner.tcp_conns.disconnect(app, peer).await
+    }
 }
 struct WifiAsyncManagerInner {
-    device_ip: IpAddr,
-    tcp_listener: Mutex<Option<TcpListener>>,
-    udp_socket: Mutex<Option<UdpSocket>>,
+    tcp_conns: Arc<WifiTcpConns>,
+    udp_socket: Mutex<Option<Arc<UdpSocket>>>,
 }
 impl WifiAsyncManagerInner {
-    fn new(device_ip: IpAddr) -> Self {
+    fn new() -> Self {
         Self {
-            device_ip,
-            tcp_listener: Mutex::new(None),
+            tcp_conns:    Arc::new(WifiTcpConns::new()),
             udp_socket:   Mutex::new(None),
         }
     }
 
-    async fn tcp_read(&self) -> Result<WifiPacket, Box<dyn Error + Send + Sync>> {
-        let mut guard = self.tcp_listener.lock().await;
-        let listener = if let Some(lsn) = guard.as_mut() {
-            lsn
-        } else {
-            return Err("UDP listener not initialized".into());
-        };
-        let (mut stream, peer) = if let Ok(pair) = listener.accept().await {
-            pair
-        } else {
-            return Err("TCP accept failed".into());
-        };
-        let mut buf = vec![0u8; MAX_RECEIVE_BUFFER_SIZE];
-        let count = if let Ok(n) = stream.read(&mut buf).await {
-            n
-        } else {
-            return Err("TCP read error".into());
-        };
-        let packet = WifiPacket::new(self.device_ip, &buf[..count])?;
-        debug!("TCP got {} bytes from {}: {:?}", count, peer, &buf[..count]);
-        Ok(packet)
+    /// 取得 UDP socket，接收與傳送可同時進行，不需持有鎖 <br>
+    /// Returns the UDP socket, receive and send can run concurrently without holding the lock
+    async fn udp_socket(&self) -> Result<Arc<UdpSocket>, Box<dyn Error + Send + Sync>> {
+        self.udp_socket.lock().await.clone()
+            .ok_or_else(|| "UDP socket not initialized".into())
     }
 
-    /// spawn TCP 接收迴圈
-    fn tcp_read_start(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) {
+    /// spawn UDP 接收迴圈，探索封包直接處理，其餘存入 UDP 接收緩衝 <br>
+    /// Spawns the UDP receive loop, discovery packets are handled directly, others go to the UDP receive buffer
+    fn udp_read_start(self: &Arc<Self>, app: AppHandle, mut shutdown: Receiver<bool>) -> JoinHandle<()> {
         let arc_handle = Arc::clone(self);
         let app_handle = app.clone();
         tokio::spawn(async move {
-            loop {
-                if *shutdown.borrow() { break; }
-                let read_result = arc_handle.tcp_read().await;
-                if let Err(ref e) = read_result {
+            let socket = match arc_handle.udp_socket().await {
+                Ok(socket) => socket,
+                Err(e) => {
                     error!("{}", e);
-                    sleep(Duration::from_millis(10)).await;
-                    continue;
+                    return;
                 }
-                let packet = read_result.unwrap();
-                info!("Wifi tcp read succeed:\n{}", packet.show());
-                let global_state = app_handle.state::<GlobalState>();
-                let mut state_buffer = global_state.wifi_tcp_receive_buffer.lock().await;
-                if let Err(e) = state_buffer.push(packet) {
-                    error!("Packet store failed: {}", e);
-                    continue;
-                }
-            }
-        });
-    }
-
-    async fn udp_read(self: &Arc<Self>) -> Result<WifiPacket, Box<dyn Error + Send + Sync>> {
-        let mut guard = self.udp_socket.lock().await;
-        let socket = if let Some(skt) = guard.as_mut() {
-            skt
-        } else {
-            return Err("UDP socket not initialized".into());
-        };
-        let mut buf = vec![0u8; MAX_RECEIVE_BUFFER_SIZE];
-        let (count, peer) = if let Ok(pair) = socket.recv_from(&mut buf).await {
-            pair
-        } else {
-            return Err("UDP recv_from failed".into());
-        };
-        let packet = WifiPacket::new(self.device_ip, &buf[..count])?;
-        debug!("UDP got {} bytes from {}: {:?}", count, peer, &buf[..count]);
-        Ok(packet)
-    }
-
-    /// spawn UDP 接收迴圈
-    fn udp_read_start(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) {
-        let arc_handle = Arc::clone(self);
-        let app_handle = app.clone();
-        tokio::spawn(async move {
+            };
+            let mut buf = vec![0u8; MAX_RECEIVE_BUFFER_SIZE];
             loop {
-                if *shutdown.borrow() { break; }
-                let read_result = arc_handle.udp_read().await;
-                if let Err(ref e) = read_result {
-                    error!("{}", e);
-                    sleep(Duration::from_millis(10)).await;
-                    continue;
-                }
-                let packet = read_result.unwrap();
-                info!("Wifi udp read succeed:\n{}", packet.show());
-                let global_state = app_handle.state::<GlobalState>();
-                let mut state_buffer = global_state.wifi_tcp_receive_buffer.lock().await;
-                if let Err(e) = state_buffer.push(packet) {
-                    // error!("Packet store failed: {}", e);
+                let (count, peer) = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    result = socket.recv_from(&mut buf) => match result {
+                        Ok(pair) => pair,
+                        Err(e) => {
+                            error!("UDP recv_from failed: {}", e);
+                            sleep(Duration::from_millis(10)).await;
+                            continue;
+                        }
+                    },
+                };
+                let packet = match WifiPacket::new(peer, &buf[..count]) {
+                    Ok(packet) => packet,
+                    Err(e) => {
+                        error!("{}", e);
+                        continue;
+                    }
+                };
+                debug!("UDP got {} bytes from {}", count, peer);
+                if wifi_discovery_mod::re_pkt_discovery(&app_handle, &packet).await {
                     continue;
                 }
+                wifi_packet_proc_mod::re_pkt_store(&app_handle, WifiProtocol::Udp, packet).await;
             }
-        });
-    }
-
-    async fn tcp_write(&self, packet: WifiPacket) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let (target, data) =
-            (SocketAddr::new(packet.target_ip(), TCP_PORT), packet.data());
-        let connect = TcpStream::connect(target).await;
-        if let Err(e) = connect {
-            let message = format!("TCP connect failed: {}", e);
-            return Err(message.into());
-        }
-        let mut stream = connect.unwrap();
-        if let Err(e) = stream.write_all(&data).await {
-            let message = format!("TCP write failed: {}", e);
-            return Err(message.into());
-        }
-        let message = format!("TCP sent {} bytes to {}", data.len(), target);
-        debug!("{}", message);
-        Ok(())
+        })
     }
 
     // spawn TCP 寫出迴圈，定時從 AppHandle 取得要發送的資料
-    fn tcp_write_spawn(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) {
+    fn tcp_write_spawn(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) -> JoinHandle<()> {
         let arc_handle = Arc::clone(self);
         let app_handle = app.clone();
         tokio::spawn(async move {
@@ -207,27 +313,23 @@ impl WifiAsyncManagerInner {
                     sleep(Duration::from_millis(10)).await;
                     continue;
                 };
-                if let Err(e) = arc_handle.tcp_write(packet.clone()).await {
+                if let Err(e) = arc_handle.tcp_conns.send(&app_handle, packet.clone()).await {
                     error!("TCP write failed: {}", e);
                     continue;
                 }
                 debug!("TCP write succeed:\n{}", packet.show());
             }
-        });
+        })
     }
 
     async fn udp_write(&self, packet: WifiPacket) -> Result<usize, Box<dyn Error + Send + Sync>> {
-        let guard = self.udp_socket.lock().await;
-        if let Some(socket) = guard.as_ref() {
-            let (target, data) = (SocketAddr::new(packet.target_ip(), UDP_PORT), packet.data());
-            socket.send_to(&data, target).await
-                .map_err(|e| e.into())
-        } else {
-            Err("UDP socket not initialized".into())
-        }
+        let socket = self.udp_socket().await?;
+        let (target, data) = (packet.addr(), packet.data());
+        socket.send_to(&data, target).await
+            .map_err(|e| e.into())
     }
 
-    fn udp_write_spawn(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) {
+    fn udp_write_spawn(self: &Arc<Self>, app: AppHandle, shutdown: Receiver<bool>) -> JoinHandle<()> {
         let arc = Arc::clone(self);
         let app_handle = app.clone();
         tokio::spawn(async move {
@@ -248,32 +350,183 @@ impl WifiAsyncManagerInner {
                     error!("UDP send failed: {}", e);
                 }
             }
-        });
+        })
     }
 }
 
+/// 傳輸協定 <br>
+/// Transport protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WifiProtocol {
+    Tcp,
+    Udp,
+}
+
+/// Tauri 指令：列出本機網路介面 <br>
+/// Tauri command: list local network interfaces
 #[tauri::command]
-pub async fn cmd_wifi_start(app: AppHandle) -> Result<String, String> {
+pub async fn cmd_wifi_interfaces() -> Result<Vec<WifiInterface>, String> {
+    interfaces().map_err(|e| {
+        error!("{}", e);
+        e
+    })
+}
+
+/// 由前端參數建立設定，未指定者使用預設值 <br>
+/// Builds the configuration from frontend arguments, using defaults for omitted values
+fn config_parse(
+    bind_addr: Option<String>,
+    interface: Option<String>,
+    tcp_port: Option<u16>,
+    udp_port: Option<u16>,
+) -> Result<WifiConfig, String> {
+    let bind_ip = match (bind_addr.filter(|s| !s.trim().is_empty()), interface.filter(|s| !s.is_empty())) {
+        (Some(addr), _) => addr.trim().parse::<IpAddr>()
+            .map_err(|e| format!("Invalid bind address {}: {}", addr, e))?,
+        (None, Some(name)) => interface_ip(&name)?,
+        (None, None) => DEFAULT_BIND_IP,
+    };
+    Ok(WifiConfig {
+        bind_ip,
+        tcp_port: tcp_port.unwrap_or(DEFAULT_TCP_PORT),
+        udp_port: udp_port.unwrap_or(DEFAULT_UDP_PORT),
+    })
+}
+
+/// 發出 `wifi-status` 事件 <br>
+/// Emits the `wifi-status` event
+fn status_emit(app: &AppHandle, status: &WifiStatus) {
+    if let Err(e) = app.emit("wifi-status", status) {
+        error!("Emit wifi-status failed: {}", e);
+    }
+}
+
+/// Tauri 指令：啟動 WiFi 監聽，可指定綁定位址或介面與埠號；已啟動時回傳錯誤 <br>
+/// Tauri command: start the WiFi listener with an optional bind address or interface and ports; errors when already running
+#[tauri::command]
+pub async fn cmd_wifi_start(
+    app: AppHandle,
+    bind_addr: Option<String>,
+    interface: Option<String>,
+    tcp_port: Option<u16>,
+    udp_port: Option<u16>,
+) -> Result<WifiStatus, String> {
+    let config = config_parse(bind_addr, interface, tcp_port, udp_port)?;
     let global_state = app.state::<GlobalState>();
     let mut mgr = global_state.wifi_manager.lock().await;
-    mgr.start(app.clone()).await.map_err(|e| e)?;
-    Ok("WiFi listener started".into())
+    mgr.start(app.clone(), config).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let status = mgr.status().await;
+    info!("WiFi listener started: TCP {:?}, UDP {:?}", status.tcp_addr, status.udp_addr);
+    status_emit(&app, &status);
+    Ok(status)
 }
 
+/// Tauri 指令：停止 WiFi 監聽，未啟動時直接回傳 <br>
+/// Tauri command: stop the WiFi listener, returns directly when not running
 #[tauri::command]
-pub async fn cmd_wifi_transmie(app: AppHandle) -> Result<String, String> {
+pub async fn cmd_wifi_stop(app: AppHandle) -> Result<WifiStatus, String> {
     let global_state = app.state::<GlobalState>();
-    let packet = WifiPacket::new(TARGET_IP, "Hello from rust udp").unwrap();
-    let mut buffer = global_state.wifi_udp_transmit_buffer.lock().await;
-    let _ = buffer.push(packet);
-    info!("push fin");
-    Ok("Suc".into())
+    let mut mgr = global_state.wifi_manager.lock().await;
+    let running = mgr.is_started();
+    mgr.stop(app.clone()).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let status = mgr.status().await;
+    if running {
+        info!("WiFi listener stopped");
+        status_emit(&app, &status);
+    }
+    Ok(status)
 }
 
+/// Tauri 指令：重新啟動 WiFi 監聽，未指定參數時沿用目前設定 <br>
+/// Tauri command: restart the WiFi listener, keeping the current configuration when no arguments are given
 #[tauri::command]
-pub async fn cmd_wifi_stop(app: AppHandle) -> Result<String, String> {
+pub async fn cmd_wifi_restart(
+    app: AppHandle,
+    bind_addr: Option<String>,
+    interface: Option<String>,
+    tcp_port: Option<u16>,
+    udp_port: Option<u16>,
+) -> Result<WifiStatus, String> {
     let global_state = app.state::<GlobalState>();
     let mut mgr = global_state.wifi_manager.lock().await;
-    mgr.stop().await.map_err(|e| e)?;
-    Ok("WiFi listener stopped".into())
+    let config = if bind_addr.is_none() && interface.is_none() && tcp_port.is_none() && udp_port.is_none() {
+        mgr.config()
+    } else {
+        config_parse(bind_addr, interface, tcp_port, udp_port)?
+    };
+    mgr.stop(app.clone()).await?;
+    let result = mgr.start(app.clone(), config).await;
+    let status = mgr.status().await;
+    status_emit(&app, &status);
+    result.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    info!("WiFi listener restarted: TCP {:?}, UDP {:?}", status.tcp_addr, status.udp_addr);
+    Ok(status)
+}
+
+/// Tauri 指令：取得 WiFi 監聽狀態、位址與連線 <br>
+/// Tauri command: get the WiFi listener status, addresses and peers
+#[tauri::command]
+pub async fn cmd_wifi_status(app: AppHandle) -> Result<WifiStatus, String> {
+    let global_state = app.state::<GlobalState>();
+    let mgr = global_state.wifi_manager.lock().await;
+    Ok(mgr.status().await)
+}
+
+/// Tauri 指令：傳送資料到指定目標 <br>
+/// Tauri command: transmit data to the given target
+#[tauri::command]
+pub async fn cmd_wifi_transmie(app: AppHandle, target: String, protocol: WifiProtocol, data: String) -> Result<String, String> {
+    let global_state = app.state::<GlobalState>();
+    let config = global_state.wifi_manager.lock().await.config();
+    let default_port = match protocol {
+        WifiProtocol::Tcp => config.tcp_port,
+        WifiProtocol::Udp => config.udp_port,
+    };
+    let addr = parse_target(&target, default_port)?;
+    let packet = WifiPacket::new(addr, data.as_bytes()).map_err(|e| e.to_string())?;
+    let mut buffer = match protocol {
+        WifiProtocol::Tcp => global_state.wifi_tcp_transmit_buffer.lock().await,
+        WifiProtocol::Udp => global_state.wifi_udp_transmit_buffer.lock().await,
+    };
+    buffer.push(packet).map_err(|e| {
+        let message = format!("Packet store failed: {}", e);
+        error!("{}", message);
+        message
+    })?;
+    let message = format!("Queued {} bytes to {} ({:?})", data.len(), addr, protocol);
+    info!("{}", message);
+    Ok(message)
+}
+
+/// Tauri 指令：取得目前 TCP 連線清單 <br>
+/// Tauri command: list current TCP connections
+#[tauri::command]
+pub async fn cmd_wifi_peers(app: AppHandle) -> Result<Vec<TcpPeerInfo>, String> {
+    let global_state = app.state::<GlobalState>();
+    let mgr = global_state.wifi_manager.lock().await;
+    Ok(mgr.peers().await)
+}
+
+/// Tauri 指令：中斷指定 TCP 連線並停止重連 <br>
+/// Tauri command: disconnect a TCP peer and stop reconnecting
+#[tauri::command]
+pub async fn cmd_wifi_disconnect(app: AppHandle, peer: String) -> Result<String, String> {
+    let peer: SocketAddr = peer.parse().map_err(|e| format!("Invalid peer address {}: {}", peer, e))?;
+    let global_state = app.state::<GlobalState>();
+    let mgr = global_state.wifi_manager.lock().await;
+    mgr.disconnect(&app, peer).await.map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    Ok(format!("TCP peer disconnected: {}", peer))
 }
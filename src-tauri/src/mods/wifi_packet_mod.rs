@@ -1,28 +1,134 @@
-use std::{error::Error, mem, net::IpAddr};
+use std::{error::Error, mem, net::SocketAddr};
 
 use log::{debug, info};
 
-use crate::mods::user_vec_mod::UserVecU8;
+use crate::mods::{mcu_const, user_vec_mod::UserVecU8};
 
 pub const WIFI_TCP_PACKET_MAX_SIZE: usize = 1024;
 pub const WIFI_UDP_PACKET_MAX_SIZE: usize = 1024;
+pub const WIFI_FRAME_PAYLOAD_MAX_SIZE: usize = WIFI_UDP_PACKET_MAX_SIZE - mcu_const::WIFI_FRAME_HEADER_SIZE as usize;
+
+/// WiFi 訊框，標頭見 `mcu_const::WIFI_FRAME_HEADER_SIZE`<br>
+/// WiFi frame, see `mcu_const::WIFI_FRAME_HEADER_SIZE` for the header layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiFrame {
+    device_id: u16,         // 裝置 ID / device id
+    seq: u16,               // 序號 / sequence number
+    frame_type: u8,         // 訊框種類 / frame type
+    payload: Vec<u8>,       // 資料 / payload
+}
+impl WifiFrame {
+    /// 建立訊框，資料長度不可超過封包上限<br>
+    /// Creates a frame, the payload must fit in a packet
+    pub fn new<T: AsRef<[u8]>>(device_id: u16, seq: u16, frame_type: u8, payload: T) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let payload = payload.as_ref().to_vec();
+        if payload.len() > WIFI_FRAME_PAYLOAD_MAX_SIZE {
+            let message = format!("Frame payload too long (max {}): {}", WIFI_FRAME_PAYLOAD_MAX_SIZE, payload.len());
+            return Err(message.into());
+        }
+        Ok(Self { device_id, seq, frame_type, payload })
+    }
+
+    /// 取得裝置 ID<br>
+    /// Returns the device id
+    pub fn device_id(&self) -> u16 { self.device_id }
+
+    /// 取得序號<br>
+    /// Returns the sequence number
+    pub fn seq(&self) -> u16 { self.seq }
+
+    /// 取得訊框種類<br>
+    /// Returns the frame type
+    pub fn frame_type(&self) -> u8 { self.frame_type }
+
+    /// 取得資料<br>
+    /// Returns the payload
+    pub fn payload(&self) -> &[u8] { &self.payload }
+
+    /// 計算檢查碼：檢查碼欄位為 0 時，標頭與資料所有位元組之 16 位元累加<br>
+    /// Computes the checksum: 16-bit sum of all header and payload bytes with the checksum field zeroed
+    fn checksum(header: &[u8], payload: &[u8]) -> u16 {
+        header.iter().chain(payload)
+            .fold(0u16, |sum, b| sum.wrapping_add(*b as u16))
+    }
+
+    /// 從原始資料解析訊框並檢驗長度與檢查碼<br>
+    /// Parses raw data into a frame and validates length and checksum
+    pub fn pack(data: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let header_size = mcu_const::WIFI_FRAME_HEADER_SIZE as usize;
+        if data.len() < header_size {
+            let message = format!("Frame too short (min {}): {}", header_size, data.len());
+            return Err(message.into());
+        }
+        let length = u16::from_be_bytes([data[5], data[6]]) as usize;
+        if data.len() != header_size + length {
+            let message = format!("Frame length invalid (expected {}): {}", header_size + length, data.len());
+            return Err(message.into());
+        }
+        let mut header = data[..header_size].to_vec();
+        let checksum = u16::from_be_bytes([header[7], header[8]]);
+        header[7] = 0;
+        header[8] = 0;
+        let payload = &data[header_size..];
+        let expected = Self::checksum(&header, payload);
+        if checksum != expected {
+            let message = format!("Frame checksum invalid (expected {:#06x}): {:#06x}", expected, checksum);
+            return Err(message.into());
+        }
+        Self::new(
+            u16::from_be_bytes([data[0], data[1]]),
+            u16::from_be_bytes([data[2], data[3]]),
+            data[4],
+            payload,
+        )
+    }
+
+    /// 將訊框序列化為位元組向量<br>
+    /// Serializes the frame into a byte vector
+    pub fn unpack(&self) -> Vec<u8> {
+        let mut buffer = Vec::<u8>::with_capacity(mcu_const::WIFI_FRAME_HEADER_SIZE as usize + self.payload.len());
+        buffer.extend_from_slice(&self.device_id.to_be_bytes());
+        buffer.extend_from_slice(&self.seq.to_be_bytes());
+        buffer.push(self.frame_type);
+        buffer.extend_from_slice(&(self.payload.len() as u16).to_be_bytes());
+        buffer.extend_from_slice(&[0, 0]);
+        let checksum = Self::checksum(&buffer, &self.payload);
+        buffer[7..9].copy_from_slice(&checksum.to_be_bytes());
+        buffer.extend_from_slice(&self.payload);
+        buffer
+    }
+
+    /// 顯示訊框內容為字串<br>
+    /// Formats frame contents into a string for display
+    pub fn show(&self) -> String {
+        format!(
+            ">>> dev {} seq {} type {:#04x}\n>>>   {:?}",
+            self.device_id,
+            self.seq,
+            self.frame_type,
+            self.payload,
+        )
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct WifiPacket {
-    target_ip: IpAddr,
+    addr: SocketAddr,       // 傳送目標或接收來源 / transmit target or receive source
     data: UserVecU8,
 }
 impl WifiPacket {
-    pub fn new<T: AsRef<[u8]>>(target_ip: IpAddr, data: T) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new<T: AsRef<[u8]>>(addr: SocketAddr, data: T) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let mut user_vec = UserVecU8::new(WIFI_TCP_PACKET_MAX_SIZE);
         user_vec.extend(data)?;
         Ok( Self {
-            target_ip,
+            addr,
             data: user_vec,
         })
     }
 
-    pub fn target_ip(&self) -> IpAddr { self.target_ip.clone() }
+    /// 取得傳送目標或接收來源位址<br>
+    /// Returns the transmit target or receive source address
+    pub fn addr(&self) -> SocketAddr { self.addr }
 
     /// 取得封包資料參考<br>
     /// Returns a reference to the packet data array
@@ -37,26 +143,17 @@ impl WifiPacket {
         )
     }
 
-    // pub fn pack(data: Vec<u8>) -> Result<Self, Box<dyn Error + Send + Sync>> {
-    //     if data.first() != Some(&UART_PACKET_START_CODE) {
-    //         let message = format!("Start byte invalid (expected {:?}): {:?}", UART_PACKET_START_CODE, data.first());
-    //         return Err(message.into());
-    //     }
-    //     if data.last() != Some(&UART_PACKET_END_CODE) {
-    //         let message = format!("End byte invalid (expected {:?}): {:?}", UART_PACKET_END_CODE, data.last());
-    //         return Err(message.into());
-    //     }
-    //     let packet = Self::new(data[1..(data.len()-1)].to_vec())?;
-    //     Ok(packet)
-    // }
-
-    // pub fn unpack(&self) -> Vec<u8> {
-    //     let mut buffer= Vec::<u8>::new();
-    //     buffer.push(self.start);
-    //     buffer.extend_from_slice(&self.data.show());
-    //     buffer.push(self.end);
-    //     buffer
-    // }
+    /// 由 WiFi 訊框建立封包<br>
+    /// Creates a packet from a WiFi frame
+    pub fn from_frame(addr: SocketAddr, frame: &WifiFrame) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::new(addr, frame.unpack())
+    }
+
+    /// 將封包資料解析為 WiFi 訊框<br>
+    /// Parses the packet data into a WiFi frame
+    pub fn frame(&self) -> Result<WifiFrame, Box<dyn Error + Send + Sync>> {
+        WifiFrame::pack(&self.data.show())
+    }
 }
 
 #[derive(Debug)]
@@ -1,3 +1,254 @@
+use std::{collections::HashMap, net::SocketAddr, time::Instant};
+use log::{debug, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::{mods::{mcu_const, mcu_control_mod::McuLink, ota_mod, uart_packet_proc_mod, wifi_mod::WifiProtocol, wifi_packet_mod::{WifiFrame, WifiPacket}}, GlobalState};
 
-// .map_err(|e| -> Box<dyn Error> { e })?;
+/// 接收封包處理結果<br>
+/// Result of processing a received packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RxOutcome {
+    Dispatched,     // MCU 訊框已交由解碼器處理／MCU frame handled by the decoder
+    Raw,            // 原始資料訊框／raw data frame
+    Ignored,        // 非目前鏈路裝置／not from the current link device
+    Unhandled,      // 解碼器無法處理／decoder could not handle it
+    Invalid,        // 訊框格式錯誤／malformed frame
+}
 
+/// 單一來源的接收統計<br>
+/// Receive statistics of a single source
+#[derive(Debug, Clone, Serialize)]
+pub struct WifiSourceStats {
+    pub protocol: WifiProtocol,
+    pub addr: SocketAddr,
+    pub received: u64,
+    pub bytes: u64,
+    pub overflow: u64,
+    pub dispatched: u64,
+    pub raw: u64,
+    pub ignored: u64,
+    pub unhandled: u64,
+    pub invalid: u64,
+    pub last_seen_ms: u64,
+}
+
+/// 傳送給前端的接收統計<br>
+/// Receive statistics sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct WifiRxReport {
+    pub tcp_overflow: u64,
+    pub udp_overflow: u64,
+    pub sources: Vec<WifiSourceStats>,
+}
+
+struct SourceEntry {
+    stats: WifiSourceStats,
+    last_seen: Instant,
+}
+
+/// WiFi 接收統計，依協定與來源位址分別計數<br>
+/// WiFi receive statistics, counted per protocol and source address
+pub struct WifiRxStats {
+    sources: HashMap<(WifiProtocol, SocketAddr), SourceEntry>,
+    tcp_overflow: u64,
+    udp_overflow: u64,
+}
+impl WifiRxStats {
+    /// 建立空的接收統計<br>
+    /// Creates empty receive statistics
+    pub fn new() -> Self {
+        Self {
+            sources: HashMap::new(),
+            tcp_overflow: 0,
+            udp_overflow: 0,
+        }
+    }
+
+    fn source(&mut self, protocol: WifiProtocol, addr: SocketAddr) -> &mut SourceEntry {
+        self.sources.entry((protocol, addr)).or_insert_with(|| SourceEntry {
+            stats: WifiSourceStats {
+                protocol,
+                addr,
+                received: 0,
+                bytes: 0,
+                overflow: 0,
+                dispatched: 0,
+                raw: 0,
+                ignored: 0,
+                unhandled: 0,
+                invalid: 0,
+                last_seen_ms: 0,
+            },
+            last_seen: Instant::now(),
+        })
+    }
+
+    /// 記錄收到的封包<br>
+    /// Records a received packet
+    fn received(&mut self, protocol: WifiProtocol, addr: SocketAddr, bytes: usize) {
+        let entry = self.source(protocol, addr);
+        entry.stats.received += 1;
+        entry.stats.bytes += bytes as u64;
+        entry.last_seen = Instant::now();
+    }
+
+    /// 記錄緩衝區已滿而丟棄的封包，回傳該協定累計丟棄數<br>
+    /// Records a packet dropped because the buffer was full, returns the protocol's total drops
+    fn overflow(&mut self, protocol: WifiProtocol, addr: SocketAddr) -> u64 {
+        self.source(protocol, addr).stats.overflow += 1;
+        let total = match protocol {
+            WifiProtocol::Tcp => &mut self.tcp_overflow,
+            WifiProtocol::Udp => &mut self.udp_overflow,
+        };
+        *total += 1;
+        *total
+    }
+
+    /// 記錄封包處理結果<br>
+    /// Records the processing result of a packet
+    fn outcome(&mut self, protocol: WifiProtocol, addr: SocketAddr, outcome: RxOutcome) {
+        let stats = &mut self.source(protocol, addr).stats;
+        match outcome {
+            RxOutcome::Dispatched => stats.dispatched += 1,
+            RxOutcome::Raw => stats.raw += 1,
+            RxOutcome::Ignored => stats.ignored += 1,
+            RxOutcome::Unhandled => stats.unhandled += 1,
+            RxOutcome::Invalid => stats.invalid += 1,
+        }
+    }
+
+    /// 取得統計報告，來源依協定與位址排序<br>
+    /// Returns the statistics report, sources sorted by protocol and address
+    pub fn report(&self) -> WifiRxReport {
+        let mut sources: Vec<_> = self.sources.values().map(|entry| WifiSourceStats {
+            last_seen_ms: entry.last_seen.elapsed().as_millis() as u64,
+            ..entry.stats.clone()
+        }).collect();
+        sources.sort_by_key(|s| (s.protocol == WifiProtocol::Udp, s.addr));
+        WifiRxReport {
+            tcp_overflow: self.tcp_overflow,
+            udp_overflow: self.udp_overflow,
+            sources,
+        }
+    }
+
+    /// 清除所有統計<br>
+    /// Clears all statistics
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+impl Default for WifiRxStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 將收到的封包存入對應協定的接收緩衝，已滿時計入溢位<br>
+/// Stores a received packet into the receive buffer of its protocol, counts an overflow when full
+pub async fn re_pkt_store(app: &AppHandle, protocol: WifiProtocol, packet: WifiPacket) {
+    let global_state = app.state::<GlobalState>();
+    let addr = packet.addr();
+    let bytes = packet.data().len();
+    let result = {
+        let mut state_buffer = match protocol {
+            WifiProtocol::Tcp => global_state.wifi_tcp_receive_buffer.lock().await,
+            WifiProtocol::Udp => global_state.wifi_udp_receive_buffer.lock().await,
+        };
+        state_buffer.push(packet).map_err(|e| e.to_string())
+    };
+    let mut rx_stats = global_state.wifi_rx_stats.lock().await;
+    rx_stats.received(protocol, addr, bytes);
+    if let Err(e) = result {
+        let total = rx_stats.overflow(protocol, addr);
+        warn!("{:?} receive buffer overflow from {} (total {}): {}", protocol, addr, total, e);
+    }
+}
+
+/// 處理 TCP 與 UDP 接收緩衝區的封包，各最多處理 n 筆<br>
+/// Processes up to n packets from each of the TCP and UDP receive buffers
+pub async fn re_pkt_proccess(app: AppHandle) {
+    let global_state = app.state::<GlobalState>();
+    for protocol in [WifiProtocol::Tcp, WifiProtocol::Udp] {
+        for _ in 0..10 {
+            let maybe_pkt = {
+                let mut wifi_recv_buffer = match protocol {
+                    WifiProtocol::Tcp => global_state.wifi_tcp_receive_buffer.lock().await,
+                    WifiProtocol::Udp => global_state.wifi_udp_receive_buffer.lock().await,
+                };
+                wifi_recv_buffer.pop_front()
+            };
+            let packet = match maybe_pkt {
+                Err(_) => break,
+                Ok(p) => p,
+            };
+            let addr = packet.addr();
+            let outcome = re_pkt_frame(app.clone(), packet).await;
+            global_state.wifi_rx_stats.lock().await.outcome(protocol, addr, outcome);
+        }
+    }
+}
+
+/// 解析 WiFi 訊框，MCU 訊框交由與 UART 共用的解碼器處理<br>
+/// Parses a WiFi frame, MCU frames go to the decoder shared with UART
+async fn re_pkt_frame(app: AppHandle, packet: WifiPacket) -> RxOutcome {
+    let frame = match packet.frame() {
+        Ok(frame) => frame,
+        Err(e) => {
+            warn!("WiFi frame from {} invalid: {}", packet.addr(), e);
+            return RxOutcome::Invalid;
+        }
+    };
+    match frame.frame_type() {
+        mcu_const::WIFI_FRAME_TYPE_MCU => re_pkt_mcu(app, &packet, frame).await,
+        mcu_const::WIFI_FRAME_TYPE_RAW => {
+            debug!("WiFi raw frame from {}:\n{}", packet.addr(), frame.show());
+            RxOutcome::Raw
+        }
+        mcu_const::WIFI_FRAME_TYPE_OTA => match ota_mod::re_pkt_ota(&app, &packet, frame).await {
+            true => RxOutcome::Dispatched,
+            false => RxOutcome::Ignored,
+        },
+        other => {
+            warn!("WiFi frame type unknown from {}: {:#04x}", packet.addr(), other);
+            RxOutcome::Unhandled
+        }
+    }
+}
+
+/// 只處理來自目前 WiFi 鏈路裝置的 MCU 訊框<br>
+/// Only handles MCU frames from the device of the current WiFi link
+async fn re_pkt_mcu(app: AppHandle, packet: &WifiPacket, frame: WifiFrame) -> RxOutcome {
+    let global_state = app.state::<GlobalState>();
+    let link = global_state.mcu_link.lock().await.link();
+    match link {
+        McuLink::Wifi { device_id, .. } if device_id == frame.device_id() => {}
+        _ => {
+            debug!("MCU frame from device {} ({}) ignored, link: {:?}", frame.device_id(), packet.addr(), link);
+            return RxOutcome::Ignored;
+        }
+    }
+    if !uart_packet_proc_mod::re_pkt_dispatch(app, frame.payload().to_vec()).await {
+        warn!("MCU frame from device {} unhandled:\n{}", frame.device_id(), frame.show());
+        return RxOutcome::Unhandled;
+    }
+    RxOutcome::Dispatched
+}
+
+/// Tauri 指令：取得 WiFi 接收統計<br>
+/// Tauri command: get the WiFi receive statistics
+#[tauri::command]
+pub async fn cmd_wifi_rx_stats(app: AppHandle) -> Result<WifiRxReport, String> {
+    let global_state = app.state::<GlobalState>();
+    let rx_stats = global_state.wifi_rx_stats.lock().await;
+    Ok(rx_stats.report())
+}
+
+/// Tauri 指令：清除 WiFi 接收統計<br>
+/// Tauri command: clear the WiFi receive statistics
+#[tauri::command]
+pub async fn cmd_wifi_rx_stats_reset(app: AppHandle) -> Result<(), String> {
+    let global_state = app.state::<GlobalState>();
+    global_state.wifi_rx_stats.lock().await.clear();
+    Ok(())
+}
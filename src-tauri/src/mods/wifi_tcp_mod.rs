@@ -0,0 +1,415 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, error::Error, net::SocketAddr, sync::{atomic::{AtomicU64, Ordering}, Arc}, time::{Duration, Instant}};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt}, net::{tcp::{OwnedReadHalf, OwnedWriteHalf}, TcpListener, TcpStream}, sync::{mpsc, watch::{self, Receiver}, Mutex}, task::JoinHandle, time::{interval, sleep, timeout, MissedTickBehavior}};
+use crate::mods::{wifi_mod::WifiProtocol, wifi_packet_mod::{self, WifiPacket}, wifi_packet_proc_mod};
+
+/// 無資料時送出 keepalive 空封包的間隔（ms）<br>
+/// Interval of empty keepalive frames when idle (ms)
+pub const TCP_KEEPALIVE_MS: u64 = 2000;
+
+/// 超過此時間未收到任何封包（含 keepalive）即斷線（ms）<br>
+/// Connection is dropped when nothing (including keepalive) arrives within this time (ms)
+pub const TCP_IDLE_TIMEOUT_MS: u64 = 3 * TCP_KEEPALIVE_MS;
+
+/// 主動連線斷線後的重連間隔（ms）<br>
+/// Reconnect interval for dropped outbound connections (ms)
+pub const TCP_RECONNECT_MS: u64 = 1000;
+
+/// 連線逾時（ms）<br>
+/// Connect timeout (ms)
+const TCP_CONNECT_TIMEOUT_MS: u64 = 2000;
+
+/// 每條連線的傳送佇列長度<br>
+/// Transmit queue length of each connection
+const TCP_CONN_QUEUE_SIZE: usize = 16;
+
+/// 單一 TCP 連線，傳送資料經由佇列交給寫入任務；移除時 cancel 被丟棄，讀寫任務隨之結束<br>
+/// One TCP connection, outgoing data goes through a queue to its writer task;
+/// dropping cancel on removal stops both reader and writer tasks
+struct TcpConn {
+    id: u64,
+    tx: mpsc::Sender<Vec<u8>>,
+    cancel: watch::Sender<bool>,
+    outbound: bool,
+    connected_at: Instant,
+    rx_frames: Arc<AtomicU64>,
+    tx_frames: Arc<AtomicU64>,
+}
+
+/// 傳送給前端的連線資訊<br>
+/// Connection info sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct TcpPeerInfo {
+    pub addr: String,
+    pub outbound: bool,
+    pub connected_secs: u64,
+    pub rx_frames: u64,
+    pub tx_frames: u64,
+}
+
+/// TCP 長連線表，以對端位址為鍵，每條連線各有讀寫任務<br>
+/// Persistent TCP connection table keyed by peer address, each connection has reader/writer tasks
+///
+/// 串流格式：`[len u16 BE][data; len]`，len = 0 為 keepalive<br>
+/// Stream framing: `[len u16 BE][data; len]`, len = 0 is a keepalive
+pub struct WifiTcpConns {
+    conns: Mutex<HashMap<SocketAddr, TcpConn>>,
+    targets: Mutex<HashSet<SocketAddr>>,
+    pending: Mutex<HashMap<SocketAddr, VecDeque<Vec<u8>>>>,
+    shutdown: Mutex<Option<Receiver<bool>>>,
+    next_id: AtomicU64,
+}
+impl WifiTcpConns {
+    /// 建立空的連線表<br>
+    /// Creates an empty connection table
+    pub fn new() -> Self {
+        Self {
+            conns: Mutex::new(HashMap::new()),
+            targets: Mutex::new(HashSet::new()),
+            pending: Mutex::new(HashMap::new()),
+            shutdown: Mutex::new(None),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// 啟動：記錄停止訊號並開始接受連線，回傳接受迴圈的 handle<br>
+    /// Starts: keeps the shutdown signal and begins accepting connections, returns the accept loop handle
+    pub async fn start(self: &Arc<Self>, app: AppHandle, listener: TcpListener, shutdown: Receiver<bool>) -> JoinHandle<()> {
+        *self.shutdown.lock().await = Some(shutdown.clone());
+        self.accept_spawn(app, listener, shutdown)
+    }
+
+    /// 關閉所有連線並清除重連目標<br>
+    /// Closes all connections and clears reconnect targets
+    pub async fn clear(&self, app: &AppHandle) {
+        self.targets.lock().await.clear();
+        self.pending.lock().await.clear();
+        self.conns.lock().await.clear();
+        *self.shutdown.lock().await = None;
+        self.peers_emit(app).await;
+    }
+
+    fn is_shutdown(shutdown: &Receiver<bool>) -> bool {
+        *shutdown.borrow() || shutdown.has_changed().is_err()
+    }
+
+    /// 取得目前所有連線資訊<br>
+    /// Returns info of all current connections
+    pub async fn peers(&self) -> Vec<TcpPeerInfo> {
+        let conns = self.conns.lock().await;
+        let mut peers: Vec<TcpPeerInfo> = conns.iter().map(|(addr, conn)| TcpPeerInfo {
+            addr: addr.to_string(),
+            outbound: conn.outbound,
+            connected_secs: conn.connected_at.elapsed().as_secs(),
+            rx_frames: conn.rx_frames.load(Ordering::Relaxed),
+            tx_frames: conn.tx_frames.load(Ordering::Relaxed),
+        }).collect();
+        peers.sort_by(|a, b| a.addr.cmp(&b.addr));
+        peers
+    }
+
+    /// 發出 `wifi-peers` 事件<br>
+    /// Emits the `wifi-peers` event
+    async fn peers_emit(&self, app: &AppHandle) {
+        let peers = self.peers().await;
+        if let Err(e) = app.emit("wifi-peers", &peers) {
+            error!("Emit wifi-peers failed: {}", e);
+        }
+    }
+
+    /// 接受連線迴圈<br>
+    /// Accept loop
+    fn accept_spawn(self: &Arc<Self>, app: AppHandle, listener: TcpListener, mut shutdown: Receiver<bool>) -> JoinHandle<()> {
+        let arc_handle = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let accepted = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    accepted = listener.accept() => accepted,
+                };
+                match accepted {
+                    Ok((stream, peer)) => {
+                        info!("TCP accepted: {}", peer);
+                        arc_handle.register(app.clone(), stream, peer, false).await;
+                    }
+                    Err(e) => {
+                        error!("TCP accept failed: {}", e);
+                        sleep(Duration::from_millis(10)).await;
+                    }
+                }
+                if Self::is_shutdown(&shutdown) { break; }
+            }
+            debug!("TCP accept loop stopped");
+        })
+    }
+
+    /// 註冊新連線並啟動讀寫任務，取代同位址的舊連線<br>
+    /// Registers a new connection and starts its reader/writer tasks, replacing any old one on the same address
+    async fn register(self: &Arc<Self>, app: AppHandle, stream: TcpStream, peer: SocketAddr, outbound: bool) {
+        let Some(shutdown) = self.shutdown.lock().await.clone() else {
+            warn!("TCP connection {} dropped, WiFi not started", peer);
+            return;
+        };
+        let _ = stream.set_nodelay(true);
+        let (reader, writer) = stream.into_split();
+        let (tx, rx) = mpsc::channel(TCP_CONN_QUEUE_SIZE);
+        let (cancel, cancel_rx) = watch::channel(false);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let rx_frames = Arc::new(AtomicU64::new(0));
+        let tx_frames = Arc::new(AtomicU64::new(0));
+        {
+            // 持有連線表鎖送出暫存資料，確保順序先於之後的傳送
+            // flush queued data while holding the table lock so it goes out before later sends
+            let mut conns = self.conns.lock().await;
+            conns.insert(peer, TcpConn {
+                id,
+                tx: tx.clone(),
+                cancel,
+                outbound,
+                connected_at: Instant::now(),
+                rx_frames: rx_frames.clone(),
+                tx_frames: tx_frames.clone(),
+            });
+            if let Some(queued) = self.pending.lock().await.remove(&peer) {
+                debug!("TCP {} flushing {} queued packets", peer, queued.len());
+                for data in queued {
+                    let _ = tx.try_send(data);
+                }
+            }
+        }
+        Self::write_spawn(writer, rx, peer, tx_frames, shutdown.clone(), cancel_rx.clone());
+        self.read_spawn(app.clone(), reader, peer, id, outbound, rx_frames, shutdown, cancel_rx);
+        self.peers_emit(&app).await;
+    }
+
+    /// 寫入一個長度前綴封包<br>
+    /// Writes one length-prefixed frame
+    pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, data: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut buffer = Vec::with_capacity(data.len() + 2);
+        buffer.extend((data.len() as u16).to_be_bytes());
+        buffer.extend_from_slice(data);
+        writer.write_all(&buffer).await?;
+        Ok(())
+    }
+
+    /// 讀取一個長度前綴封包<br>
+    /// Reads one length-prefixed frame
+    pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let len = reader.read_u16().await? as usize;
+        if len > wifi_packet_mod::WIFI_TCP_PACKET_MAX_SIZE {
+            return Err(format!("TCP frame too long: {}", len).into());
+        }
+        let mut buffer = vec![0u8; len];
+        reader.read_exact(&mut buffer).await?;
+        Ok(buffer)
+    }
+
+    /// 寫入任務：從佇列取出資料寫出，閒置時送 keepalive<br>
+    /// Writer task: writes queued data, sends keepalive when idle
+    fn write_spawn(
+        mut writer: OwnedWriteHalf,
+        mut rx: mpsc::Receiver<Vec<u8>>,
+        peer: SocketAddr,
+        tx_frames: Arc<AtomicU64>,
+        mut shutdown: Receiver<bool>,
+        mut cancel: Receiver<bool>,
+    ) {
+        tokio::spawn(async move {
+            let mut keepalive = interval(Duration::from_millis(TCP_KEEPALIVE_MS));
+            keepalive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // 略過立即觸發的第一次 / skip the immediate first tick
+            keepalive.reset();
+            loop {
+                let result = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = cancel.changed() => break,
+                    data = rx.recv() => match data {
+                        Some(data) => {
+                            keepalive.reset();
+                            let result = Self::write_frame(&mut writer, &data).await;
+                            if result.is_ok() {
+                                tx_frames.fetch_add(1, Ordering::Relaxed);
+                                debug!("TCP sent {} bytes to {}", data.len(), peer);
+                            }
+                            result
+                        }
+                        None => break,
+                    },
+                    _ = keepalive.tick() => Self::write_frame(&mut writer, &[]).await,
+                };
+                if let Err(e) = result {
+                    error!("TCP write to {} failed: {}", peer, e);
+                    break;
+                }
+            }
+            let _ = writer.shutdown().await;
+            debug!("TCP writer {} stopped", peer);
+        });
+    }
+
+    /// 讀取任務：收到的封包推入接收緩衝，斷線後移除連線並視需要重連<br>
+    /// Reader task: pushes received frames into the receive buffer, removes the connection on drop and reconnects if needed
+    #[allow(clippy::too_many_arguments)]
+    fn read_spawn(
+        self: &Arc<Self>,
+        app: AppHandle,
+        mut reader: OwnedReadHalf,
+        peer: SocketAddr,
+        id: u64,
+        outbound: bool,
+        rx_frames: Arc<AtomicU64>,
+        mut shutdown: Receiver<bool>,
+        mut cancel: Receiver<bool>,
+    ) {
+        let arc_handle = Arc::clone(self);
+        tokio::spawn(async move {
+            let idle = Duration::from_millis(TCP_IDLE_TIMEOUT_MS);
+            loop {
+                let result = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = cancel.changed() => {
+                        debug!("TCP {} cancelled", peer);
+                        break;
+                    }
+                    result = timeout(idle, Self::read_frame(&mut reader)) => result,
+                };
+                let data = match result {
+                    Err(_) => {
+                        warn!("TCP {} idle timeout", peer);
+                        break;
+                    }
+                    Ok(Err(e)) => {
+                        info!("TCP {} closed: {}", peer, e);
+                        break;
+                    }
+                    Ok(Ok(data)) => data,
+                };
+                if data.is_empty() {
+                    continue;
+                }
+                rx_frames.fetch_add(1, Ordering::Relaxed);
+                let packet = match WifiPacket::new(peer, &data) {
+                    Ok(packet) => packet,
+                    Err(e) => {
+                        error!("{}", e);
+                        continue;
+                    }
+                };
+                debug!("TCP got {} bytes from {}", data.len(), peer);
+                wifi_packet_proc_mod::re_pkt_store(&app, WifiProtocol::Tcp, packet).await;
+            }
+            arc_handle.remove(&app, peer, id).await;
+            let target = arc_handle.targets.lock().await.contains(&peer);
+            if (outbound || target) && !Self::is_shutdown(&shutdown) {
+                arc_handle.reconnect_spawn(app, peer);
+            }
+        });
+    }
+
+    /// 移除指定連線（僅在 id 相符時，避免移除已取代的新連線）<br>
+    /// Removes a connection (only when the id matches, so a replacing connection is kept)
+    async fn remove(&self, app: &AppHandle, peer: SocketAddr, id: u64) {
+        {
+            let mut conns = self.conns.lock().await;
+            if conns.get(&peer).is_some_and(|conn| conn.id == id) {
+                conns.remove(&peer);
+            }
+        }
+        self.peers_emit(app).await;
+    }
+
+    /// 主動連線到指定位址<br>
+    /// Opens an outbound connection to the given address
+    async fn connect(self: &Arc<Self>, app: &AppHandle, peer: SocketAddr) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let stream = timeout(Duration::from_millis(TCP_CONNECT_TIMEOUT_MS), TcpStream::connect(peer))
+            .await
+            .map_err(|_| format!("TCP connect {} timed out", peer))?
+            .map_err(|e| format!("TCP connect {} failed: {}", peer, e))?;
+        info!("TCP connected: {}", peer);
+        self.register(app.clone(), stream, peer, true).await;
+        Ok(())
+    }
+
+    /// 重連任務：直到連上、停止或目標被移除<br>
+    /// Reconnect task: runs until connected, shut down or the target is removed
+    fn reconnect_spawn(self: &Arc<Self>, app: AppHandle, peer: SocketAddr) {
+        let arc_handle = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_millis(TCP_RECONNECT_MS)).await;
+                let shutdown = arc_handle.shutdown.lock().await.clone();
+                if shutdown.is_none_or(|s| Self::is_shutdown(&s)) { break; }
+                if !arc_handle.targets.lock().await.contains(&peer) { break; }
+                if arc_handle.conns.lock().await.contains_key(&peer) { break; }
+                match arc_handle.connect(&app, peer).await {
+                    Ok(()) => break,
+                    Err(e) => debug!("{}", e),
+                }
+            }
+        });
+    }
+
+    /// 找出對應的連線：完全相同位址優先，其次為同 IP 的連入連線<br>
+    /// Finds the connection: exact address first, then an inbound connection from the same IP
+    fn sender(conns: &HashMap<SocketAddr, TcpConn>, peer: SocketAddr) -> Option<(mpsc::Sender<Vec<u8>>, SocketAddr)> {
+        if let Some(conn) = conns.get(&peer) {
+            return Some((conn.tx.clone(), peer));
+        }
+        conns.iter()
+            .find(|(addr, _)| addr.ip() == peer.ip())
+            .map(|(addr, conn)| (conn.tx.clone(), *addr))
+    }
+
+    /// 傳送封包；若尚無連線則暫存並交由重連任務連線，不在呼叫端等待連線<br>
+    /// Sends a packet; without a connection the packet is queued for the peer and the reconnect task connects, never waiting on the caller
+    pub async fn send(self: &Arc<Self>, app: &AppHandle, packet: WifiPacket) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let peer = packet.addr();
+        let (tx, addr) = {
+            let conns = self.conns.lock().await;
+            match Self::sender(&conns, peer) {
+                Some(pair) => pair,
+                None => {
+                    let mut pending = self.pending.lock().await;
+                    let queue = pending.entry(peer).or_default();
+                    if queue.len() >= TCP_CONN_QUEUE_SIZE {
+                        queue.pop_front();
+                        warn!("TCP {} not connected, oldest queued packet dropped", peer);
+                    }
+                    queue.push_back(packet.data());
+                    drop(pending);
+                    drop(conns);
+                    if self.targets.lock().await.insert(peer) {
+                        info!("TCP {} not connected, connecting in background", peer);
+                        self.reconnect_spawn(app.clone(), peer);
+                    }
+                    return Ok(());
+                }
+            }
+        };
+        tx.send(packet.data()).await.map_err(|_| format!("TCP connection {} closed", addr))?;
+        Ok(())
+    }
+
+    /// 中斷指定連線並停止重連，讀寫任務同時結束<br>
+    /// Disconnects the given peer and stops reconnecting, both reader and writer tasks stop
+    pub async fn disconnect(&self, app: &AppHandle, peer: SocketAddr) -> Result<(), String> {
+        self.targets.lock().await.remove(&peer);
+        self.pending.lock().await.remove(&peer);
+        let removed = self.conns.lock().await.remove(&peer);
+        self.peers_emit(app).await;
+        match removed {
+            Some(conn) => {
+                let _ = conn.cancel.send(true);
+                Ok(())
+            }
+            None => Err(format!("TCP peer not connected: {}", peer)),
+        }
+    }
+}
+impl Default for WifiTcpConns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,11 +1,20 @@
 import { useState } from 'react';
 import UartPortOCComp from './components/UartPortOCComp';
 import UartControlComp from './components/UartControlComp';
+import MotorSetComp from './components/MotorSetComp';
 import PageSelectComp from './components/PageSelectComp';
 import ChartGenerateComp from './components/ChartGenerateComp';
 import MapGeneratorComp from './components/MapGeneratorComp';
 import MapDisplayComp from './components/MapDisplayComp';
+import MissionComp from './components/MissionComp';
 import WifiControlComp from './components/WifiControlComp';
+import WifiDevicesComp from './components/WifiDevicesComp';
+import VehicleControlComp from './components/VehicleControlComp';
+import TeleopComp from './components/TeleopComp';
+import BridgeControlComp from './components/BridgeControlComp';
+import MqttControlComp from './components/MqttControlComp';
+import OtaComp from './components/OtaComp';
+import BootloaderComp from './components/BootloaderComp';
 
 const App = () => {
     /*useEffect(() => {
@@ -22,10 +31,19 @@ const App = () => {
             content = <UartPortOCComp />;
             break;
         case "uart_port_control":
-            content = <UartControlComp />;
+            content = <><UartControlComp /><MotorSetComp /></>;
+            break;
+        case "vehicle_control":
+            content = <><VehicleControlComp /><TeleopComp /></>;
             break;
         case "wifi_control":
-            content = <WifiControlComp />;
+            content = <><WifiControlComp /><WifiDevicesComp /></>;
+            break;
+        case "remote":
+            content = <><BridgeControlComp /><MqttControlComp /></>;
+            break;
+        case "firmware":
+            content = <><OtaComp /><BootloaderComp /></>;
             break;
         case "chart_generate":
             content = <ChartGenerateComp />;
@@ -34,7 +52,7 @@ const App = () => {
             content = <MapGeneratorComp />;
             break;
         case "test":
-            content = <><MapDisplayComp /></>;
+            content = <><MapDisplayComp /><MissionComp /></>;
             break;
         default:
             content = <div>Page Not Found</div>;
@@ -0,0 +1,109 @@
+// 開機載入程式元件：經由 UART 以 STM32 系統開機載入程式燒錄韌體
+// Bootloader component: flashes firmware through the STM32 system bootloader over UART
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// BootProgress：需與後端 BootProgress 一致
+// Must match backend BootProgress
+interface BootProgress {
+    state: 'idle' | 'connecting' | 'erasing' | 'writing' | 'verifying' | 'starting' | 'done' | 'failed';
+    error?: string;
+    simulated: boolean;
+    version: string | null;
+    product_id: number | null;
+    written: number;
+    verified: number;
+    total: number;
+}
+
+const BootloaderComp = () => {
+    const [path, setPath] = useState(() => localStorage.getItem('bootPath') || '');
+    const [baseAddr, setBaseAddr] = useState(() => localStorage.getItem('bootBaseAddr') || '0x08000000');
+    const [verify, setVerify] = useState(true);
+    const [progress, setProgress] = useState<BootProgress | null>(null);
+    const [response, setResponse] = useState('');
+
+    useEffect(() => {
+        localStorage.setItem('bootPath', path);
+        localStorage.setItem('bootBaseAddr', baseAddr);
+    }, [path, baseAddr]);
+
+    useEffect(() => {
+        invoke<BootProgress>('cmd_bootloader_status').then(setProgress).catch(() => {});
+        const unlisten = listen<BootProgress>('bootloader-progress', event => setProgress(event.payload));
+        return () => {
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const flash = async (simulate: boolean) => {
+        try {
+            setProgress(await invoke<BootProgress>('cmd_bootloader_flash', {
+                path,
+                baseAddr: baseAddr || null,
+                verify,
+                go: true,
+                simulate,
+            }));
+            setResponse('');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const done = progress ? (progress.state === 'verifying' ? progress.verified : progress.written) : 0;
+    const percent = progress && progress.total > 0 ? Math.floor(done * 100 / progress.total) : 0;
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div>STM32 bootloader (UART 8E1, set BOOT0 and reset the board first)</div>
+            <div className="grid grid-cols-4 gap-4">
+                <input
+                    placeholder="Firmware file path (.hex / .bin)"
+                    value={path}
+                    onChange={e => setPath(e.target.value)}
+                    className="map_generate-input-defalt col-span-2"
+                />
+                <input
+                    placeholder="Bin base address"
+                    value={baseAddr}
+                    onChange={e => setBaseAddr(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <label className="flex items-center gap-2">
+                    <input type="checkbox" checked={verify} onChange={e => setVerify(e.target.checked)} />
+                    Verify
+                </label>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => flash(false)}
+                > Flash </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => flash(true)}
+                > Flash (simulated) </button>
+            </div>
+            {progress && progress.state !== 'idle' && (
+                <div className="space-y-2">
+                    <div className="w-full bg-gray-500 rounded h-4">
+                        <div className="bg-blue-500 rounded h-4" style={{ width: `${percent}%` }} />
+                    </div>
+                    <div>
+                        {progress.state}{progress.error ? `: ${progress.error}` : ''}
+                        {progress.simulated ? ' (simulated)' : ''}
+                        {progress.version ? ` bootloader v${progress.version}` : ''}
+                        {progress.product_id !== null ? ` PID 0x${progress.product_id.toString(16).padStart(4, '0')}` : ''}
+                        {' '}{done}/{progress.total} bytes ({percent}%)
+                    </div>
+                </div>
+            )}
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default BootloaderComp;
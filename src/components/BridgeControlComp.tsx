@@ -0,0 +1,103 @@
+// 橋接伺服器元件：啟動／停止 WebSocket 與 HTTP 遙測橋接
+// Bridge server component: start / stop the WebSocket and HTTP telemetry bridge
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+// BridgeStatus：需與後端 BridgeStatus 一致
+// Must match backend BridgeStatus
+interface BridgeStatus {
+    running: boolean;
+    addr: string | null;
+    token_set: boolean;
+    uptime_secs: number | null;
+}
+
+const BridgeControlComp = () => {
+    const [bindAddr, setBindAddr] = useState(() => localStorage.getItem('bridgeBindAddr') || '127.0.0.1');
+    const [port, setPort] = useState(() => localStorage.getItem('bridgePort') || '8765');
+    const [token, setToken] = useState('');
+    const [status, setStatus] = useState<BridgeStatus | null>(null);
+    const [response, setResponse] = useState('');
+
+    useEffect(() => {
+        localStorage.setItem('bridgeBindAddr', bindAddr);
+        localStorage.setItem('bridgePort', port);
+    }, [bindAddr, port]);
+
+    useEffect(() => {
+        const refresh = () =>
+            invoke<BridgeStatus>('cmd_bridge_status').then(setStatus).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        return () => clearInterval(interval);
+    }, []);
+
+    const start = async () => {
+        try {
+            const n = parseInt(port, 10);
+            const result = await invoke<BridgeStatus>('cmd_bridge_start', {
+                bindAddr: bindAddr || null,
+                port: Number.isFinite(n) ? n : null,
+                token: token || null,
+            });
+            setStatus(result);
+            setResponse(`Bridge running at http://${result.addr}/`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const stop = async () => {
+        try {
+            setStatus(await invoke<BridgeStatus>('cmd_bridge_stop'));
+            setResponse('Bridge stopped');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-5 gap-4">
+                <input
+                    placeholder="Bind address"
+                    value={bindAddr}
+                    onChange={e => setBindAddr(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="Port"
+                    value={port}
+                    onChange={e => setPort(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    type="password"
+                    placeholder="Token (required off localhost)"
+                    value={token}
+                    onChange={e => setToken(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={start}
+                > Bridge start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={stop}
+                > Bridge stop </button>
+            </div>
+            <div>
+                {status?.running
+                    ? `Running at ${status.addr}${status.token_set ? ' (token)' : ''}, up ${status.uptime_secs ?? 0}s`
+                    : 'Stopped'}
+            </div>
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default BridgeControlComp;
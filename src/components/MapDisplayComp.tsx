@@ -1,8 +1,17 @@
-import React, { useMemo } from 'react';
+import React, { useEffect, useMemo, useState } from 'react';
 import { Graph } from 'react-d3-graph';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 // 嘗試載入 map_info.json，若不存在則忽略
-interface RawMapNode { id: number; name: string; connect: { pos: number; len: number }[]; }
+interface RawMapNode {
+  id: number;
+  name: string;
+  kind?: 'junction' | 'station' | 'charger';
+  x?: number;
+  y?: number;
+  connect: { pos: number; len: number; heading?: number; one_way?: boolean }[];
+}
 let mapInfo: RawMapNode[] = [];
 try {
   // 使用 require 進行條件載入
@@ -15,8 +24,29 @@ try {
 // 方向對應角度（度）：index 0=北，1=東北，2=東，…
 const dirAngles = [-90, -45, 0, 45, 90, 135, 180, -135];
 
-type NodeType = { id: string; label: string; x: number; y: number };
-type LinkType = { source: string; target: string; label: string };
+type NodeType = { id: string; label: string; x: number; y: number; color?: string };
+type LinkType = { source: string; target: string; label: string; color?: string };
+
+// MapRoute：需與後端 MapRoute 一致 / Must match backend MapRoute
+interface MapRoute { from: number; to: number; path: number[]; length: number; turns: number[]; }
+
+// VehicleLocation：需與後端 VehicleLocation 一致 / Must match backend VehicleLocation
+interface VehicleLocation {
+  state: 'at_node' | 'on_edge' | 'off_map';
+  node: number;
+  name: string | null;
+  next: number | null;
+  travelled: number;
+  edge_len: number | null;
+  progress: number | null;
+  message: string | null;
+  ts_ms: number;
+}
+
+// 路徑高亮顏色
+const routeColor = '#e53e3e';
+// 車輛所在節點與行進中邊的顏色
+const vehicleColor = '#38a169';
 
 const MapDisplayComp: React.FC = () => {
     const width = 800;
@@ -25,6 +55,57 @@ const MapDisplayComp: React.FC = () => {
     const centerY = height / 2;
     const scale = 20; // 每單位長度像素比例
 
+    const [from, setFrom] = useState('');
+    const [to, setTo] = useState('');
+    const [route, setRoute] = useState<MapRoute | null>(null);
+    const [response, setResponse] = useState('');
+    const [imgSrc, setImgSrc] = useState('');
+    const [vehicle, setVehicle] = useState<VehicleLocation | null>(null);
+
+    // 後端事件：車輛位置更新
+    useEffect(() => {
+        invoke<VehicleLocation | null>('map_vehicle_location').then(setVehicle).catch(() => {});
+        const unlisten = listen<VehicleLocation>('vehicle-location', event => setVehicle(event.payload));
+        return () => {
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const vehicleText = (loc: VehicleLocation) => {
+        const name = loc.name ? ` ${loc.name}` : '';
+        switch (loc.state) {
+            case 'at_node': return `Vehicle at ${loc.node}${name}`;
+            case 'on_edge': return `Vehicle ${loc.node}${name} → ${loc.next}: ${loc.travelled}/${loc.edge_len} (${Math.round((loc.progress ?? 0) * 100)}%)`;
+            default: return `Vehicle off map: ${loc.message ?? loc.node}`;
+        }
+    };
+
+    // 後端命令：以 plotters 繪製地圖（含路徑與車輛位置）
+    const chartRender = async (format: 'png' | 'svg') => {
+        try {
+            const image = await invoke<string>('map_chart', {
+                format,
+                from: from ? Number(from) : null,
+                to: to ? Number(to) : null,
+            });
+            setImgSrc(`data:image/${format === 'svg' ? 'svg+xml' : 'png'};base64,${image}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：計算兩點間最短路徑
+    const routeFind = async () => {
+        try {
+            const result = await invoke<MapRoute>('map_route', { from: Number(from), to: Number(to) });
+            setRoute(result);
+            setResponse(`${result.path.join(' → ')}  (length ${result.length}, turns ${result.turns.join(', ') || '-'})`);
+        } catch (error: any) {
+            setRoute(null);
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
     const data = useMemo(() => {
         if (mapInfo.length === 0) {
         return { nodes: [] as NodeType[], links: [] as LinkType[] };
@@ -32,7 +113,20 @@ const MapDisplayComp: React.FC = () => {
 
         const home = mapInfo.find(n => n.id === 1)!;
 
+        // 全部節點皆有座標時直接使用，y 軸向上故取負
+        const hasCoords = mapInfo.every(n => n.x !== undefined && n.y !== undefined);
+        const kindLabel = (node: RawMapNode) =>
+            node.kind && node.kind !== 'junction' ? `${node.name} (${node.kind})` : node.name;
+
         const nodes: NodeType[] = mapInfo.map(node => {
+        if (hasCoords) {
+            return {
+            id: node.id.toString(),
+            label: kindLabel(node),
+            x: centerX + node.x! * scale,
+            y: centerY - node.y! * scale,
+            };
+        }
         if (node.id === 1) {
             return { id: '1', label: node.name, x: centerX, y: centerY };
         }
@@ -56,12 +150,28 @@ const MapDisplayComp: React.FC = () => {
             .map(c => ({
             source: node.id.toString(),
             target: c.pos.toString(),
-            label: `${c.len}`,
+            label: c.one_way ? `${c.len} (one-way)` : `${c.len}`,
             }))
         );
 
+        // 高亮路徑上的節點與邊
+        const onRoute = new Set(route?.path ?? []);
+        const routeEdges = new Set(
+            (route?.path ?? []).slice(1).map((id, i) => `${route!.path[i]}-${id}`)
+        );
+        nodes.forEach(n => { if (onRoute.has(Number(n.id))) n.color = routeColor; });
+        links.forEach(l => { if (routeEdges.has(`${l.source}-${l.target}`)) l.color = routeColor; });
+
+        // 標示車輛所在節點與行進中的邊
+        if (vehicle && vehicle.state !== 'off_map') {
+            nodes.forEach(n => { if (Number(n.id) === vehicle.node) n.color = vehicleColor; });
+            links.forEach(l => {
+                if (l.source === `${vehicle.node}` && l.target === `${vehicle.next}`) l.color = vehicleColor;
+            });
+        }
+
         return { nodes, links };
-    }, []);
+    }, [route, vehicle]);
 
     const config = useMemo(() => ({
         directed: true,
@@ -75,6 +185,35 @@ const MapDisplayComp: React.FC = () => {
     return (
         <div className="p-4 bg-white rounded-2xl shadow h-full w-full">
             <h2 className="text-xl font-semibold mb-4">路線圖</h2>
+            <div className="flex gap-4 mb-4">
+                <input
+                    placeholder="From id"
+                    value={from}
+                    onChange={e => setFrom(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="To id"
+                    value={to}
+                    onChange={e => setTo(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={routeFind}
+                > Route </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => chartRender('png')}
+                > PNG </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => chartRender('svg')}
+                > SVG </button>
+            </div>
+            {vehicle && <pre className="mb-4">{vehicleText(vehicle)}</pre>}
+            {response && <pre className="mb-4">{response}</pre>}
+            {imgSrc && <img src={imgSrc} alt="Map rendered by backend" className="mb-4 max-w-full" />}
             <Graph id="route-graph" data={data} config={config} />
         </div>
     );
@@ -6,7 +6,69 @@ import React, { useEffect, useState } from 'react';
 interface MappingItem {
     id: number; // 唯一識別碼 / Unique identifier
     name: string; // 元件名稱 / Component name
-    connect: { pos: number; len: number }[]; // pos/len 陣列 / Array of pos/len pairs
+    kind?: NodeKind; // 節點種類 / Node kind
+    x?: number | null; // 座標，與長度同單位 / Coordinate in length units
+    y?: number | null;
+    connect: MappingConnect[]; // 連接陣列 / Array of connections
+}
+
+// NodeKind：需與後端 NodeKind 一致 / Must match backend NodeKind
+type NodeKind = 'junction' | 'station' | 'charger';
+
+// 連接：heading 為出發方位（度，北為 0 順時針），未填時依方向欄位
+// Connection: heading is the departure heading (degrees, north 0 clockwise), slot direction when empty
+interface MappingConnect {
+    pos: number;
+    len: number;
+    heading?: number | null;
+    one_way?: boolean;
+}
+
+// MapNormalizeResult：需與後端 MapNormalizeResult 一致 / Must match backend MapNormalizeResult
+interface MapNormalizeResult {
+    items: MappingItem[];
+    mirrored: { node: number; slot: number; pos: number; len: number }[];
+    report: { issues: MapIssue[] };
+}
+
+// MapVersionInfo：需與後端 MapVersionInfo 一致 / Must match backend MapVersionInfo
+interface MapVersionInfo {
+    version: number;
+    timestamp: string;
+    description: string;
+    nodes: number;
+    edges: number;
+}
+
+// MapDiff：需與後端 MapDiff 一致 / Must match backend MapDiff
+interface MapDiff {
+    from: number;
+    to: number;
+    added_nodes: number[];
+    removed_nodes: number[];
+    changed_nodes: number[];
+    added_edges: { from: number; to: number; len: number }[];
+    removed_edges: { from: number; to: number; len: number }[];
+    changed_edges: { from: number; to: number; old_len: number; new_len: number }[];
+}
+
+// MapFormat：需與後端 MapFormat 一致 / Must match backend MapFormat
+type MapFormat = 'graphml' | 'geojson' | 'dot' | 'csv';
+
+// MapImportResult：需與後端 MapImportResult 一致 / Must match backend MapImportResult
+interface MapImportResult {
+    items: MappingItem[];
+    id_map: { source: string; id: number }[];
+    report: { issues: MapIssue[] };
+}
+
+// MapIssue：需與後端 MapIssue 一致 / Must match backend MapIssue
+interface MapIssue {
+    level: 'error' | 'warning';
+    code: string;
+    node: number | null;
+    slot: number | null;
+    message: string;
 }
 
 const MapGeneratorComp: React.FC = () => {
@@ -78,10 +140,148 @@ const MapGeneratorComp: React.FC = () => {
         }
     };
 
-    // 後端命令：保存目前設定
-    // backend command: save current settings
+    // state: 驗證問題與後端回應
+    // state: validation issues and backend response
+    const [issues, setIssues] = useState<MapIssue[]>([]);
+    const [response, setResponse] = useState('');
+
+    // state: 路由表輸出選項（需與後端 MapRouteOutput 一致）
+    // state: routing table options (must match backend MapRouteOutput)
+    const [routing, setRouting] = useState({ next_hop: false, distance: false });
+
+    // state: 保存前自動補齊反向連接
+    // state: complete reverse connections before saving
+    const [mirror, setMirror] = useState(false);
+
+    // state: 版本說明與歷史版本列表
+    // state: version description and history list
+    const [description, setDescription] = useState('');
+    const [versions, setVersions] = useState<MapVersionInfo[]>([]);
+
+    // state: 匯入/匯出檔案路徑與格式
+    // state: import/export file path and format
+    const [filePath, setFilePath] = useState(() => localStorage.getItem('mapFilePath') || '');
+    const [fileFormat, setFileFormat] = useState<MapFormat>('graphml');
+    useEffect(() => {
+        localStorage.setItem('mapFilePath', filePath);
+    }, [filePath]);
+
+    // 後端命令：匯入地圖，成功後取代目前項目（尚未保存）
+    // backend command: import a map, replaces the current items on success (not saved yet)
+    const mapImport = async () => {
+        try {
+            const result = await invoke<MapImportResult>('map_import', { path: filePath, format: null });
+            setItems(result.items);
+            setNextId(Math.max(0, ...result.items.map(i => i.id)) + 1);
+            setIssues(result.report.issues);
+            const remapped = result.id_map.filter(m => m.source !== m.id.toString());
+            setResponse(`Imported ${result.items.length} nodes`
+                + (remapped.length > 0 ? `\n${remapped.map(m => `${m.source} → ${m.id}`).join(', ')}` : ''));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：匯出已保存的地圖，路徑空白時寫到 generate/map
+    // backend command: export the saved map, written under generate/map when the path is empty
+    const mapExport = async () => {
+        try {
+            const path = await invoke<string>('map_export', { format: fileFormat, path: filePath || null });
+            setResponse(`Exported: ${path}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：檢查雙向連接，mirror 時補齊反向連接並更新畫面
+    // backend command: check bidirectional links, with mirror the reverse connections are added and shown
+    const mapNormalize = async (mirror: boolean) => {
+        const normalized = await invoke<MapNormalizeResult>('map_normalize', {
+            data: JSON.stringify(items),
+            mirror,
+        });
+        if (normalized.mirrored.length > 0) {
+            setItems(normalized.items);
+        }
+        return normalized;
+    };
+
+    // 檢查連接按鈕：補齊並顯示結果
+    // check links button: complete and show the result
+    const linksCheck = async () => {
+        try {
+            const normalized = await mapNormalize(true);
+            setIssues(normalized.report.issues);
+            setResponse(normalized.mirrored.length > 0
+                ? normalized.mirrored.map(m => `Added ${m.node} ${direction(m.slot)} → ${m.pos} (${m.len})`).join('\n')
+                : 'All links are bidirectional');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：驗證後保存目前設定，有錯誤時後端拒絕產生
+    // backend command: validate then save current settings, the backend refuses to generate on errors
     const mapSave = async () => {
-        await invoke<string>('map_save', { data: JSON.stringify(items) });
+        try {
+            const normalized = await mapNormalize(mirror);
+            const data = JSON.stringify(normalized.items);
+            const report = await invoke<{ issues: MapIssue[] }>('map_validate', { data });
+            setIssues([...report.issues, ...normalized.report.issues]);
+            const path = await invoke<string>('map_save', { data, routing, mirror, description });
+            setResponse(`Saved: ${path}`);
+            setDescription('');
+            if (versions.length > 0) {
+                await historyList();
+            }
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：列出歷史版本
+    // backend command: list history versions
+    const historyList = async () => {
+        try {
+            setVersions((await invoke<MapVersionInfo[]>('map_history_list')).reverse());
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：與最新版本比較
+    // backend command: compare with the latest version
+    const historyDiff = async (version: number) => {
+        try {
+            const diff = await invoke<MapDiff>('map_history_diff', { from: version, to: null });
+            const edge = (e: { from: number; to: number }) => `${e.from}→${e.to}`;
+            setResponse([
+                `v${diff.from} → v${diff.to}`,
+                `+ nodes: ${diff.added_nodes.join(', ') || '-'}`,
+                `- nodes: ${diff.removed_nodes.join(', ') || '-'}`,
+                `~ nodes: ${diff.changed_nodes.join(', ') || '-'}`,
+                `+ edges: ${diff.added_edges.map(e => `${edge(e)} (${e.len})`).join(', ') || '-'}`,
+                `- edges: ${diff.removed_edges.map(e => `${edge(e)} (${e.len})`).join(', ') || '-'}`,
+                `~ edges: ${diff.changed_edges.map(e => `${edge(e)} (${e.old_len} → ${e.new_len})`).join(', ') || '-'}`,
+            ].join('\n'));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 後端命令：還原指定版本或復原上一次保存，完成後重新載入
+    // backend command: restore a version or undo the last save, then reload
+    const historyRestore = async (version: number | null) => {
+        try {
+            const path = version === null
+                ? await invoke<string>('map_history_undo')
+                : await invoke<string>('map_history_restore', { version });
+            await mapLoad();
+            await historyList();
+            setResponse(version === null ? `Undone: ${path}` : `Restored v${version}: ${path}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
     };
 
     // 新增一筆映射項目 / add a mapping item
@@ -132,15 +332,26 @@ const MapGeneratorComp: React.FC = () => {
         );
     };
 
-    // 更新 pos 或 len 的值
-    // update pos or len for a connection
+    // 更新種類或座標，空白座標視為未設定
+    // update kind or coordinates, empty coordinates are unset
+    const updateItemField = (id: number, patch: Partial<MappingItem>) => {
+        setItems(
+            items.map(item => (item.id === id ? { ...item, ...patch } : item))
+        );
+    };
+    const coordValue = (raw: number) => (Number.isFinite(raw) ? raw : null);
+
+    // 更新連接欄位：pos/len 無效輸入歸零，heading 空白為未設定
+    // update a connection field: invalid pos/len become zero, empty heading is unset
     const updateItemConnect = (
         id: number,
         idx: number,
-        key: 'pos' | 'len',
-        raw: number
+        key: 'pos' | 'len' | 'heading' | 'one_way',
+        raw: number | boolean
     ) => {
-        const val = Number.isFinite(raw) ? raw : 0; // 無效輸入歸零 / invalid input to zero
+        const val = typeof raw === 'boolean'
+            ? raw
+            : Number.isFinite(raw) ? raw : (key === 'heading' ? null : 0); // 無效輸入 / invalid input
         setItems(
             items.map(item => {
                 if (item.id !== id) {
@@ -172,8 +383,132 @@ const MapGeneratorComp: React.FC = () => {
                 >
                     Map Load
                 </button>
+                <input
+                    type="text"
+                    placeholder="Version description"
+                    value={description}
+                    onChange={e => setDescription(e.target.value)}
+                    className="map_generate-input-defalt w-60"
+                />
+                <button
+                    onClick={() => historyRestore(null)}
+                    className="map_generate-button-defalt w-40"
+                >
+                    Undo
+                </button>
+                <button
+                    onClick={historyList}
+                    className="map_generate-button-defalt w-40"
+                >
+                    History
+                </button>
+                <button
+                    onClick={linksCheck}
+                    className="map_generate-button-defalt w-40"
+                >
+                    Mirror Links
+                </button>
+                <label className="flex items-center gap-2">
+                    <input
+                        type="checkbox"
+                        checked={mirror}
+                        onChange={e => setMirror(e.target.checked)}
+                    />
+                    Mirror on save
+                </label>
+                <label className="flex items-center gap-2">
+                    <input
+                        type="checkbox"
+                        checked={routing.next_hop}
+                        onChange={e => setRouting({ ...routing, next_hop: e.target.checked })}
+                    />
+                    Next-hop table
+                </label>
+                <label className="flex items-center gap-2">
+                    <input
+                        type="checkbox"
+                        checked={routing.distance}
+                        onChange={e => setRouting({ ...routing, distance: e.target.checked })}
+                    />
+                    Distance table
+                </label>
             </div>
 
+            {/* 匯入與匯出 / import and export */}
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    placeholder="File path (.graphml / .geojson / .dot / .csv)"
+                    value={filePath}
+                    onChange={e => setFilePath(e.target.value)}
+                    className="map_generate-input-defalt flex-1"
+                />
+                <select
+                    value={fileFormat}
+                    onChange={e => setFileFormat(e.target.value as MapFormat)}
+                    className="map_generate-input-defalt w-32"
+                >
+                    <option value="graphml">GraphML</option>
+                    <option value="geojson">GeoJSON</option>
+                    <option value="dot">DOT</option>
+                    <option value="csv">CSV</option>
+                </select>
+                <button
+                    onClick={mapImport}
+                    className="map_generate-button-defalt w-40"
+                >
+                    Import
+                </button>
+                <button
+                    onClick={mapExport}
+                    className="map_generate-button-defalt w-40"
+                >
+                    Export
+                </button>
+            </div>
+
+            {/* 歷史版本 / history versions */}
+            {versions.length > 0 && (
+                <div className="flex flex-col gap-1 text-xl">
+                    {versions.map(v => (
+                        <div key={v.version} className="flex items-center gap-2">
+                            <span className="w-16">v{v.version}</span>
+                            <span className="w-56">{v.timestamp}</span>
+                            <span className="flex-1">{v.description || '-'} ({v.nodes} nodes, {v.edges} edges)</span>
+                            <button
+                                onClick={() => historyDiff(v.version)}
+                                className="map_generate-button-defalt w-24"
+                            >
+                                Diff
+                            </button>
+                            <button
+                                onClick={() => historyRestore(v.version)}
+                                className="map_generate-button-defalt w-24"
+                            >
+                                Restore
+                            </button>
+                        </div>
+                    ))}
+                </div>
+            )}
+
+            {/* 驗證結果 / validation results */}
+            {issues.length > 0 && (
+                <div className="flex flex-col text-xl">
+                    {issues.map((issue, idx) => (
+                        <span
+                            key={idx}
+                            className={issue.level === 'error' ? 'text-red-500' : 'text-yellow-600'}
+                        >
+                            {issue.level === 'error' ? 'Error' : 'Warning'}
+                            {issue.node !== null ? ` [${issue.node}${issue.slot !== null ? ` ${direction(issue.slot)}` : ''}]` : ''}
+                            {' '}{issue.message}
+                        </span>
+                    ))}
+                </div>
+            )}
+            {response && <pre className="text-xl whitespace-pre-wrap">{response}</pre>}
+
             {/* 動態生成的映射項目列表 / dynamic list of mapping items */}
             {items.map(item => (
                 <div key={item.id} className="border p-4">
@@ -199,6 +534,29 @@ const MapGeneratorComp: React.FC = () => {
                             onChange={e => updateItemName(item.id, e.target.value)}
                             className="map_generate-input-defalt flex-1"
                         />
+                        <select
+                            value={item.kind ?? 'junction'}
+                            onChange={e => updateItemField(item.id, { kind: e.target.value as NodeKind })}
+                            className="map_generate-input-defalt w-32"
+                        >
+                            <option value="junction">Junction</option>
+                            <option value="station">Station</option>
+                            <option value="charger">Charger</option>
+                        </select>
+                        <input
+                            type="number" step={1}
+                            placeholder="X"
+                            value={item.x ?? ''}
+                            onChange={e => updateItemField(item.id, { x: coordValue(e.target.valueAsNumber) })}
+                            className="map_generate-input-defalt w-24"
+                        />
+                        <input
+                            type="number" step={1}
+                            placeholder="Y"
+                            value={item.y ?? ''}
+                            onChange={e => updateItemField(item.id, { y: coordValue(e.target.valueAsNumber) })}
+                            className="map_generate-input-defalt w-24"
+                        />
                     </div>
                     {/* pos/len 輸入欄位，分兩排顯示，每排 4 欄 / pos/len inputs in 2 rows of 4 columns */}
                     <div className="grid grid-cols-4 gap-4">
@@ -231,8 +589,39 @@ const MapGeneratorComp: React.FC = () => {
                                             e.target.valueAsNumber
                                         )
                                     }
-                                    className="map_generate-input-defalt"
+                                    className="map_generate-input-defalt mb-1.5"
                                 />
+                                <div className="flex items-center gap-2">
+                                    <input
+                                        type="number" min={-180} max={359} step={1}
+                                        placeholder={`HDG ${idx * 45}`}
+                                        value={connect.heading ?? ''}
+                                        onChange={e =>
+                                            updateItemConnect(
+                                                item.id,
+                                                idx,
+                                                'heading',
+                                                e.target.valueAsNumber
+                                            )
+                                        }
+                                        className="map_generate-input-defalt flex-1"
+                                    />
+                                    <label className="flex items-center gap-1">
+                                        <input
+                                            type="checkbox"
+                                            checked={connect.one_way ?? false}
+                                            onChange={e =>
+                                                updateItemConnect(
+                                                    item.id,
+                                                    idx,
+                                                    'one_way',
+                                                    e.target.checked
+                                                )
+                                            }
+                                        />
+                                        One-way
+                                    </label>
+                                </div>
                             </div>
                         ))}
                     </div>
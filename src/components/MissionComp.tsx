@@ -0,0 +1,188 @@
+// 任務元件：以地圖節點編輯任務，規劃路徑後送出航點並追蹤進度
+// Mission component: edit missions over map nodes, send the planned waypoints and track progress
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// Mission / MissionRun：需與後端一致
+// Must match backend Mission / MissionRun
+type MissionStep = { type: 'goto'; node: number } | { type: 'wait'; ms: number };
+interface Mission {
+    name: string;
+    steps: MissionStep[];
+}
+interface Waypoint {
+    node: number;
+    heading: number;
+    len: number;
+    wait_ms: number;
+}
+interface MissionRun {
+    id: number;
+    name: string;
+    start: number;
+    status: 'planned' | 'running' | 'paused' | 'completed' | 'aborted';
+    waypoints: Waypoint[];
+    sent: number;
+    reached: number;
+}
+
+// 步驟文字格式："goto 3, wait 5000, goto 1"
+// Step text format: "goto 3, wait 5000, goto 1"
+const stepsParse = (text: string): MissionStep[] =>
+    text.split(',').map(s => s.trim()).filter(s => s).map(s => {
+        const [kind, value] = s.split(/\s+/);
+        const num = Number(value);
+        if (!Number.isInteger(num) || num < 0) {
+            throw `Invalid step: ${s}`;
+        }
+        if (kind === 'goto') return { type: 'goto', node: num };
+        if (kind === 'wait') return { type: 'wait', ms: num };
+        throw `Unknown step: ${s}`;
+    });
+
+const stepsFormat = (steps: MissionStep[]) =>
+    steps.map(s => (s.type === 'goto' ? `goto ${s.node}` : `wait ${s.ms}`)).join(', ');
+
+const MissionComp = () => {
+    const [name, setName] = useState('');
+    const [stepsText, setStepsText] = useState('');
+    const [start, setStart] = useState('');
+    const [missions, setMissions] = useState<Mission[]>([]);
+    const [run, setRun] = useState<MissionRun | null>(null);
+    const [response, setResponse] = useState('');
+
+    useEffect(() => {
+        invoke<Mission[]>('map_mission_list').then(setMissions).catch(() => {});
+        invoke<MissionRun | null>('map_mission_status').then(setRun).catch(() => {});
+        const unlisten = listen<MissionRun>('mission-progress', event => setRun(event.payload));
+        return () => {
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const startNode = () => (start ? Number(start) : null);
+
+    const save = async () => {
+        try {
+            const mission: Mission = { name, steps: stepsParse(stepsText) };
+            setMissions(await invoke<Mission[]>('map_mission_save', { mission }));
+            setResponse(`Mission ${name} saved`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const remove = async (target: string) => {
+        try {
+            setMissions(await invoke<Mission[]>('map_mission_delete', { name: target }));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const plan = async () => {
+        try {
+            const mission: Mission = { name, steps: stepsParse(stepsText) };
+            setRun(await invoke<MissionRun>('map_mission_plan', { mission, start: startNode() }));
+            setResponse('');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const control = async (cmd: string, args?: Record<string, unknown>) => {
+        try {
+            setRun(await invoke<MissionRun>(cmd, args));
+            setResponse('');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-3 gap-4 items-center">
+                <input
+                    placeholder="Mission name"
+                    value={name}
+                    onChange={e => setName(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="goto 3, wait 5000, goto 1"
+                    value={stepsText}
+                    onChange={e => setStepsText(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="Start id (vehicle position)"
+                    value={start}
+                    onChange={e => setStart(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+            </div>
+            <div className="flex gap-4">
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={save}
+                > Save </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={plan}
+                > Plan </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => control('map_mission_start', { name, start: startNode() })}
+                > Start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => control('map_mission_pause')}
+                > Pause </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => control('map_mission_resume')}
+                > Resume </button>
+                <button
+                    className="bg-red-500 hover:bg-red-600 text-white py-1 px-4 rounded"
+                    onClick={() => control('map_mission_abort')}
+                > Abort </button>
+            </div>
+            <ul>
+                {missions.map(m => (
+                    <li key={m.name} className="flex gap-4 items-center">
+                        <button
+                            className="underline"
+                            onClick={() => { setName(m.name); setStepsText(stepsFormat(m.steps)); }}
+                        >{m.name}</button>
+                        <span>{stepsFormat(m.steps)}</span>
+                        <button
+                            className="text-red-400"
+                            onClick={() => remove(m.name)}
+                        > Delete </button>
+                    </li>
+                ))}
+            </ul>
+            {run && (
+                <div>
+                    <div>
+                        Mission {run.name}: {run.status} / reached {run.reached} / sent {run.sent} / {run.waypoints.length}
+                    </div>
+                    <div>
+                        {run.start} → {run.waypoints.map((w, i) => (
+                            <span key={i} className={i < run.reached ? 'text-green-400' : ''}>
+                                {w.node}{w.wait_ms ? ` (wait ${w.wait_ms} ms)` : ''}{i < run.waypoints.length - 1 ? ' → ' : ''}
+                            </span>
+                        ))}
+                    </div>
+                </div>
+            )}
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default MissionComp;
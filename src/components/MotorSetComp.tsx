@@ -0,0 +1,126 @@
+// 馬達設定與追蹤元件 / Motor setpoint and tracking component
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// MotorTracking：需與後端 MotorTracking 一致
+// Must match backend MotorTracking
+interface MotorTracking {
+    motor: 'left' | 'right';
+    speed_command: number | null;
+    speed_setpoint: number | null;
+    speed_target: number | null;
+    speed_present: number | null;
+    direction_command: number | null;
+    rotate_direction: number | null;
+    setpoint_echo_ok: boolean | null;
+    direction_echo_ok: boolean | null;
+    tracking_error: number | null;
+    settling: boolean;
+    exceeded: boolean;
+    flagged: boolean;
+}
+
+const MOTORS = ['left', 'right'] as const;
+
+const MotorSetComp = () => {
+    const [setpoints, setSetpoints] = useState<Record<string, number>>({
+        left: 0,
+        right: 0,
+    });
+    const [tracking, setTracking] = useState<MotorTracking[]>([]);
+    const [response, setResponse] = useState('');
+
+    // 載入追蹤結果並監聽 motor-tracking 事件
+    // load tracking results and listen to motor-tracking events
+    useEffect(() => {
+        const refresh = async () => {
+            try {
+                setTracking(await invoke<MotorTracking[]>('cmd_motor_tracking'));
+            } catch (error: any) {
+                setResponse(`錯誤：${error}`);
+            }
+        };
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        const unlisten = listen<MotorTracking>('motor-tracking', event => {
+            setTracking(prev =>
+                prev.map(t => (t.motor === event.payload.motor ? event.payload : t))
+            );
+        });
+        return () => {
+            clearInterval(interval);
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const setSpeed = async (motor: string) => {
+        try {
+            await invoke('cmd_motor_set_speed', { motor, setpoint: setpoints[motor] });
+            setResponse(`${motor} setpoint: ${setpoints[motor]}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const setDirection = async (motor: string, direction: 'forward' | 'reverse') => {
+        try {
+            await invoke('cmd_motor_set_direction', { motor, direction });
+            setResponse(`${motor} direction: ${direction}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            {MOTORS.map(motor => {
+                const t = tracking.find(x => x.motor === motor);
+                return (
+                    <div key={motor} className="grid grid-cols-5 gap-4 items-center">
+                        <span>{motor}</span>
+                        <input
+                            type="number" min={0} max={255} step={1}
+                            value={setpoints[motor]}
+                            onChange={e =>
+                                setSetpoints({
+                                    ...setpoints,
+                                    [motor]: Number.isFinite(e.target.valueAsNumber)
+                                        ? e.target.valueAsNumber
+                                        : 0,
+                                })
+                            }
+                            className="map_generate-input-defalt"
+                        />
+                        <button
+                            className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                            onClick={() => setSpeed(motor)}
+                        > Set speed </button>
+                        <button
+                            className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                            onClick={() => setDirection(motor, 'forward')}
+                        > Forward </button>
+                        <button
+                            className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                            onClick={() => setDirection(motor, 'reverse')}
+                        > Reverse </button>
+                        <span className={t?.flagged ? 'col-span-5 text-red-400' : 'col-span-5'}>
+                            cmd {t?.speed_command ?? '-'} / echo {t?.speed_setpoint ?? '-'} /
+                            target {t?.speed_target?.toFixed(2) ?? '-'} /
+                            present {t?.speed_present?.toFixed(2) ?? '-'} /
+                            error {t?.tracking_error?.toFixed(2) ?? '-'}
+                            {t?.settling ? ' (settling)' : ''}
+                            {t?.flagged ? ' (tracking error)' : ''}
+                        </span>
+                    </div>
+                );
+            })}
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default MotorSetComp;
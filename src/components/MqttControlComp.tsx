@@ -0,0 +1,141 @@
+// MQTT 元件：設定代理伺服器與主題，啟動／停止遙測發布
+// MQTT component: configure the broker and topics, start / stop telemetry publishing
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// MqttConfig：需與後端 MqttConfig 一致
+// Must match backend MqttConfig
+interface MqttConfig {
+    host: string;
+    port: number;
+    client_id: string;
+    username: string | null;
+    password?: string | null;
+    telemetry_topic: string;
+    status_topic: string;
+    command_topic: string;
+}
+
+// MqttStatus：需與後端 MqttStatus 一致
+// Must match backend MqttStatus
+interface MqttStatus {
+    running: boolean;
+    connected: boolean;
+    config: MqttConfig;
+    published: number;
+    dropped: number;
+    commands: number;
+}
+
+const defaultConfig: MqttConfig = {
+    host: '127.0.0.1',
+    port: 1883,
+    client_id: 'tauri-app',
+    username: null,
+    telemetry_topic: 'tauri-app/telemetry',
+    status_topic: 'tauri-app/link',
+    command_topic: 'tauri-app/command',
+};
+
+const MqttControlComp = () => {
+    const [config, setConfig] = useState<MqttConfig>(() => {
+        const saved = localStorage.getItem('mqttConfig');
+        return saved ? { ...defaultConfig, ...JSON.parse(saved) } : defaultConfig;
+    });
+    const [password, setPassword] = useState('');
+    const [status, setStatus] = useState<MqttStatus | null>(null);
+    const [response, setResponse] = useState('');
+
+    useEffect(() => {
+        localStorage.setItem('mqttConfig', JSON.stringify(config));
+    }, [config]);
+
+    useEffect(() => {
+        const refresh = () =>
+            invoke<MqttStatus>('cmd_mqtt_status').then(setStatus).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        const unlisten = listen<MqttStatus>('mqtt-status', event => setStatus(event.payload));
+        return () => {
+            clearInterval(interval);
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const field = (key: keyof MqttConfig, placeholder: string) => (
+        <input
+            placeholder={placeholder}
+            value={(config[key] ?? '') as string | number}
+            onChange={e => setConfig({
+                ...config,
+                [key]: key === 'port' ? parseInt(e.target.value, 10) || 0 : e.target.value || (key === 'username' ? null : ''),
+            })}
+            className="map_generate-input-defalt"
+        />
+    );
+
+    const start = async () => {
+        try {
+            const result = await invoke<MqttStatus>('cmd_mqtt_start', {
+                config: { ...config, password: password || null },
+            });
+            setStatus(result);
+            setResponse(`MQTT started: ${result.config.host}:${result.config.port}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const stop = async () => {
+        try {
+            setStatus(await invoke<MqttStatus>('cmd_mqtt_stop'));
+            setResponse('MQTT stopped');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-4 gap-4">
+                {field('host', 'Broker host')}
+                {field('port', 'Port')}
+                {field('client_id', 'Client id')}
+                {field('username', 'Username')}
+                <input
+                    type="password"
+                    placeholder="Password"
+                    value={password}
+                    onChange={e => setPassword(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                {field('telemetry_topic', 'Telemetry topic')}
+                {field('status_topic', 'Link status topic')}
+                {field('command_topic', 'Command topic')}
+            </div>
+            <div className="flex items-center gap-4">
+                <span className="flex-1">
+                    {status?.running
+                        ? `${status.connected ? 'Connected' : 'Connecting'} ${status.config.host}:${status.config.port}, `
+                            + `published ${status.published}, dropped ${status.dropped}, commands ${status.commands}`
+                        : 'Stopped'}
+                </span>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={start}
+                > MQTT start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={stop}
+                > MQTT stop </button>
+            </div>
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default MqttControlComp;
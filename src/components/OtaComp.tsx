@@ -0,0 +1,146 @@
+// 韌體更新元件：經由 WiFi TCP 傳送韌體並顯示進度
+// Firmware update component: sends firmware over WiFi TCP and shows the progress
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// OtaProgress：需與後端 OtaProgress 一致
+// Must match backend OtaProgress
+interface OtaProgress {
+    state: 'idle' | 'connecting' | 'sending' | 'verifying' | 'done' | 'failed' | 'cancelled';
+    error?: string;
+    target: string | null;
+    total_bytes: number;
+    sent_bytes: number;
+    chunk: number;
+    chunks: number;
+    retries: number;
+    sha256: string;
+}
+
+const OtaComp = () => {
+    const [path, setPath] = useState(() => localStorage.getItem('otaPath') || '');
+    const [addr, setAddr] = useState(() => localStorage.getItem('otaAddr') || '');
+    const [chunkSize, setChunkSize] = useState(() => localStorage.getItem('otaChunkSize') || '512');
+    const [progress, setProgress] = useState<OtaProgress | null>(null);
+    const [response, setResponse] = useState('');
+
+    useEffect(() => {
+        localStorage.setItem('otaPath', path);
+        localStorage.setItem('otaAddr', addr);
+        localStorage.setItem('otaChunkSize', chunkSize);
+    }, [path, addr, chunkSize]);
+
+    useEffect(() => {
+        invoke<OtaProgress>('cmd_ota_status').then(setProgress).catch(() => {});
+        const unlisten = listen<OtaProgress>('ota-progress', event => setProgress(event.payload));
+        return () => {
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const start = async () => {
+        try {
+            const n = parseInt(chunkSize, 10);
+            setProgress(await invoke<OtaProgress>('cmd_ota_start', {
+                path,
+                addr: addr || null,
+                deviceId: null,
+                chunkSize: Number.isFinite(n) ? n : null,
+            }));
+            setResponse('');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const cancel = async () => {
+        try {
+            setProgress(await invoke<OtaProgress>('cmd_ota_cancel'));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const mockStart = async () => {
+        try {
+            const mockAddr = await invoke<string>('cmd_ota_mock_start', { port: null, nakEvery: 7 });
+            setAddr(mockAddr);
+            setResponse(`OTA mock device listening at ${mockAddr}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const mockStop = async () => {
+        try {
+            setResponse(await invoke<string>('cmd_ota_mock_stop'));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const percent = progress && progress.total_bytes > 0
+        ? Math.floor(progress.sent_bytes * 100 / progress.total_bytes)
+        : 0;
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-4 gap-4">
+                <input
+                    placeholder="Firmware file path (.bin)"
+                    value={path}
+                    onChange={e => setPath(e.target.value)}
+                    className="map_generate-input-defalt col-span-2"
+                />
+                <input
+                    placeholder="Device TCP address (empty = current link)"
+                    value={addr}
+                    onChange={e => setAddr(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="Chunk size"
+                    value={chunkSize}
+                    onChange={e => setChunkSize(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={start}
+                > OTA start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={cancel}
+                > OTA cancel </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={mockStart}
+                > Mock start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={mockStop}
+                > Mock stop </button>
+            </div>
+            {progress && progress.state !== 'idle' && (
+                <div className="space-y-2">
+                    <div className="w-full bg-gray-500 rounded h-4">
+                        <div className="bg-blue-500 rounded h-4" style={{ width: `${percent}%` }} />
+                    </div>
+                    <div>
+                        {progress.state}{progress.error ? `: ${progress.error}` : ''} {progress.target}
+                        {' '}{progress.chunk}/{progress.chunks} chunks, {progress.sent_bytes}/{progress.total_bytes} bytes
+                        ({percent}%), retries {progress.retries}
+                    </div>
+                    <div className="text-sm break-all">SHA-256 {progress.sha256}</div>
+                </div>
+            )}
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default OtaComp;
@@ -35,12 +35,36 @@ const PageSelectComp = ({ pageName, setPageName }: PageSelectProps) => {
         setPageName(name);
     };
 
+    // handleVCtClick: 選擇 Vehicle Control 頁面
+    // Select the Vehicle Control page
+    const handleVCtClick = () => {
+        const name = "vehicle_control";
+        localStorage.setItem("pageName", name);
+        setPageName(name);
+    };
+
     const handleWFCClick = () => {
         const name = "wifi_control";
         localStorage.setItem("pageName", name);
         setPageName(name);
     };
 
+    // handleRMTClick: 選擇 Remote 頁面
+    // Select the Remote page
+    const handleRMTClick = () => {
+        const name = "remote";
+        localStorage.setItem("pageName", name);
+        setPageName(name);
+    };
+
+    // handleFWClick: 選擇 Firmware 頁面
+    // Select the Firmware page
+    const handleFWClick = () => {
+        const name = "firmware";
+        localStorage.setItem("pageName", name);
+        setPageName(name);
+    };
+
     // handleCGeClick: 選擇 Chart Generate 頁面
     // Select the Chart Generate page
     const handleCGeClick = () => {
@@ -109,6 +133,17 @@ const PageSelectComp = ({ pageName, setPageName }: PageSelectProps) => {
                 Port Control
             </button>
             <div className="border-b-2 border-gray-600"></div>
+            <button
+                onClick={handleVCtClick}
+                className={
+                    pageName === "vehicle_control"
+                        ? "page_select-button_list-select"
+                        : "page_select-button_list-defalt"
+                }
+            >
+                Vehicle Control
+            </button>
+            <div className="border-b-2 border-gray-600"></div>
             <button
                 onClick={handleWFCClick}
                 className={
@@ -120,6 +155,28 @@ const PageSelectComp = ({ pageName, setPageName }: PageSelectProps) => {
                 Wifi Control
             </button>
             <div className="border-b-2 border-gray-600"></div>
+            <button
+                onClick={handleRMTClick}
+                className={
+                    pageName === "remote"
+                        ? "page_select-button_list-select"
+                        : "page_select-button_list-defalt"
+                }
+            >
+                Remote
+            </button>
+            <div className="border-b-2 border-gray-600"></div>
+            <button
+                onClick={handleFWClick}
+                className={
+                    pageName === "firmware"
+                        ? "page_select-button_list-select"
+                        : "page_select-button_list-defalt"
+                }
+            >
+                Firmware
+            </button>
+            <div className="border-b-2 border-gray-600"></div>
             <button
                 onClick={handleCGeClick}
                 className={
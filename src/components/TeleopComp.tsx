@@ -0,0 +1,160 @@
+// 遙控元件：鍵盤 WASD / 方向鍵控制，按住空白鍵為失效開關
+// Teleop component: WASD / arrow keys drive, hold Space as the dead-man switch
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+// TeleopReport：需與後端 TeleopReport 一致
+// Must match backend TeleopReport
+interface TeleopReport {
+    enabled: boolean;
+    source: 'keyboard' | 'gamepad' | null;
+    active: boolean;
+    left: number;
+    right: number;
+    max_speed: number;
+}
+
+// 鍵盤輸入送出週期（ms），需小於後端失效開關逾時
+// Keyboard input period in ms, must be shorter than the backend dead-man timeout
+const INPUT_PERIOD_MS = 50;
+
+const TeleopComp = () => {
+    const [maxSpeed, setMaxSpeed] = useState(100);
+    const [report, setReport] = useState<TeleopReport | null>(null);
+    const [response, setResponse] = useState('');
+    // pressed：目前按下的按鍵
+    // currently pressed keys
+    const pressed = useRef<Set<string>>(new Set());
+
+    const refresh = async () => {
+        try {
+            setReport(await invoke<TeleopReport>('cmd_teleop_status'));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 定期更新狀態
+    // refresh status periodically
+    useEffect(() => {
+        refresh();
+        const interval = setInterval(refresh, 500);
+        return () => clearInterval(interval);
+    }, []);
+
+    // 鍵盤模式：記錄按鍵並在按住空白鍵時週期送出輸入
+    // keyboard mode: track keys and send input periodically while Space is held
+    useEffect(() => {
+        if (report?.source !== 'keyboard') {
+            return;
+        }
+        const down = (e: KeyboardEvent) => {
+            pressed.current.add(e.code);
+            if (e.code === 'Space' || e.code.startsWith('Arrow')) {
+                e.preventDefault();
+            }
+        };
+        const up = (e: KeyboardEvent) => {
+            pressed.current.delete(e.code);
+            if (e.code === 'Space') {
+                invoke('cmd_teleop_input', { throttle: 0, steer: 0, deadman: false }).catch(
+                    () => {}
+                );
+            }
+        };
+        const blur = () => pressed.current.clear();
+        const interval = setInterval(() => {
+            const keys = pressed.current;
+            if (!keys.has('Space')) {
+                return;
+            }
+            const axis = (plus: string[], minus: string[]) =>
+                (plus.some(k => keys.has(k)) ? 1 : 0) -
+                (minus.some(k => keys.has(k)) ? 1 : 0);
+            const throttle = axis(['KeyW', 'ArrowUp'], ['KeyS', 'ArrowDown']);
+            const steer = axis(['KeyD', 'ArrowRight'], ['KeyA', 'ArrowLeft']);
+            invoke('cmd_teleop_input', { throttle, steer, deadman: true }).catch(
+                (error: any) => setResponse(`錯誤：${error}`)
+            );
+        }, INPUT_PERIOD_MS);
+        window.addEventListener('keydown', down);
+        window.addEventListener('keyup', up);
+        window.addEventListener('blur', blur);
+        return () => {
+            clearInterval(interval);
+            window.removeEventListener('keydown', down);
+            window.removeEventListener('keyup', up);
+            window.removeEventListener('blur', blur);
+            pressed.current.clear();
+        };
+    }, [report?.source]);
+
+    const start = async (source: 'keyboard' | 'gamepad') => {
+        try {
+            setReport(await invoke<TeleopReport>('cmd_teleop_start', { source, maxSpeed }));
+            setResponse(
+                source === 'keyboard'
+                    ? 'Hold Space and use WASD / arrow keys'
+                    : 'Hold RB and use left stick Y / right stick X'
+            );
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const stop = async () => {
+        try {
+            await invoke('cmd_teleop_stop');
+            await refresh();
+            setResponse('Teleop stopped');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-4 gap-4 items-center">
+                <label className="flex flex-col">
+                    Max speed
+                    <input
+                        type="number" min={0} step="any"
+                        value={maxSpeed}
+                        onChange={e =>
+                            setMaxSpeed(
+                                Number.isFinite(e.target.valueAsNumber)
+                                    ? e.target.valueAsNumber
+                                    : 0
+                            )
+                        }
+                        className="map_generate-input-defalt"
+                    />
+                </label>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => start('keyboard')}
+                > Keyboard </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => start('gamepad')}
+                > Gamepad </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={stop}
+                > Stop teleop </button>
+            </div>
+            <div className={report?.active ? 'text-green-400' : ''}>
+                Teleop: {report?.enabled ? report.source : 'off'}
+                {report?.enabled
+                    ? ` / L ${report.left.toFixed(1)} R ${report.right.toFixed(1)}`
+                    : ''}
+            </div>
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default TeleopComp;
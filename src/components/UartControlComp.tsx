@@ -1,52 +1,106 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 
+// 馬達與資料種類，需與後端 MotorSide / MotorDataType 一致
+// Motors and quantities, must match backend MotorSide / MotorDataType
+const MOTORS = ["left", "right"] as const;
+const QUANTITIES = [
+    "speed_present",
+    "adc_value",
+    "speed_setpoint",
+    "rotate_direction",
+] as const;
+
+type Motor = typeof MOTORS[number];
+type Quantity = typeof QUANTITIES[number];
+type Mode = "stop" | "once" | "start";
+
+const streamKey = (motor: Motor, quantity: Quantity) => `${motor}:${quantity}`;
+
 const UartControlComp = () => {
     const [response, setResponse] = useState(() => sessionStorage.getItem("TRResponse") || "");
     useEffect(() => { sessionStorage.setItem("TRResponse", response); }, [response]);
 
-    const cmd_stop = async () => {
-        try {
-            const result = await invoke("cmd_send_spd_stop");
-            setResponse(result as string);
-        } catch (error: any) {
-            setResponse(`錯誤：${error}`);
-        }
-    };
-    const cmd_once = async () => {
-        try {
-            const result = await invoke("cmd_send_spd_once");
-            setResponse(result as string);
-        } catch (error: any) {
-            setResponse(`錯誤：${error}`);
-        }
-    };
-    const cmd_start = async () => {
+    // streaming：各串流是否持續回傳
+    // streaming: whether each stream is reporting continuously
+    const [streaming, setStreaming] = useState<Record<string, boolean>>(() => {
+        const stored = sessionStorage.getItem("TRStreaming");
+        return stored ? JSON.parse(stored) : {};
+    });
+    useEffect(() => {
+        sessionStorage.setItem("TRStreaming", JSON.stringify(streaming));
+    }, [streaming]);
+
+    // sendSelect：送出單一或多個串流的回傳模式
+    // send report mode of one or more streams
+    const sendSelect = async (
+        selections: { motor: Motor; quantity: Quantity; mode: Mode }[]
+    ) => {
         try {
-            const result = await invoke("cmd_send_spd_start");
-            setResponse(result as string);
+            await invoke("cmd_send_data_select", { selections });
+            setStreaming(prev => {
+                const next = { ...prev };
+                selections.forEach(s => {
+                    if (s.mode !== "once") {
+                        next[streamKey(s.motor, s.quantity)] = s.mode === "start";
+                    }
+                });
+                return next;
+            });
+            setResponse(
+                selections.map(s => `${s.motor} ${s.quantity}: ${s.mode}`).join("\n")
+            );
         } catch (error: any) {
             setResponse(`錯誤：${error}`);
         }
     };
 
+    // stopAll：停止所有串流
+    // stop all streams
+    const stopAll = () =>
+        sendSelect(
+            MOTORS.flatMap(motor =>
+                QUANTITIES.map(quantity => ({ motor, quantity, mode: "stop" as Mode }))
+            )
+        );
+
     return (
         <div className="
             flex flex-col p-4 space-y-4
             dark:bg-gray-700 text-white text-xl md:text-3xl"
         >
+            <div className="grid grid-cols-3 gap-4 text-xl">
+                {MOTORS.flatMap(motor =>
+                    QUANTITIES.map(quantity => {
+                        const on = streaming[streamKey(motor, quantity)] || false;
+                        return (
+                            <div key={streamKey(motor, quantity)} className="contents">
+                                <span className="self-center">
+                                    {motor} {quantity}
+                                </span>
+                                <button
+                                    className={
+                                        on
+                                            ? "bg-green-600 hover:bg-green-700 text-white py-1 px-4 rounded"
+                                            : "bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                    }
+                                    onClick={() =>
+                                        sendSelect([{ motor, quantity, mode: on ? "stop" : "start" }])
+                                    }
+                                > {on ? "Streaming" : "Stopped"} </button>
+                                <button
+                                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                    onClick={() => sendSelect([{ motor, quantity, mode: "once" }])}
+                                > Once </button>
+                            </div>
+                        );
+                    })
+                )}
+            </div>
             <button
                 className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
-                onClick={cmd_stop}
-            > cmd_stop </button>
-            <button
-                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
-                onClick={cmd_once}
-            > cmd_once </button>
-            <button
-                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
-                onClick={cmd_start}
-            > cmd_start </button>
+                onClick={stopAll}
+            > Stop all </button>
             <div>
                 <pre className="min-h-[8em]">
                     {response || ""}
@@ -10,6 +10,10 @@ const UartPortOCComp = () => {
     const [selectedPort, setSelectPort] = useState(
         () => localStorage.getItem("selectedPort") || ""
     );
+    // parity：同位元設定 / parity setting
+    const [parity, setParity] = useState(
+        () => localStorage.getItem("selectedParity") || "none"
+    );
     // isOpen：埠是否已開啟
     // whether the port is open
     const [isOpen, setIsOpen] = useState(false);
@@ -23,7 +27,8 @@ const UartPortOCComp = () => {
     // sync selectedPort to localStorage
     useEffect(() => {
         localStorage.setItem("selectedPort", selectedPort);
-    }, [selectedPort]);
+        localStorage.setItem("selectedParity", parity);
+    }, [selectedPort, parity]);
 
     // 同步 response 到 sessionStorage
     // sync response to sessionStorage
@@ -59,7 +64,7 @@ const UartPortOCComp = () => {
     // openPort：呼叫後端開埠
     // call backend to open port
     const openPort = async () => {
-        const result = await invoke("cmd_open_port_async", { portName: selectedPort });
+        const result = await invoke("cmd_open_port_async", { portName: selectedPort, parity });
         const message = `${result}`;
         setResponse(message);
         setIsOpen(true);
@@ -90,6 +95,16 @@ const UartPortOCComp = () => {
                     </option>
                 ))}
             </select>
+            {/* 下拉選單：同位元 / dropdown for parity */}
+            <select
+                value={parity}
+                onChange={(e) => setParity(e.target.value)}
+                className="open_close_port-input-defalt"
+            >
+                <option value="none" className="open_close_port-input-defalt">Parity none</option>
+                <option value="even" className="open_close_port-input-defalt">Parity even</option>
+                <option value="odd" className="open_close_port-input-defalt">Parity odd</option>
+            </select>
             {/* 根據 isOpen 顯示 Open/Close 按鈕 / toggle Open/Close button based on isOpen */}
             {isOpen ? (
                 <button onClick={closePort} className="open_close_port-button-defalt">
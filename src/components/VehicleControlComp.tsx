@@ -0,0 +1,169 @@
+// 車輛控制元件 / Vehicle control component
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// LinkReport：需與後端 LinkReport 一致
+// Must match backend LinkReport
+interface LinkReport {
+    status: 'disconnected' | 'waiting' | 'alive' | 'stale';
+    since_last_ms: number | null;
+    last_seq: number | null;
+}
+
+const VehicleControlComp = () => {
+    // speed / radius / duration：移動參數
+    // motion parameters
+    const [speed, setSpeed] = useState(0);
+    const [radius, setRadius] = useState(0);
+    const [left, setLeft] = useState(0);
+    const [right, setRight] = useState(0);
+    const [durationMs, setDurationMs] = useState(0);
+    // response：後端命令回傳訊息
+    // backend command response message
+    const [response, setResponse] = useState(
+        () => sessionStorage.getItem('VCResponse') || ''
+    );
+    useEffect(() => {
+        sessionStorage.setItem('VCResponse', response);
+    }, [response]);
+    // link：MCU 心跳連線狀態
+    // MCU heartbeat link status
+    const [link, setLink] = useState<LinkReport | null>(null);
+
+    // 載入連線狀態並監聽心跳事件
+    // load link status and listen to heartbeat events
+    useEffect(() => {
+        invoke<LinkReport>('cmd_mcu_link_status').then(setLink).catch(() => {});
+        const unlistenStatus = listen<LinkReport>('mcu-link-status', event => {
+            setLink(event.payload);
+        });
+        const unlistenAlert = listen<LinkReport>('mcu-link-alert', event => {
+            setResponse(
+                `警告：MCU 心跳逾時 / MCU heartbeat lost (${event.payload.since_last_ms ?? '-'} ms)`
+            );
+        });
+        return () => {
+            unlistenStatus.then(f => f());
+            unlistenAlert.then(f => f());
+        };
+    }, []);
+
+    // sendMotion：送出移動指令
+    // send a motion command
+    const sendMotion = async (motion: Record<string, unknown>) => {
+        try {
+            await invoke('cmd_vehicle_control', { motion });
+            setResponse(`OK: ${motion.mode}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // emergencyStop：緊急停止
+    // emergency stop
+    const emergencyStop = async () => {
+        try {
+            await invoke('cmd_vehicle_emergency_stop');
+            setResponse('Emergency stop sent');
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const numberInput = (
+        label: string,
+        value: number,
+        setValue: (v: number) => void
+    ) => (
+        <label className="flex flex-col text-xl">
+            {label}
+            <input
+                type="number"
+                step="any"
+                value={value}
+                onChange={e =>
+                    setValue(
+                        Number.isFinite(e.target.valueAsNumber)
+                            ? e.target.valueAsNumber
+                            : 0
+                    )
+                }
+                className="map_generate-input-defalt"
+            />
+        </label>
+    );
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl md:text-3xl"
+        >
+            <div
+                className={
+                    link?.status === 'stale'
+                        ? 'text-red-400'
+                        : link?.status === 'alive'
+                          ? 'text-green-400'
+                          : ''
+                }
+            >
+                Link: {link?.status ?? '-'}
+            </div>
+            <div className="grid grid-cols-5 gap-4">
+                {numberInput('Speed', speed, setSpeed)}
+                {numberInput('Radius', radius, setRadius)}
+                {numberInput('Left', left, setLeft)}
+                {numberInput('Right', right, setRight)}
+                {numberInput('Duration (ms)', durationMs, setDurationMs)}
+            </div>
+            <div className="grid grid-cols-3 gap-4">
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() =>
+                        sendMotion({ mode: 'left', speed, radius, duration_ms: durationMs })
+                    }
+                > Left </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() =>
+                        sendMotion({ mode: 'forward', speed, duration_ms: durationMs })
+                    }
+                > Forward </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() =>
+                        sendMotion({ mode: 'right', speed, radius, duration_ms: durationMs })
+                    }
+                > Right </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() =>
+                        sendMotion({ mode: 'differential', left, right, duration_ms: durationMs })
+                    }
+                > Differential </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() =>
+                        sendMotion({ mode: 'backward', speed, duration_ms: durationMs })
+                    }
+                > Backward </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => sendMotion({ mode: 'stop' })}
+                > Stop </button>
+            </div>
+            <button
+                className="bg-red-600 hover:bg-red-700 text-white py-2 px-4 rounded"
+                onClick={emergencyStop}
+            > EMERGENCY STOP </button>
+            <div>
+                <pre className="min-h-[8em]">
+                    {response || ''}
+                </pre>
+            </div>
+        </div>
+    );
+};
+
+export default VehicleControlComp;
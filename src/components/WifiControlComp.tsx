@@ -1,13 +1,178 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// TcpPeerInfo：需與後端 TcpPeerInfo 一致
+// Must match backend TcpPeerInfo
+interface TcpPeerInfo {
+    addr: string;
+    outbound: boolean;
+    connected_secs: number;
+    rx_frames: number;
+    tx_frames: number;
+}
+
+// WifiInterface：需與後端 WifiInterface 一致
+// Must match backend WifiInterface
+interface WifiInterface {
+    name: string;
+    ip: string;
+    broadcast: string | null;
+    loopback: boolean;
+}
+
+// WifiRxReport：需與後端 WifiRxReport 一致
+// Must match backend WifiRxReport
+interface WifiSourceStats {
+    protocol: 'tcp' | 'udp';
+    addr: string;
+    received: number;
+    bytes: number;
+    overflow: number;
+    dispatched: number;
+    raw: number;
+    ignored: number;
+    unhandled: number;
+    invalid: number;
+    last_seen_ms: number;
+}
+interface WifiRxReport {
+    tcp_overflow: number;
+    udp_overflow: number;
+    sources: WifiSourceStats[];
+}
+
+// WifiStatus：需與後端 WifiStatus 一致
+// Must match backend WifiStatus
+interface WifiStatus {
+    running: boolean;
+    config: { bind_ip: string; tcp_port: number; udp_port: number };
+    tcp_addr: string | null;
+    udp_addr: string | null;
+    uptime_secs: number | null;
+    peers: TcpPeerInfo[];
+}
+
+// 設定值存於 localStorage
+// settings persisted in localStorage
+const loadSetting = (key: string, fallback: string) =>
+    localStorage.getItem(key) || fallback;
 
 const WifiControlComp = () => {
     const [response, setResponse] = useState(() => sessionStorage.getItem("TRResponse") || "");
     useEffect(() => { sessionStorage.setItem("TRResponse", response); }, [response]);
 
+    // peers：目前 TCP 連線
+    // current TCP connections
+    const [peers, setPeers] = useState<TcpPeerInfo[]>([]);
+    useEffect(() => {
+        const refresh = () =>
+            invoke<TcpPeerInfo[]>("cmd_wifi_peers").then(setPeers).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        const unlisten = listen<TcpPeerInfo[]>("wifi-peers", event => setPeers(event.payload));
+        return () => {
+            clearInterval(interval);
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    // rxStats：各來源接收統計
+    // per-source receive statistics
+    const [rxStats, setRxStats] = useState<WifiRxReport | null>(null);
+    useEffect(() => {
+        const refresh = () =>
+            invoke<WifiRxReport>("cmd_wifi_rx_stats").then(setRxStats).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        return () => clearInterval(interval);
+    }, []);
+
+    const rxStatsReset = async () => {
+        try {
+            await invoke("cmd_wifi_rx_stats_reset");
+            setRxStats(await invoke<WifiRxReport>("cmd_wifi_rx_stats"));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    // 監聽設定：介面、綁定位址與埠號
+    // listener settings: interface, bind address and ports
+    const [interfaces, setInterfaces] = useState<WifiInterface[]>([]);
+    const [iface, setIface] = useState(() => loadSetting("wifiInterface", ""));
+    const [bindAddr, setBindAddr] = useState(() => loadSetting("wifiBindAddr", ""));
+    const [tcpPort, setTcpPort] = useState(() => loadSetting("wifiTcpPort", "60000"));
+    const [udpPort, setUdpPort] = useState(() => loadSetting("wifiUdpPort", "60001"));
+    // 傳送設定：目標、協定與資料
+    // transmit settings: target, protocol and data
+    const [target, setTarget] = useState(() => loadSetting("wifiTarget", "192.168.0.20"));
+    const [protocol, setProtocol] = useState(() => loadSetting("wifiProtocol", "udp"));
+    const [data, setData] = useState("Hello from rust");
+
+    useEffect(() => {
+        localStorage.setItem("wifiInterface", iface);
+        localStorage.setItem("wifiBindAddr", bindAddr);
+        localStorage.setItem("wifiTcpPort", tcpPort);
+        localStorage.setItem("wifiUdpPort", udpPort);
+        localStorage.setItem("wifiTarget", target);
+        localStorage.setItem("wifiProtocol", protocol);
+    }, [iface, bindAddr, tcpPort, udpPort, target, protocol]);
+
+    useEffect(() => {
+        invoke<WifiInterface[]>("cmd_wifi_interfaces").then(setInterfaces).catch(() => {});
+    }, []);
+
+    const port = (value: string) => {
+        const n = parseInt(value, 10);
+        return Number.isFinite(n) ? n : null;
+    };
+
+    // status：監聽狀態
+    // listener status
+    const [status, setStatus] = useState<WifiStatus | null>(null);
+    useEffect(() => {
+        const refresh = () =>
+            invoke<WifiStatus>("cmd_wifi_status").then(setStatus).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        const unlisten = listen<WifiStatus>("wifi-status", event => setStatus(event.payload));
+        return () => {
+            clearInterval(interval);
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const lifecycle = async (cmd: "cmd_wifi_start" | "cmd_wifi_restart" | "cmd_wifi_stop") => {
+        try {
+            const args = cmd === "cmd_wifi_stop" ? {} : {
+                bindAddr: bindAddr || null,
+                interface: iface || null,
+                tcpPort: port(tcpPort),
+                udpPort: port(udpPort),
+            };
+            const result = await invoke<WifiStatus>(cmd, args);
+            setStatus(result);
+            setResponse(result.running
+                ? `WiFi listener running: TCP ${result.tcp_addr}, UDP ${result.udp_addr}`
+                : "WiFi listener stopped");
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
     const serial_c = async () => {
         try {
-            const result = await invoke("cmd_wifi_transmie");
+            const result = await invoke("cmd_wifi_transmie", { target, protocol, data });
+            setResponse(result as string);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const disconnect = async (peer: string) => {
+        try {
+            const result = await invoke("cmd_wifi_disconnect", { peer });
             setResponse(result as string);
         } catch (error: any) {
             setResponse(`錯誤：${error}`);
@@ -19,10 +184,122 @@ const WifiControlComp = () => {
             flex flex-col p-4 space-y-4
             dark:bg-gray-700 text-white text-xl md:text-3xl"
         >
-            <button
-                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
-                onClick={serial_c}
-            > Transmit </button>
+            <div className="grid grid-cols-5 gap-4 text-xl">
+                <select
+                    value={iface}
+                    onChange={e => setIface(e.target.value)}
+                    className="open_close_port-input-defalt"
+                >
+                    <option value="" className="open_close_port-input-defalt">Any interface</option>
+                    {interfaces
+                        .filter(i => i.ip.includes("."))
+                        .map(i => (
+                            <option key={`${i.name}-${i.ip}`} value={i.name} className="open_close_port-input-defalt">
+                                {i.name} ({i.ip})
+                            </option>
+                        ))}
+                </select>
+                <input
+                    placeholder="Bind address"
+                    value={bindAddr}
+                    onChange={e => setBindAddr(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="TCP port"
+                    value={tcpPort}
+                    onChange={e => setTcpPort(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <input
+                    placeholder="UDP port"
+                    value={udpPort}
+                    onChange={e => setUdpPort(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <div className="flex gap-2">
+                    <button
+                        className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                        onClick={() => lifecycle("cmd_wifi_start")}
+                    > Start </button>
+                    <button
+                        className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                        onClick={() => lifecycle("cmd_wifi_restart")}
+                    > Restart </button>
+                    <button
+                        className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                        onClick={() => lifecycle("cmd_wifi_stop")}
+                    > Stop </button>
+                </div>
+            </div>
+            <div className={`text-xl ${status?.running ? "text-green-400" : ""}`}>
+                {status?.running
+                    ? `Running: TCP ${status.tcp_addr}, UDP ${status.udp_addr}, ${status.uptime_secs}s, ${status.peers.length} peers`
+                    : "Stopped"}
+            </div>
+            <div className="grid grid-cols-4 gap-4 text-xl">
+                <input
+                    placeholder="Target ip[:port]"
+                    value={target}
+                    onChange={e => setTarget(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <select
+                    value={protocol}
+                    onChange={e => setProtocol(e.target.value)}
+                    className="open_close_port-input-defalt"
+                >
+                    <option value="udp" className="open_close_port-input-defalt">UDP</option>
+                    <option value="tcp" className="open_close_port-input-defalt">TCP</option>
+                </select>
+                <input
+                    placeholder="Data"
+                    value={data}
+                    onChange={e => setData(e.target.value)}
+                    className="map_generate-input-defalt"
+                />
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={serial_c}
+                > Transmit </button>
+            </div>
+            <div className="text-xl space-y-2">
+                {peers.length === 0 ? (
+                    <div>No TCP peers</div>
+                ) : (
+                    peers.map(peer => (
+                        <div key={peer.addr} className="flex items-center gap-4">
+                            <span className="flex-1">
+                                {peer.addr} {peer.outbound ? "(out)" : "(in)"} {peer.connected_secs}s
+                                rx {peer.rx_frames} / tx {peer.tx_frames}
+                            </span>
+                            <button
+                                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                onClick={() => disconnect(peer.addr)}
+                            > Disconnect </button>
+                        </div>
+                    ))
+                )}
+            </div>
+            <div className="text-xl space-y-2">
+                <div className="flex items-center gap-4">
+                    <span className="flex-1">
+                        Overflow: TCP {rxStats?.tcp_overflow ?? 0} / UDP {rxStats?.udp_overflow ?? 0}
+                    </span>
+                    <button
+                        className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                        onClick={rxStatsReset}
+                    > Reset stats </button>
+                </div>
+                {rxStats?.sources.map(src => (
+                    <div key={`${src.protocol}-${src.addr}`}>
+                        {src.protocol.toUpperCase()} {src.addr}: rx {src.received} ({src.bytes} B)
+                        {" "}ok {src.dispatched} raw {src.raw} ignored {src.ignored}
+                        {" "}unhandled {src.unhandled} invalid {src.invalid} overflow {src.overflow}
+                        {" "}{(src.last_seen_ms / 1000).toFixed(1)}s
+                    </div>
+                ))}
+            </div>
             <div>
                 <pre className="min-h-[8em]">
                     {response || ""}
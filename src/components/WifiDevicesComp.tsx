@@ -0,0 +1,191 @@
+// 裝置探索元件：廣播探詢並顯示裝置登錄表
+// Device discovery component: broadcast probes and show the device registry
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// DeviceInfo：需與後端 DeviceInfo 一致
+// Must match backend DeviceInfo
+interface DeviceInfo {
+    id: number;
+    name: string;
+    addr: string;
+    tcp_addr: string;
+    firmware: string;
+    capabilities: string[];
+    status: 'online' | 'stale' | 'offline';
+    last_seen_ms: number;
+}
+
+// McuLink：需與後端 McuLink 一致
+// Must match backend McuLink
+type McuLink =
+    | { kind: 'uart' }
+    | { kind: 'wifi'; addr: string; protocol: 'tcp' | 'udp'; device_id: number };
+
+const statusColor = {
+    online: 'text-green-400',
+    stale: 'text-yellow-400',
+    offline: 'text-red-400',
+};
+
+const WifiDevicesComp = () => {
+    const [devices, setDevices] = useState<DeviceInfo[]>([]);
+    const [target, setTarget] = useState('');
+    const [auto, setAuto] = useState(false);
+    const [response, setResponse] = useState('');
+    const [link, setLink] = useState<McuLink>({ kind: 'uart' });
+
+    useEffect(() => {
+        invoke<McuLink>('cmd_mcu_link_get').then(setLink).catch(() => {});
+    }, []);
+
+    const linkSet = async (next: McuLink) => {
+        try {
+            setLink(await invoke<McuLink>('cmd_mcu_link_set', { link: next }));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    useEffect(() => {
+        const refresh = () =>
+            invoke<DeviceInfo[]>('cmd_wifi_devices').then(setDevices).catch(() => {});
+        refresh();
+        const interval = setInterval(refresh, 1000);
+        const unlisten = listen<DeviceInfo[]>('wifi-devices', event => setDevices(event.payload));
+        return () => {
+            clearInterval(interval);
+            unlisten.then(f => f());
+        };
+    }, []);
+
+    const discover = async (enableAuto: boolean) => {
+        try {
+            const result = await invoke<string>('cmd_wifi_discover', {
+                target: target || null,
+                auto: enableAuto,
+            });
+            setAuto(enableAuto);
+            setResponse(result);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const remove = async (id: number | null) => {
+        try {
+            setDevices(await invoke<DeviceInfo[]>('cmd_wifi_device_remove', { id }));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const mockStart = async () => {
+        try {
+            const addr = await invoke<string>('cmd_wifi_mock_device_start', {
+                port: null,
+                deviceId: null,
+            });
+            setTarget(addr);
+            setResponse(`Mock device listening at ${addr}`);
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    const mockStop = async () => {
+        try {
+            setResponse(await invoke<string>('cmd_wifi_mock_device_stop'));
+        } catch (error: any) {
+            setResponse(`錯誤：${error}`);
+        }
+    };
+
+    return (
+        <div className="
+            flex flex-col p-4 space-y-4
+            dark:bg-gray-700 text-white text-xl"
+        >
+            <div className="grid grid-cols-6 gap-4">
+                <input
+                    placeholder="Probe target (empty = broadcast)"
+                    value={target}
+                    onChange={e => setTarget(e.target.value)}
+                    className="map_generate-input-defalt col-span-2"
+                />
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => discover(auto)}
+                > Discover </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => discover(!auto)}
+                > {auto ? 'Auto off' : 'Auto on'} </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={mockStart}
+                > Mock start </button>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={mockStop}
+                > Mock stop </button>
+            </div>
+            <div className="flex items-center gap-4">
+                <span className="flex-1">
+                    MCU link: {link.kind === 'uart'
+                        ? 'UART'
+                        : `WiFi #${link.device_id} ${link.addr} (${link.protocol.toUpperCase()})`}
+                </span>
+                <button
+                    className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                    onClick={() => linkSet({ kind: 'uart' })}
+                > Use UART </button>
+            </div>
+            <div className="space-y-2">
+                {devices.length === 0 ? (
+                    <div>No devices</div>
+                ) : (
+                    devices.map(device => (
+                        <div key={device.id} className="flex items-center gap-4">
+                            <span className={`flex-1 ${statusColor[device.status]}`}>
+                                #{device.id} {device.name} v{device.firmware} @ {device.addr}
+                                (TCP {device.tcp_addr}) [{device.capabilities.join(', ')}]
+                                {' '}{device.status} {(device.last_seen_ms / 1000).toFixed(1)}s
+                            </span>
+                            <button
+                                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                onClick={() => setTarget(device.addr)}
+                            > Use </button>
+                            <button
+                                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                onClick={() => {
+                                    const tcp = device.capabilities.includes('tcp');
+                                    linkSet({
+                                        kind: 'wifi',
+                                        addr: tcp ? device.tcp_addr : device.addr,
+                                        protocol: tcp ? 'tcp' : 'udp',
+                                        device_id: device.id,
+                                    });
+                                }}
+                            > Link </button>
+                            <button
+                                className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                                onClick={() => remove(device.id)}
+                            > Remove </button>
+                        </div>
+                    ))
+                )}
+                {devices.length > 0 && (
+                    <button
+                        className="bg-blue-500 hover:bg-blue-600 text-white py-1 px-4 rounded"
+                        onClick={() => remove(null)}
+                    > Clear </button>
+                )}
+            </div>
+            <pre className="min-h-[2em]">{response}</pre>
+        </div>
+    );
+};
+
+export default WifiDevicesComp;